@@ -0,0 +1,71 @@
+use scirs2::fft::rfft;
+use std::f64::consts::PI;
+
+// One sinusoid of a fit: amplitude * cos(2 pi frequency t + phase), t in samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Harmonic {
+    // Cycles per sample
+    pub frequency: f64,
+    pub amplitude: f64,
+    // Radians
+    pub phase: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FourierFit {
+    pub mean: f64,
+    // Largest amplitude first
+    pub harmonics: Vec<Harmonic>,
+    // mean + sum of the harmonics at each sample of the input
+    pub reconstruction: Vec<f64>,
+}
+
+// Least-squares fit of the mean plus n_harmonics sinusoids on the FFT bin
+// frequencies. The bins are orthogonal over the record, so keeping the largest
+// ones minimizes the residual among fits restricted to those frequencies.
+pub fn fit_fourier(data: &[f64], n_harmonics: usize) -> Result<FourierFit, String> {
+    let n = data.len();
+    if n < 2 {
+        return Err(format!("Fourier fit needs at least 2 samples, got {n}"));
+    }
+    if data.iter().any(|v| !v.is_finite()) {
+        return Err(String::from(
+            "Fourier fit needs finite samples; fill or drop gaps first",
+        ));
+    }
+    let spectrum = match rfft(data, None) {
+        Ok(s) => s,
+        Err(_) => return Err(String::from("Could not take fft of data")),
+    };
+    let mean = spectrum[0].re / n as f64;
+
+    let mut bins: Vec<usize> = (1..spectrum.len()).collect();
+    bins.sort_by(|&i, &j| spectrum[j].norm().total_cmp(&spectrum[i].norm()));
+    let harmonics: Vec<Harmonic> = bins
+        .into_iter()
+        .take(n_harmonics)
+        .map(|k| {
+            // The Nyquist bin of an even-length record has no mirror image
+            let scale = if 2 * k == n { 1.0 } else { 2.0 };
+            Harmonic {
+                frequency: k as f64 / n as f64,
+                amplitude: scale * spectrum[k].norm() / n as f64,
+                phase: spectrum[k].arg(),
+            }
+        })
+        .collect();
+
+    let reconstruction = (0..n)
+        .map(|t| {
+            mean + harmonics
+                .iter()
+                .map(|h| h.amplitude * (2.0 * PI * h.frequency * t as f64 + h.phase).cos())
+                .sum::<f64>()
+        })
+        .collect();
+    Ok(FourierFit {
+        mean,
+        harmonics,
+        reconstruction,
+    })
+}
@@ -1,5 +1,10 @@
+pub mod fourier;
 pub mod logic;
 pub mod math;
+pub mod preprocess;
+pub mod regression;
+pub mod smoothing;
+pub mod stats;
 pub mod structures;
 pub mod views;
 use directories::ProjectDirs;
@@ -12,138 +17,1564 @@ use math::{
 use num_complex::Complex;
 
 const DEFAULT_ORDER: usize = 4;
+// Past this the companion-matrix eigenvalues are slow and too inaccurate to trust
+pub const MAX_ORDER: usize = 20;
 const DEFAULT_RIPPLE: f64 = 5.;
+const DEFAULT_POLE_RADIUS: f64 = 0.9;
+const DEFAULT_NOTCH_DEPTH: f64 = 1.0;
 const DEFAULT_ATTENUATION: f64 = 40.;
+const DEFAULT_SMOOTHING_WINDOW: usize = 7;
+pub const DEFAULT_ROOT_TOL: f64 = 1e-3;
 pub const DEFAULT_FILENAME: &str = "fourier_fit_data.json";
+pub const SESSION_FILENAME: &str = "fourier_fit_session.json";
+pub const REFERENCE_SINE_LEN: usize = 512;
+pub const DEFAULT_EXPORT_FILENAME: &str = "fourier_fit_export.csv";
+pub const DEFAULT_SAVED_SESSION_FILENAME: &str = "fourier_fit_saved_session.json";
+pub const DEFAULT_PLOT_FILENAME: &str = "fourier_fit_plot.png";
+pub const DEFAULT_PLOT_SIZE: (u32, u32) = (1600, 900);
+// Largest exported plot side in pixels; a 8192x8192 RGBA buffer is 256 MiB
+pub const MAX_PLOT_SIDE: u32 = 8192;
+// Longest padded FFT; 2^22 complex bins are 64 MiB
+pub const MAX_FFT_LEN: usize = 1 << 22;
+pub const DEFAULT_SWEEP_STEPS: usize = 8;
+// Samples per day unless the user says otherwise
+pub const DEFAULT_SAMPLE_RATE: f64 = 1.;
+// Time unit assumed when the loaded file doesn't name one
+pub const DEFAULT_TIME_UNIT: &str = "days";
+pub const STEP_RESPONSE_LEN: usize = 128;
+pub const NYQUIST_POINTS: usize = 512;
+// Nyquist points further out than this are drawn on the clamp circle
+pub const NYQUIST_MAX_GAIN: f64 = 10.0;
+pub const DEFAULT_FOURIER_HARMONICS: usize = 3;
+pub const DEFAULT_KEEP_BINS: usize = 5;
+// Fewer logged points than this is too short to filter meaningfully
+pub const MIN_FILTER_SAMPLES: usize = 8;
+// Fewest samples the time-series plot will zoom in to
+pub const MIN_ZOOM_SAMPLES: usize = 8;
+pub const MAX_SPECTRAL_PEAKS: usize = 5;
+// Harmonics above the fundamental (2nd through 10th) summed into the THD
+pub const THD_HARMONICS: usize = 9;
+// Autocorrelation runs out to this fraction of the record
+pub const ACF_MAX_LAG_FRAC: f64 = 0.5;
+// Slack on |p| = 1 so float error on the unit circle isn't flagged as unstable
+pub const STABILITY_EPS: f64 = 1e-9;
+// Terms below this fraction of a polynomial's largest coefficient are left out of H(z)
+pub const TF_ZERO_TOL: f64 = 1e-12;
+// Lowest level the dB spectrum and the Bode magnitude are drawn at
+pub const DEFAULT_DB_FLOOR: f64 = -120.0;
+pub const DEFAULT_WELCH_SEGMENT: usize = 64;
+pub const DEFAULT_WELCH_OVERLAP: usize = 32;
+// Peak prominence as a fraction of the tallest non-DC bin
+pub const PEAK_PROMINENCE_FRAC: f64 = 0.05;
+pub const DEFAULT_BOLLINGER_PERIOD: usize = 20;
+pub const DEFAULT_BOLLINGER_K: f64 = 2.0;
+// Moving averages the candle view can overlay, period in candles
+pub const CANDLE_SMAS: [(usize, Color); 2] = [
+    (20, Color::from_rgb8(0xFF, 0xC8, 0x57)),
+    (50, Color::from_rgb8(0x4F, 0xC3, 0xF7)),
+];
 
-#[derive(Default)]
+#[derive(Default, Debug, Clone)]
 pub struct App {
+    // Loaded data after normalization; everything downstream reads this
     pub raw_data: Option<Vec<f64>>,
+    // Loaded data as given, kept so normalization can be switched off
+    pub original_data: Option<Vec<f64>>,
+    pub normalize: structures::filters::NormalizeMode,
+    pub gap_mode: structures::filters::GapMode,
+    // Non-finite samples in original_data that raw_data repaired or dropped
+    pub gaps_repaired: usize,
+    // Dates of raw_data samples when it came from the logged entries
+    pub raw_dates: Option<Vec<chrono::NaiveDate>>,
+    // Dates of original_data samples, trimmed with it into raw_dates
+    pub original_dates: Option<Vec<chrono::NaiveDate>>,
+    // Time unit named by the loaded file, shown on the time-series axis
+    pub axis_unit: Option<String>,
+    pub datasets: Vec<structures::dataset::NamedSeries>,
+    // Samples per day
+    pub fs: f64,
     pub filter: structures::filters::FilterType,
+    pub band: structures::filters::FilterBand,
+    pub edge_handling: structures::filters::EdgeHandling,
+    pub phase: structures::filters::FilterPhase,
+    pub spectrum_window: structures::filters::Window,
+    pub fft_padding: structures::filters::FftPadding,
+    pub detrend: structures::filters::DetrendMode,
+    pub spectrum_scale: structures::filters::SpectrumScale,
+    pub spectrum_mode: structures::filters::SpectrumMode,
+    // Samples per Welch segment and how many consecutive segments share
+    pub welch_segment: usize,
+    pub welch_overlap: usize,
+    pub bode_x_scale: structures::filters::BodeXScale,
+    pub db_floor: f64,
     pub cutoff_freq: f64,
+    pub second_cutoff_freq: f64,
     pub filtered_data: Option<FilterData>,
     pub order: usize,
+    // Lowpass stage order for a bandpass
+    pub lowpass_order: usize,
     pub ripple: f64,
     pub attenuation: f64,
+    // Radius of the all-pass poles, in (0, 1)
+    pub pole_radius: f64,
+    // Fraction of each comb notch removed, in (0, 1]
+    pub notch_depth: f64,
+    // Samples in the moving average / median window
+    pub smoothing_window: usize,
+    // Transfer function used as-is by FilterType::CUSTOM
+    pub custom_b: Vec<f64>,
+    pub custom_a: Vec<f64>,
+    // Frozen stages run in order before the current settings
+    pub stages: Vec<structures::filters::FilterStage>,
     pub poles: Option<Vec<Complex<f64>>>,
+    pub root_tol: f64,
     pub zeros: Option<Vec<Complex<f64>>>,
     pub bode_plot: Option<(Vec<f64>, Vec<f64>)>,
+    pub bode_phase: Option<(Vec<f64>, Vec<f64>)>,
+    // Bode magnitude and phase pinned from an earlier design for A/B comparison
+    pub reference_bode: Option<(Vec<f64>, Vec<f64>)>,
+    pub reference_phase: Option<(Vec<f64>, Vec<f64>)>,
+    pub reference_label: String,
+    // H(e^{jw}) for w in [0, pi], for the Nyquist plot
+    pub nyquist: Option<Vec<Complex<f64>>>,
+    pub bode_range: math::BodeRange,
+    pub step_response: Option<Vec<f64>>,
+    pub impulse_response: Option<Vec<f64>>,
+    pub section_bode: Option<Vec<(Vec<f64>, Vec<f64>)>>,
+    pub center_sweep: Option<Vec<math::SweepStep>>,
     pub data_spectrum: Option<Vec<f64>>,
+    pub raw_spectrum: Option<Vec<f64>>,
+    // Welch PSDs of the filtered and raw series
+    pub data_welch: Option<Vec<f64>>,
+    pub raw_welch: Option<Vec<f64>>,
+    // STFT magnitudes of the raw series, frames over the Welch segment and hop
+    pub spectrogram: Option<Vec<Vec<f64>>>,
+    pub spectrum_peaks: Option<Vec<(usize, f64)>>,
+    // Filtered trend (or mean) plus one sinusoid per spectral peak
+    pub peak_model: Option<Vec<f64>>,
+    // Hilbert envelope of the filtered series, (upper, lower) about its mean
+    pub envelope: Option<(Vec<f64>, Vec<f64>)>,
+    // Autocorrelation of the filtered series by lag, and its first significant peak
+    pub autocorrelation: Option<Vec<f64>>,
+    pub acf_period: Option<usize>,
+    pub fourier_fit: Option<fourier::FourierFit>,
+    // Regression trend over the raw data
+    pub trend: Option<regression::TrendLine>,
+    // Raw data rebuilt from DC and its K largest rfft bins, with that K
+    pub denoised: Option<(usize, Vec<f64>)>,
+    // Period of the raw data's main spectral peak, in samples
+    pub dominant_period: Option<f64>,
     pub candles: Option<Vec<structures::candle::Candle>>,
     pub candle_length: structures::candle::CandleLengths,
+    pub candle_palette: structures::candle::CandlePalette,
+    pub candle_style: structures::candle::CandleStyle,
+    pub candle_source: structures::candle::CandleSource,
+    // Hashes of the inputs behind the current spectra, Bode curves and
+    // candles; a match means the stored result is still good
+    fft_key: Option<u64>,
+    bode_key: Option<u64>,
+    candle_key: Option<u64>,
+    // Bumped whenever the data, overlays, sample rate or frozen stages change,
+    // so a background compute started before that can be recognised as stale
+    input_generation: u64,
 }
 
 impl App {
     pub fn new() -> Self {
         Self {
             raw_data: None,
+            original_data: None,
+            normalize: structures::filters::NormalizeMode::Off,
+            gap_mode: structures::filters::GapMode::LinearInterpolate,
+            gaps_repaired: 0,
+            raw_dates: None,
+            original_dates: None,
+            axis_unit: None,
+            datasets: Vec::new(),
+            fs: DEFAULT_SAMPLE_RATE,
             filter: structures::filters::FilterType::BUTTERWORTH,
+            band: structures::filters::FilterBand::Lowpass,
+            edge_handling: structures::filters::EdgeHandling::Padded,
+            phase: structures::filters::FilterPhase::ZeroPhase,
+            spectrum_window: structures::filters::Window::Rectangular,
+            fft_padding: structures::filters::FftPadding::Off,
+            detrend: structures::filters::DetrendMode::Off,
+            spectrum_scale: structures::filters::SpectrumScale::Linear,
+            spectrum_mode: structures::filters::SpectrumMode::Fft,
+            welch_segment: DEFAULT_WELCH_SEGMENT,
+            welch_overlap: DEFAULT_WELCH_OVERLAP,
+            bode_x_scale: structures::filters::BodeXScale::Log,
+            db_floor: DEFAULT_DB_FLOOR,
             cutoff_freq: NYQUIST_PERIOD,
+            second_cutoff_freq: NYQUIST_PERIOD,
             filtered_data: None,
             order: DEFAULT_ORDER,
+            lowpass_order: DEFAULT_ORDER,
             ripple: DEFAULT_RIPPLE,
+            pole_radius: DEFAULT_POLE_RADIUS,
+            notch_depth: DEFAULT_NOTCH_DEPTH,
             attenuation: DEFAULT_ATTENUATION,
+            smoothing_window: DEFAULT_SMOOTHING_WINDOW,
+            custom_b: Vec::new(),
+            custom_a: Vec::new(),
+            stages: Vec::new(),
             poles: None,
+            root_tol: DEFAULT_ROOT_TOL,
             zeros: None,
             bode_plot: None,
+            bode_phase: None,
+            nyquist: None,
+            reference_bode: None,
+            reference_phase: None,
+            reference_label: String::new(),
+            bode_range: math::BodeRange::full(DEFAULT_SAMPLE_RATE),
+            step_response: None,
+            impulse_response: None,
+            section_bode: None,
+            center_sweep: None,
             data_spectrum: None,
+            raw_spectrum: None,
+            data_welch: None,
+            raw_welch: None,
+            spectrogram: None,
+            spectrum_peaks: None,
+            peak_model: None,
+            envelope: None,
+            autocorrelation: None,
+            acf_period: None,
+            fourier_fit: None,
+            trend: None,
+            denoised: None,
+            dominant_period: None,
             candles: None,
             candle_length: structures::candle::CandleLengths::Weekly,
+            candle_palette: structures::candle::CandlePalette::GreenRed,
+            candle_style: structures::candle::CandleStyle::Standard,
+            candle_source: structures::candle::CandleSource::Raw,
+            fft_key: None,
+            bode_key: None,
+            candle_key: None,
+            input_generation: 0,
         }
     }
 
     pub fn filter(&mut self) -> Result<(), String> {
+        let data = match self.raw_data.as_ref() {
+            Some(v) => preprocess::sanitize_series(v, self.gap_mode)?,
+            None => return Err(String::from("No data set")),
+        };
+        // Everything is computed before any of it is stored, so a failing
+        // dataset or root solve leaves the previous results in place
+        let filtered = self.run_filter(&data)?;
+        let datasets = self
+            .datasets
+            .iter()
+            .map(|d| {
+                preprocess::sanitize_series(&d.data, self.gap_mode)
+                    .and_then(|data| self.run_filter(&data))
+                    .map(|f| f.filtered_data)
+                    .map_err(|e| format!("{}: {e}", d.name))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        // A nonlinear run leaves no transfer function behind
+        let linear = if filtered.a.is_empty() {
+            None
+        } else {
+            let roots = math::iir_zeros_poles_z(&filtered.b, &filtered.a)?;
+            Some((
+                math::step_response(&filtered.b, &filtered.a, STEP_RESPONSE_LEN),
+                math::impulse_response(&filtered.b, &filtered.a, STEP_RESPONSE_LEN),
+                roots,
+            ))
+        };
+
+        self.filtered_data = Some(filtered);
+        for (d, f) in self.datasets.iter_mut().zip(datasets) {
+            d.filtered = Some(f);
+        }
+        match linear {
+            Some((step, impulse, (z, p))) => {
+                self.step_response = Some(step);
+                self.impulse_response = Some(impulse);
+                (self.zeros, self.poles) = (Some(z), Some(p));
+            }
+            None => {
+                self.step_response = None;
+                self.impulse_response = None;
+                (self.zeros, self.poles) = (None, None);
+            }
+        }
+        self.rebuild_candles();
+        Ok(())
+    }
+
+    // Truncated Fourier series over the raw data
+    pub fn fit_fourier(&mut self, n_harmonics: usize) -> Result<(), String> {
         let data = match self.raw_data.as_ref() {
             Some(v) => v,
             None => return Err(String::from("No data set")),
         };
-        self.filtered_data = match self.filter {
-            structures::filters::FilterType::BUTTERWORTH => {
-                Some(butterworth_filter(data, self.cutoff_freq, self.order)?)
+        self.fourier_fit = Some(fourier::fit_fourier(data, n_harmonics)?);
+        Ok(())
+    }
+
+    // Keep DC and the k largest of the n/2 other rfft bins of the raw data
+    pub fn denoise(&mut self, k: usize) -> Result<(), String> {
+        let data = match self.raw_data.as_ref() {
+            Some(v) => v,
+            None => return Err(String::from("No data set")),
+        };
+        let bins = data.len() / 2;
+        if !(1..=bins).contains(&k) {
+            return Err(format!("Keep between 1 and {bins} bins, got {k}"));
+        }
+        self.denoised = Some((k, math::spectral_denoise(data, k)?));
+        Ok(())
+    }
+
+    // Regression trend over the raw data
+    pub fn fit_trend(&mut self, model: structures::filters::TrendModel) -> Result<(), String> {
+        let data = match self.raw_data.as_ref() {
+            Some(v) => v,
+            None => return Err(String::from("No data set")),
+        };
+        self.trend = Some(regression::fit_trend(data, model)?);
+        Ok(())
+    }
+
+    // Move what compute() derived on `job`, a clone taken when Calculate
+    // started, into this app. Settings changed while it ran are kept; the
+    // results are dropped if the data or cascade they came from has changed.
+    pub fn merge_results(&mut self, job: App) -> Result<(), String> {
+        if job.input_generation != self.input_generation {
+            return Err(String::from(
+                "Data changed while calculating, press Calculate again",
+            ));
+        }
+        let spectrum_settings = |a: &App| {
+            (
+                a.spectrum_window,
+                a.fft_padding,
+                a.detrend,
+                a.welch_segment,
+                a.welch_overlap,
+            )
+        };
+        let respectrum = spectrum_settings(&job) != spectrum_settings(self);
+        self.filtered_data = job.filtered_data;
+        (self.zeros, self.poles) = (job.zeros, job.poles);
+        self.step_response = job.step_response;
+        self.impulse_response = job.impulse_response;
+        self.bode_plot = job.bode_plot;
+        self.bode_phase = job.bode_phase;
+        self.nyquist = job.nyquist;
+        self.section_bode = job.section_bode;
+        self.data_spectrum = job.data_spectrum;
+        self.raw_spectrum = job.raw_spectrum;
+        self.data_welch = job.data_welch;
+        self.raw_welch = job.raw_welch;
+        self.spectrogram = job.spectrogram;
+        self.spectrum_peaks = job.spectrum_peaks;
+        self.peak_model = job.peak_model;
+        self.envelope = job.envelope;
+        self.autocorrelation = job.autocorrelation;
+        self.acf_period = job.acf_period;
+        self.candles = job.candles;
+        self.fft_key = job.fft_key;
+        self.bode_key = job.bode_key;
+        self.candle_key = job.candle_key;
+        for (live, done) in self.datasets.iter_mut().zip(job.datasets) {
+            live.filtered = done.filtered;
+            live.spectrum = done.spectrum;
+        }
+        // Catch up with candle and spectrum options changed mid-run; both
+        // are keyed, so this is a no-op when nothing moved
+        self.rebuild_candles();
+        if respectrum && self.filtered_data.is_some() {
+            self.fft_filtered()?;
+            self.fft_raw()?;
+        }
+        Ok(())
+    }
+
+    // Whether the last run has a transfer function; a nonlinear stage anywhere
+    // in the cascade leaves it without coefficients
+    pub fn is_linear(&self) -> bool {
+        self.filtered_data.as_ref().is_none_or(|f| !f.a.is_empty())
+    }
+
+    // Whether every pole sits strictly inside the unit circle, None before
+    // filtering. A pole within STABILITY_EPS of the circle doesn't count.
+    pub fn is_stable(&self) -> Option<bool> {
+        self.poles
+            .as_ref()
+            .map(|ps| ps.iter().all(|p| p.norm() < 1.0 - STABILITY_EPS))
+    }
+
+    // Not stable, but with no pole outside the unit circle either, like the
+    // integrator's pole at z = 1
+    pub fn is_marginally_stable(&self) -> bool {
+        self.is_stable() == Some(false)
+            && self
+                .poles
+                .as_ref()
+                .is_some_and(|ps| ps.iter().all(|p| p.norm() <= 1.0 + STABILITY_EPS))
+    }
+
+    pub fn set_candle_length(&mut self, length: structures::candle::CandleLengths) {
+        self.candle_length = length;
+        // Only refresh candles that are already on screen
+        if self.candles.is_some() {
+            self.rebuild_candles();
+        }
+    }
+
+    pub fn set_candle_style(&mut self, style: structures::candle::CandleStyle) {
+        self.candle_style = style;
+        if self.candles.is_some() {
+            self.rebuild_candles();
+        }
+    }
+
+    // Like the other candle options this only takes effect once filtering
+    // has run, since that is when candles are first built
+    pub fn set_candle_source(&mut self, source: structures::candle::CandleSource) {
+        self.candle_source = source;
+        if self.filtered_data.is_some() {
+            self.rebuild_candles();
+        }
+    }
+
+    // Candles over the selected series at the selected length and style;
+    // filtered candles stay empty until filtering has run
+    fn rebuild_candles(&mut self) {
+        let source = match self.candle_source {
+            structures::candle::CandleSource::Raw => self.raw_data.as_deref(),
+            structures::candle::CandleSource::Filtered => self
+                .filtered_data
+                .as_ref()
+                .map(|f| f.filtered_data.as_slice()),
+        };
+        let key = source.map(|data| {
+            input_key(
+                (self.candle_length, self.candle_style, self.candle_source),
+                &[data],
+            )
+        });
+        if self.candles.is_some() && key.is_some() && key == self.candle_key {
+            return;
+        }
+        self.candles = source.and_then(|data| {
+            let candles =
+                structures::candle::vec_to_candles(data, self.candle_length.into()).ok()?;
+            Some(match self.candle_style {
+                structures::candle::CandleStyle::Standard => candles,
+                structures::candle::CandleStyle::HeikinAshi => {
+                    structures::candle::to_heikin_ashi(&candles)
+                }
+            })
+        });
+        self.candle_key = key;
+    }
+
+    // One stage of the cascade, with the shared edge handling and phase
+    fn apply_stage(
+        &self,
+        stage: &structures::filters::FilterStage,
+        data: &[f64],
+    ) -> Result<FilterData, String> {
+        use structures::filters::{FilterBand, FilterType};
+        let filtered = match (stage.band, stage.filter) {
+            // Cutoff w in (0, 1) of Nyquist sets the pole angle pi * w. Always
+            // causal; forward-backward would cancel the phase it exists to add.
+            (_, FilterType::ALL_PASS) => math::allpass_filter(
+                data,
+                stage.pole_radius,
+                std::f64::consts::PI * stage.cutoff_freq,
+                stage.order,
+                self.edge_handling,
+                structures::filters::FilterPhase::Causal,
+            )?,
+            (_, FilterType::COMB) => {
+                math::comb_filter(data, stage.window, stage.notch_depth, self.phase)?
+            }
+            // Always causal; a zero-phase backward pass would sum the
+            // integrator's output a second time
+            (_, FilterType::DIFFERENTIATOR) => FilterData {
+                filtered_data: math::differentiate(data),
+                b: vec![1.0, -1.0],
+                a: vec![1.0],
+                sos: vec![],
+            },
+            (_, FilterType::INTEGRATOR) => FilterData {
+                filtered_data: math::integrate(data),
+                b: vec![1.0],
+                a: vec![1.0, -1.0],
+                sos: vec![],
+            },
+            // Nonlinear, so there are no coefficients to carry along
+            (FilterBand::Lowpass, FilterType::MEDIAN) => FilterData {
+                filtered_data: smoothing::median_filter(data, stage.window)?,
+                b: vec![],
+                a: vec![],
+                sos: vec![],
+            },
+            (band, FilterType::MEDIAN) => {
+                return Err(format!("A median filter is a smoother, not a {band}"));
+            }
+            (FilterBand::Lowpass, FilterType::MOVING_AVERAGE) => {
+                math::moving_average(data, stage.window, self.edge_handling, self.phase)?
             }
-            structures::filters::FilterType::CHEBYSHEV1 => Some(chebyshev_filter_1(
+            (band, FilterType::MOVING_AVERAGE) => {
+                return Err(format!("A moving average is a lowpass, not a {band}"));
+            }
+            (_, FilterType::CUSTOM) => {
+                math::custom_filter(data, &self.custom_b, &self.custom_a, self.phase)?
+            }
+            (FilterBand::Bandpass, t) if t.is_designed() => math::bandpass_cascade(
                 data,
-                self.cutoff_freq,
-                self.order,
-                self.ripple,
-            )?),
-            structures::filters::FilterType::CHEBYSHEV2 => Some(chebyshev_filter_2(
+                stage.cutoff_freq.min(stage.second_cutoff_freq),
+                stage.cutoff_freq.max(stage.second_cutoff_freq),
+                stage.order,
+                stage.lowpass_order,
+                t,
+                stage.ripple,
+                stage.attenuation,
+                self.edge_handling,
+                self.phase,
+            )?,
+            (FilterBand::Bandpass, t) => {
+                return Err(format!("Bandpass needs a designed filter, not {t}"));
+            }
+            (FilterBand::Bandstop, t) if t.is_designed() => math::bandstop_filter(
+                data,
+                stage.cutoff_freq.min(stage.second_cutoff_freq),
+                stage.cutoff_freq.max(stage.second_cutoff_freq),
+                stage.order,
+                t,
+                stage.ripple,
+                stage.attenuation,
+                self.edge_handling,
+                self.phase,
+            )?,
+            (FilterBand::Bandstop, t) => {
+                return Err(format!("Bandstop needs a designed filter, not {t}"));
+            }
+            (band, FilterType::BUTTERWORTH) => butterworth_filter(
                 data,
-                self.cutoff_freq,
-                self.order,
-                self.attenuation,
-            )?),
+                stage.cutoff_freq,
+                stage.order,
+                band,
+                self.edge_handling,
+                self.phase,
+            )?,
+            (band, FilterType::CHEBYSHEV1) => chebyshev_filter_1(
+                data,
+                stage.cutoff_freq,
+                stage.order,
+                stage.ripple,
+                band,
+                self.edge_handling,
+                self.phase,
+            )?,
+            (band, FilterType::CHEBYSHEV2) => chebyshev_filter_2(
+                data,
+                stage.cutoff_freq,
+                stage.order,
+                stage.attenuation,
+                band,
+                self.edge_handling,
+                self.phase,
+            )?,
+            (band, FilterType::BESSEL) => math::bessel_filter(
+                data,
+                stage.cutoff_freq,
+                stage.order,
+                band,
+                self.edge_handling,
+                self.phase,
+            )?,
+            (band, FilterType::ELLIPTIC) => math::elliptic_filter(
+                data,
+                stage.cutoff_freq,
+                stage.order,
+                stage.ripple,
+                stage.attenuation,
+                band,
+                self.edge_handling,
+                self.phase,
+            )?,
+        };
+        Ok(filtered)
+    }
+
+    // The live settings as a stage, run after any frozen stages
+    pub fn current_stage(&self) -> structures::filters::FilterStage {
+        structures::filters::FilterStage {
+            filter: self.filter,
+            band: self.band,
+            cutoff_freq: self.cutoff_freq,
+            second_cutoff_freq: self.second_cutoff_freq,
+            order: self.order,
+            lowpass_order: self.lowpass_order,
+            ripple: self.ripple,
+            attenuation: self.attenuation,
+            window: self.smoothing_window,
+            pole_radius: self.pole_radius,
+            notch_depth: self.notch_depth,
+        }
+    }
+
+    // Apply the frozen stages and then the current settings to one series
+    fn run_filter(&self, data: &[f64]) -> Result<FilterData, String> {
+        let current = self.current_stage();
+        let mut chained: Option<FilterData> = None;
+        for (k, stage) in self.stages.iter().chain([&current]).enumerate() {
+            let input = chained
+                .as_ref()
+                .map_or(data, |f| f.filtered_data.as_slice());
+            let next = self.apply_stage(stage, input).map_err(|e| {
+                if self.stages.is_empty() {
+                    e
+                } else {
+                    format!("Stage {}: {e}", k + 1)
+                }
+            })?;
+            chained = Some(match chained.take() {
+                Some(prev) => math::chain_filter_data(prev, next),
+                None => next,
+            });
+        }
+        // The current stage always runs, so the chain is never empty
+        let filtered = chained.unwrap();
+        math::check_filtered_output(data, &filtered.filtered_data)?;
+        Ok(filtered)
+    }
+
+    // Snapshot the current data as a named overlay series
+    pub fn add_dataset(&mut self, name: &str) -> Result<(), String> {
+        let data = match self.raw_data.as_ref() {
+            Some(v) if !v.is_empty() => v.clone(),
+            _ => return Err(String::from("No data set")),
+        };
+        let name = if name.trim().is_empty() {
+            format!("Dataset {}", self.datasets.len() + 1)
+        } else {
+            name.trim().to_string()
+        };
+        self.datasets
+            .push(structures::dataset::NamedSeries::new(name, data));
+        self.input_generation += 1;
+        Ok(())
+    }
+
+    // Load each CSV as an overlay named after its file, with gaps and
+    // normalization handled like the main data. Nothing is added unless every
+    // file loads. Returns how many were added.
+    pub fn load_datasets(&mut self, paths: &[PathBuf]) -> Result<usize, String> {
+        let mut loaded = Vec::with_capacity(paths.len());
+        for path in paths {
+            let (values, _) = read_series_csv(path)?;
+            let clean = preprocess::sanitize_series(&values, self.gap_mode)
+                .map_err(|e| format!("{}: {e}", path.display()))?;
+            let name = match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().into_owned(),
+                None => format!("Dataset {}", self.datasets.len() + loaded.len() + 1),
+            };
+            loaded.push(structures::dataset::NamedSeries::new(
+                name,
+                preprocess::normalize(&clean, self.normalize),
+            ));
+        }
+        let n = loaded.len();
+        self.datasets.extend(loaded);
+        self.input_generation += 1;
+        Ok(n)
+    }
+
+    // Freeze the last calculated settings as a stage; the current settings
+    // then run after it
+    pub fn push_stage(&mut self) -> Result<(), String> {
+        if self.filtered_data.is_none() {
+            return Err(String::from("Run Calculate before adding it as a stage"));
+        }
+        // Stages only keep design parameters, not pasted coefficients
+        if self.filter == structures::filters::FilterType::CUSTOM {
+            return Err(String::from("Custom b/a can't be frozen as a stage"));
+        }
+        self.stages.push(self.current_stage());
+        self.input_generation += 1;
+        Ok(())
+    }
+
+    // Keep the current Bode curves as the reference the next designs are drawn against
+    pub fn pin_reference(&mut self) -> Result<(), String> {
+        let Some(bode) = self.bode_plot.as_ref() else {
+            return Err(String::from("Run Calculate before pinning a reference"));
         };
-        (self.zeros, self.poles) = match math::iir_zeros_poles_z(
-            self.filtered_data.as_ref().unwrap().b.as_slice(),
-            self.filtered_data.as_ref().unwrap().a.as_slice(),
-        ) {
-            Ok((z, p)) => (Some(z), Some(p)),
-            Err(s) => return Err(s),
+        self.reference_bode = Some(bode.clone());
+        self.reference_phase = self.bode_phase.clone();
+        self.reference_label = if self.stages.is_empty() {
+            self.current_stage().to_string()
+        } else {
+            format!("{}-stage cascade", self.stages.len() + 1)
         };
-        self.candles = structures::candle::vec_to_candles(
-            self.raw_data.as_deref().unwrap(),
-            self.candle_length.into(),
-        )
-        .ok();
         Ok(())
     }
 
+    pub fn clear_reference(&mut self) {
+        self.reference_bode = None;
+        self.reference_phase = None;
+        self.reference_label.clear();
+    }
+
+    pub fn remove_stage(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.stages.len() {
+            return Err(format!("No stage {}", index + 1));
+        }
+        self.stages.remove(index);
+        self.input_generation += 1;
+        Ok(())
+    }
+
+    pub fn clear_datasets(&mut self) {
+        self.datasets.clear();
+        self.datasets.shrink_to_fit();
+        self.input_generation += 1;
+    }
+
     pub fn set_filter_type(&mut self, t: structures::filters::FilterType) {
         self.filter = t;
     }
+    pub fn set_band(&mut self, b: structures::filters::FilterBand) {
+        self.band = b;
+    }
+    pub fn set_edge_handling(&mut self, e: structures::filters::EdgeHandling) {
+        self.edge_handling = e;
+    }
+    pub fn set_phase(&mut self, p: structures::filters::FilterPhase) {
+        self.phase = p;
+    }
+    pub fn set_spectrum_window(&mut self, w: structures::filters::Window) {
+        self.spectrum_window = w;
+    }
+    pub fn set_fft_padding(&mut self, p: structures::filters::FftPadding) {
+        self.fft_padding = p;
+    }
+    pub fn set_detrend(&mut self, d: structures::filters::DetrendMode) {
+        self.detrend = d;
+    }
+    // Re-derive raw_data from the untouched original under the new mode
+    pub fn set_normalize(&mut self, mode: structures::filters::NormalizeMode) {
+        self.normalize = mode;
+        self.derive_raw_data();
+        if self.candles.is_some() {
+            self.rebuild_candles();
+        }
+    }
+    pub fn set_gap_mode(&mut self, mode: structures::filters::GapMode) {
+        self.gap_mode = mode;
+        self.derive_raw_data();
+        if self.candles.is_some() {
+            self.rebuild_candles();
+        }
+    }
+
+    // raw_data is the original with gaps handled, then normalized. When the
+    // gap mode rejects the data it is left unrepaired and filter() reports why.
+    fn derive_raw_data(&mut self) {
+        self.input_generation += 1;
+        self.fourier_fit = None;
+        self.trend = None;
+        self.denoised = None;
+        let Some(original) = self.original_data.as_deref() else {
+            self.raw_data = None;
+            self.raw_dates = None;
+            self.gaps_repaired = 0;
+            self.dominant_period = None;
+            return;
+        };
+        let (clean, repaired) = match preprocess::sanitize_series(original, self.gap_mode) {
+            Ok(clean) => {
+                let repaired = original.iter().filter(|x| !x.is_finite()).count();
+                (clean, repaired)
+            }
+            Err(_) => (original.to_vec(), 0),
+        };
+        // Dropping leading/trailing gaps shortens the series; keep the dates
+        // of the samples that survived so the axis still lines up
+        let lead = match self.gap_mode {
+            structures::filters::GapMode::DropLeadingTrailing if clean.len() < original.len() => {
+                original.iter().position(|x| x.is_finite()).unwrap_or(0)
+            }
+            _ => 0,
+        };
+        self.raw_dates = self
+            .original_dates
+            .as_ref()
+            .and_then(|d| d.get(lead..lead + clean.len()))
+            .map(<[_]>::to_vec);
+        let raw = preprocess::normalize(&clean, self.normalize);
+        self.dominant_period = math::estimate_dominant_period(&raw, 1.0);
+        self.raw_data = Some(raw);
+        self.gaps_repaired = repaired;
+    }
+    pub fn set_spectrum_scale(&mut self, s: structures::filters::SpectrumScale) {
+        self.spectrum_scale = s;
+    }
+    pub fn set_spectrum_mode(&mut self, m: structures::filters::SpectrumMode) {
+        self.spectrum_mode = m;
+    }
+    pub fn set_welch(&mut self, segment: usize, overlap: usize) -> Result<(), String> {
+        if segment < 2 {
+            return Err(format!(
+                "Welch segment must be at least 2 samples, got {segment}"
+            ));
+        }
+        if overlap >= segment {
+            return Err(format!(
+                "Welch overlap ({overlap}) must be shorter than the segment ({segment})"
+            ));
+        }
+        (self.welch_segment, self.welch_overlap) = (segment, overlap);
+        Ok(())
+    }
+    // Records shorter than a segment fall back to a single segment over all of it
+    fn welch(&self, data: &[f64]) -> Result<Vec<f64>, String> {
+        let segment = self.welch_segment.min(data.len());
+        let overlap = self.welch_overlap.min(segment.saturating_sub(1));
+        math::welch_psd(data, segment, overlap, self.spectrum_window)
+    }
+    // Goertzel magnitude of the detrended filtered series at freq (cycles/day),
+    // on the same scale as the unwindowed rfft bins
+    pub fn goertzel_at(&self, freq: f64) -> Result<f64, String> {
+        let nyquist = self.fs / 2.0;
+        if !(freq.is_finite() && (0.0..=nyquist).contains(&freq)) {
+            return Err(format!(
+                "Frequency must be between 0 and {} {}",
+                fmt_tick(nyquist),
+                per_unit("cycles", self.time_unit())
+            ));
+        }
+        let data = self
+            .filtered_data
+            .as_ref()
+            .ok_or_else(|| String::from("Calculate a filter first"))?;
+        let base = math::detrend(&data.filtered_data, self.detrend);
+        Ok(math::goertzel(&base, freq, self.fs))
+    }
+    // Unit of the time axis, as the loaded file names it
+    pub fn time_unit(&self) -> &str {
+        self.axis_unit.as_deref().unwrap_or(DEFAULT_TIME_UNIT)
+    }
+    // Data, sample rate and axis unit from a JSON series
+    pub fn set_series(&mut self, series: structures::dataset::SeriesData) -> Result<(), String> {
+        self.set_fs(series.fs)?;
+        self.set_app_data(series.values);
+        self.axis_unit = series.unit;
+        Ok(())
+    }
+    pub fn set_db_floor(&mut self, floor: f64) -> Result<(), String> {
+        if !floor.is_finite() {
+            return Err(format!("dB floor must be finite, got {floor}"));
+        }
+        self.db_floor = floor;
+        Ok(())
+    }
     pub fn set_cutoff(&mut self, v: f64) {
         self.cutoff_freq = v;
     }
+    pub fn set_second_cutoff(&mut self, v: f64) {
+        self.second_cutoff_freq = v;
+    }
     pub fn set_order(&mut self, v: usize) {
         self.order = v;
     }
+    pub fn set_lowpass_order(&mut self, v: usize) {
+        self.lowpass_order = v;
+    }
+    pub fn set_smoothing_window(&mut self, v: usize) {
+        self.smoothing_window = v;
+    }
+    pub fn set_custom_coefficients(&mut self, b: Vec<f64>, a: Vec<f64>) {
+        self.custom_b = b;
+        self.custom_a = a;
+    }
     pub fn set_ripple(&mut self, v: f64) {
         self.ripple = v;
     }
+    pub fn set_pole_radius(&mut self, v: f64) {
+        self.pole_radius = v;
+    }
+    pub fn set_notch_depth(&mut self, v: f64) {
+        self.notch_depth = v;
+    }
     pub fn set_attenuation(&mut self, v: f64) {
         self.attenuation = v;
     }
+    pub fn set_root_tol(&mut self, v: f64) {
+        self.root_tol = v;
+    }
 
     pub fn set_app_data(&mut self, data: Vec<f64>) {
-        self.raw_data = Some(data);
+        self.original_data = Some(data);
+        self.original_dates = None;
+        self.axis_unit = None;
+        self.derive_raw_data();
+    }
+
+    // Load date-ordered entries, keeping the dates alongside the values.
+    // Returns a warning when there are too few points to filter.
+    pub fn set_dated_data(&mut self, entries: Vec<(chrono::NaiveDate, f64)>) -> Option<String> {
+        let (dates, values): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+        let n = values.len();
+        self.original_data = Some(values);
+        self.original_dates = Some(dates);
+        self.axis_unit = None;
+        self.derive_raw_data();
+        if n < MIN_FILTER_SAMPLES {
+            Some(format!(
+                "Only {n} data point(s) loaded, filtering needs at least {MIN_FILTER_SAMPLES}"
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn load_reference_sine(&mut self, freq: f64, amplitude: f64) -> Result<(), String> {
+        let nyquist_freq = self.fs / NYQUIST_PERIOD;
+        if !(freq > 0. && freq < nyquist_freq) {
+            return Err(format!(
+                "Sine frequency must be between 0 and the nyquist frequency of {nyquist_freq}"
+            ));
+        }
+        if !amplitude.is_finite() {
+            return Err(String::from("Sine amplitude must be finite"));
+        }
+        self.set_app_data(preprocess::generate_sine(
+            freq,
+            amplitude,
+            0.,
+            REFERENCE_SINE_LEN,
+            self.fs,
+        ));
+        Ok(())
     }
 
     pub fn fft_filtered(&mut self) -> Result<(), String> {
         if let Some(data) = &self.filtered_data {
-            self.data_spectrum = Some(math::rfft_mag(&data.filtered_data)?);
+            let window = self.spectrum_window;
+            let padding = self.fft_padding;
+            let detrend = self.detrend;
+            let mut inputs = vec![data.filtered_data.as_slice()];
+            inputs.extend(self.datasets.iter().map(|d| d.display_data()));
+            let welch = (self.welch_segment, self.welch_overlap);
+            let key = input_key(
+                (window, padding, detrend, self.fs.to_bits(), welch),
+                &inputs,
+            );
+            let have_all = self.data_spectrum.is_some()
+                && self.data_welch.is_some()
+                && self.datasets.iter().all(|d| d.spectrum.is_some());
+            if have_all && self.fft_key == Some(key) {
+                return Ok(());
+            }
+            // Invalid until every spectrum below is written
+            self.fft_key = None;
+            let base = math::detrend(&data.filtered_data, detrend);
+            let spectrum = math::rfft_mag_windowed(&base, window, padding)?;
+            let tallest = spectrum.iter().skip(1).copied().fold(0.0, f64::max);
+            let peaks = math::find_spectral_peaks(
+                &spectrum,
+                MAX_SPECTRAL_PEAKS,
+                PEAK_PROMINENCE_FRAC * tallest,
+            );
+            // Phases from the untapered spectrum so the model lines up in time
+            let n = base.len();
+            let n_fft = math::fft_len(n, padding);
+            let bins = math::rfft_complex(&math::pad_to(&base, n_fft))?;
+            let phases: Vec<f64> = peaks.iter().map(|&(k, _)| bins[k].arg()).collect();
+            let mean = bins.first().map_or(0.0, |b| b.re / n as f64);
+            // Magnitudes are sums over the n real samples, rescale to the
+            // padded length the reconstruction divides by
+            let scaled: Vec<(usize, f64)> = peaks
+                .iter()
+                .map(|&(k, m)| (k, m * n_fft as f64 / n as f64))
+                .collect();
+            // Add back whatever trend detrending took out
+            self.peak_model = Some(
+                math::reconstruct_from_peaks(&scaled, &phases, n_fft, self.fs)
+                    .into_iter()
+                    .zip(data.filtered_data.iter().zip(&base))
+                    .map(|(y, (orig, b))| y + mean + (orig - b))
+                    .collect(),
+            );
+            self.spectrum_peaks = Some(peaks);
+            let center = stats::series_stats(&data.filtered_data).mean;
+            let centered: Vec<f64> = data.filtered_data.iter().map(|y| y - center).collect();
+            let amplitude = math::envelope(&centered)?;
+            let n = data.filtered_data.len();
+            // A flat series has no autocorrelation; that shouldn't fail the spectra
+            let acf =
+                math::autocorrelation(&data.filtered_data, (ACF_MAX_LAG_FRAC * n as f64) as usize)
+                    .ok();
+            self.acf_period = acf.as_ref().and_then(|acf| math::acf_period(acf, n));
+            self.autocorrelation = acf;
+            self.envelope = Some((
+                amplitude.iter().map(|a| center + a).collect(),
+                amplitude.iter().map(|a| center - a).collect(),
+            ));
+            self.data_spectrum = Some(spectrum);
+            self.data_welch = Some(self.welch(&base)?);
+            for series in self.datasets.iter_mut() {
+                series.spectrum = Some(math::rfft_mag_windowed(
+                    &math::detrend(series.display_data(), detrend),
+                    window,
+                    padding,
+                )?);
+            }
+            self.fft_key = Some(key);
             Ok(())
         } else {
             Err(String::from("Filtering not complete"))
         }
     }
 
+    // Spectrum of the unfiltered data with the same window, for before/after
+    pub fn fft_raw(&mut self) -> Result<(), String> {
+        let data = match self.raw_data.as_ref() {
+            Some(v) => v,
+            None => return Err(String::from("No data set")),
+        };
+        let base = math::detrend(data, self.detrend);
+        self.raw_spectrum = Some(math::rfft_mag_windowed(
+            &base,
+            self.spectrum_window,
+            self.fft_padding,
+        )?);
+        self.raw_welch = Some(self.welch(&base)?);
+        let window = self.welch_segment.min(base.len());
+        let hop = window - self.welch_overlap.min(window.saturating_sub(1));
+        self.spectrogram = Some(math::stft(&base, window, hop)?);
+        Ok(())
+    }
+
+    // Everything Calculate runs; cheap to clone so the GUI can do it off-thread
+    pub fn compute(&mut self) -> Result<(), String> {
+        self.filter()?;
+        self.fft_filtered()?;
+        self.fft_raw()?;
+        self.generate_bode()
+    }
+
     pub fn generate_bode(&mut self) -> Result<(), String> {
+        if self.filtered_data.is_some() && !self.is_linear() {
+            self.bode_plot = None;
+            self.bode_phase = None;
+            self.nyquist = None;
+            self.section_bode = None;
+            return Ok(());
+        }
         if let Some(data) = &self.filtered_data {
-            self.bode_plot = Some(math::bode_mag_logspace(&data.b, &data.a, 1., 100));
+            let range = self.bode_range;
+            let fs = self.fs;
+            let sections: Vec<f64> = data
+                .sos
+                .iter()
+                .flat_map(|s| s.b.into_iter().chain(s.a))
+                .collect();
+            let key = input_key(
+                (
+                    fs.to_bits(),
+                    range.f_min.to_bits(),
+                    range.f_max.to_bits(),
+                    range.n_points,
+                    self.db_floor.to_bits(),
+                ),
+                &[&data.b, &data.a, &sections],
+            );
+            if self.bode_plot.is_some() && self.bode_key == Some(key) {
+                return Ok(());
+            }
+            self.bode_key = None;
+            self.bode_plot = Some(math::bode_mag_db_logspace(
+                &data.b,
+                &data.a,
+                fs,
+                &range,
+                self.db_floor,
+            )?);
+            self.bode_phase = Some(math::bode_phase_logspace(&data.b, &data.a, fs, &range)?);
+            self.nyquist = Some(math::nyquist_curve(&data.b, &data.a, NYQUIST_POINTS));
+            self.section_bode = Some(self.section_responses(fs, &range)?);
+            self.bode_key = Some(key);
             return Ok(());
         }
         Err(String::from("Filtering not complete"))
     }
+
+    // Octave-wide bandpass responses for centers spaced geometrically over
+    // f_lo..f_hi (cycles/day), as (center, freqs, mag_db) per step
+    pub fn sweep_center(
+        &self,
+        f_lo: f64,
+        f_hi: f64,
+        steps: usize,
+    ) -> Result<Vec<math::SweepStep>, String> {
+        let nyquist_freq = self.fs / NYQUIST_PERIOD;
+        // Each band reaches half an octave past its center, so the top
+        // center's upper edge has to stay below Nyquist too
+        let half_band = std::f64::consts::SQRT_2;
+        if !(f_lo > 0. && f_hi * half_band < nyquist_freq) {
+            return Err(format!(
+                "Sweep must stay within (0, {}) {} so the top band's upper edge is below Nyquist",
+                fmt_tick(nyquist_freq / half_band),
+                per_unit("cycles", self.time_unit())
+            ));
+        }
+        if f_lo >= f_hi {
+            return Err(String::from("Sweep start must be below sweep end"));
+        }
+        if steps < 2 {
+            return Err(String::from("Sweep requires at least 2 steps"));
+        }
+        let ratio = (f_hi / f_lo).powf(1. / (steps - 1) as f64);
+        (0..steps)
+            .map(|i| {
+                let center = f_lo * ratio.powi(i as i32);
+                // Band edges in cycles per sample
+                let (b, a) = math::butterworth_bandpass_ba(
+                    self.order,
+                    center / half_band / self.fs,
+                    center * half_band / self.fs,
+                )?;
+                let (freqs, mag_db) =
+                    math::bode_mag_db_logspace(&b, &a, self.fs, &self.bode_range, self.db_floor)?;
+                Ok((center, freqs, mag_db))
+            })
+            .collect()
+    }
+
+    // Magnitude response of each second-order section on its own
+    pub fn section_responses(
+        &self,
+        fs: f64,
+        range: &math::BodeRange,
+    ) -> Result<Vec<math::FreqResponse>, String> {
+        match &self.filtered_data {
+            Some(data) => data
+                .sos
+                .iter()
+                .map(|s| math::bode_mag_logspace(&s.b, &s.a, fs, range))
+                .collect(),
+            None => Ok(vec![]),
+        }
+    }
+
+    // Write index,raw,filtered to `path` and bin,frequency,magnitude to a
+    // sibling "<stem>_spectrum.csv". Returns both paths.
+    pub fn export_csv(&self, path: &std::path::Path) -> Result<(PathBuf, PathBuf), String> {
+        use std::fmt::Write;
+        let (raw, data) = match (self.raw_data.as_ref(), self.filtered_data.as_ref()) {
+            (Some(r), Some(d)) => (r, d),
+            _ => return Err(String::from("Nothing to export, run Calculate first")),
+        };
+        let spectrum = self
+            .data_spectrum
+            .as_ref()
+            .ok_or("Spectrum not computed, run Calculate first")?;
+
+        let mut series = String::from("index,raw,filtered\n");
+        for (i, r) in raw.iter().enumerate() {
+            let f = data
+                .filtered_data
+                .get(i)
+                .map_or(String::new(), f64::to_string);
+            let _ = writeln!(series, "{i},{r},{f}");
+        }
+        // Bin k of an n-point rfft sits at k*fs/n cycles per time unit
+        let n = math::fft_len(data.filtered_data.len(), self.fft_padding).max(1) as f64;
+        let mut bins = String::from("bin,frequency,magnitude\n");
+        for (k, m) in spectrum.iter().enumerate() {
+            let _ = writeln!(bins, "{k},{},{m}", k as f64 * self.fs / n);
+        }
+
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or(format!("Invalid export path {}", path.display()))?;
+        let spectrum_path = path.with_file_name(format!("{stem}_spectrum.csv"));
+        std::fs::write(path, series).map_err(|e| format!("{}: {e}", path.display()))?;
+        std::fs::write(&spectrum_path, bins)
+            .map_err(|e| format!("{}: {e}", spectrum_path.display()))?;
+        Ok((path.to_path_buf(), spectrum_path))
+    }
+
+    // Back to the App::new() filter design, keeping the loaded data, sample
+    // rate and spectrum settings
+    pub fn reset_params(&mut self) {
+        let defaults = App::new();
+        self.filter = defaults.filter;
+        self.band = defaults.band;
+        self.edge_handling = defaults.edge_handling;
+        self.phase = defaults.phase;
+        self.cutoff_freq = defaults.cutoff_freq;
+        self.second_cutoff_freq = defaults.second_cutoff_freq;
+        self.order = defaults.order;
+        self.lowpass_order = defaults.lowpass_order;
+        self.ripple = defaults.ripple;
+        self.attenuation = defaults.attenuation;
+        self.pole_radius = defaults.pole_radius;
+        self.notch_depth = defaults.notch_depth;
+        self.smoothing_window = defaults.smoothing_window;
+        self.custom_b = defaults.custom_b;
+        self.custom_a = defaults.custom_a;
+        self.stages = defaults.stages;
+        self.root_tol = defaults.root_tol;
+        self.input_generation += 1;
+    }
+
+    // Write the user-facing inputs and the loaded data to `path` as JSON.
+    // Derived results are not saved; they come back from compute() on load.
+    pub fn save_session(&self, path: &std::path::Path) -> Result<(), String> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Could not create {}: {e}", path.display()))?;
+        use structures::filters::SessionKey;
+        let dates = self
+            .original_dates
+            .as_ref()
+            .map(|d| d.iter().map(|d| d.to_string()).collect::<Vec<_>>());
+        let session = serde_json::json!({
+            "filter": self.filter.key(),
+            "band": self.band.key(),
+            "edge_handling": self.edge_handling.key(),
+            "phase": self.phase.key(),
+            "normalize": self.normalize.key(),
+            "gap_mode": self.gap_mode.key(),
+            "fs": self.fs,
+            "unit": self.axis_unit,
+            "cutoff_freq": self.cutoff_freq,
+            "second_cutoff_freq": self.second_cutoff_freq,
+            "order": self.order,
+            "lowpass_order": self.lowpass_order,
+            "ripple": self.ripple,
+            "attenuation": self.attenuation,
+            "pole_radius": self.pole_radius,
+            "notch_depth": self.notch_depth,
+            "smoothing_window": self.smoothing_window,
+            "custom_b": self.custom_b,
+            "custom_a": self.custom_a,
+            "candle_length": usize::from(self.candle_length),
+            // NaN gaps serialize as null
+            "data": self.original_data,
+            "dates": dates,
+        });
+        serde_json::to_writer_pretty(file, &session)
+            .map_err(|e| format!("Could not write {}: {e}", path.display()))
+    }
+
+    // Read a file written by save_session. Missing keys keep their defaults;
+    // derived fields stay empty until compute() runs, which should only
+    // follow a passing validate_inputs() since the file may be hand-edited.
+    pub fn load_session(path: &std::path::Path) -> Result<App, String> {
+        use serde_json::Value;
+        use structures::filters::{
+            EdgeHandling, FilterBand, FilterPhase, FilterType, GapMode, NormalizeMode, SessionKey,
+        };
+        let file = std::fs::File::open(path)
+            .map_err(|e| format!("Could not open {}: {e}", path.display()))?;
+        let v: Value = serde_json::from_reader(file)
+            .map_err(|e| format!("Could not parse {}: {e}", path.display()))?;
+        let mut app = App::new();
+
+        let number = |key: &str| -> Result<Option<f64>, String> {
+            match v.get(key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(x) => x.as_f64().map(Some).ok_or(format!("{key} is not a number")),
+            }
+        };
+        let count = |key: &str| -> Result<Option<usize>, String> {
+            match v.get(key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(x) => x
+                    .as_u64()
+                    .map(|n| Some(n as usize))
+                    .ok_or(format!("{key} is not a whole number")),
+            }
+        };
+        let numbers = |key: &str| -> Result<Option<Vec<f64>>, String> {
+            match v.get(key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(Value::Array(xs)) => xs
+                    .iter()
+                    .map(|x| match x {
+                        Value::Null => Ok(f64::NAN),
+                        x => x.as_f64().ok_or(format!("{key} holds a non-number")),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Some),
+                Some(_) => Err(format!("{key} is not a list")),
+            }
+        };
+        // Enums are stored by their session key; older files used the
+        // display label
+        fn label<T: Copy + std::fmt::Display + SessionKey>(
+            v: &Value,
+            key: &str,
+            all: &[T],
+        ) -> Result<Option<T>, String> {
+            match v.get(key).and_then(Value::as_str) {
+                None => Ok(None),
+                Some(s) => all
+                    .iter()
+                    .find(|t| t.key() == s)
+                    .or_else(|| all.iter().find(|t| t.to_string() == s))
+                    .copied()
+                    .map(Some)
+                    .ok_or(format!("Unknown {key} '{s}'")),
+            }
+        }
+
+        if let Some(t) = label(&v, "filter", &FilterType::ALL)? {
+            app.filter = t;
+        }
+        if let Some(b) = label(&v, "band", &FilterBand::ALL)? {
+            app.band = b;
+        }
+        if let Some(e) = label(&v, "edge_handling", &EdgeHandling::ALL)? {
+            app.edge_handling = e;
+        }
+        if let Some(p) = label(&v, "phase", &FilterPhase::ALL)? {
+            app.phase = p;
+        }
+        if let Some(m) = label(&v, "normalize", &NormalizeMode::ALL)? {
+            app.normalize = m;
+        }
+        if let Some(g) = label(&v, "gap_mode", &GapMode::ALL)? {
+            app.gap_mode = g;
+        }
+        if let Some(fs) = number("fs")? {
+            app.set_fs(fs)?;
+        }
+        app.cutoff_freq = number("cutoff_freq")?.unwrap_or(app.cutoff_freq);
+        app.second_cutoff_freq = number("second_cutoff_freq")?.unwrap_or(app.second_cutoff_freq);
+        app.order = count("order")?.unwrap_or(app.order);
+        app.lowpass_order = count("lowpass_order")?.unwrap_or(app.lowpass_order);
+        app.ripple = number("ripple")?.unwrap_or(app.ripple);
+        app.attenuation = number("attenuation")?.unwrap_or(app.attenuation);
+        app.pole_radius = number("pole_radius")?.unwrap_or(app.pole_radius);
+        app.notch_depth = number("notch_depth")?.unwrap_or(app.notch_depth);
+        app.smoothing_window = count("smoothing_window")?.unwrap_or(app.smoothing_window);
+        app.custom_b = numbers("custom_b")?.unwrap_or_default();
+        app.custom_a = numbers("custom_a")?.unwrap_or_default();
+        app.candle_length = match count("candle_length")? {
+            None => app.candle_length,
+            Some(7) => structures::candle::CandleLengths::Weekly,
+            Some(30) => structures::candle::CandleLengths::Monthly,
+            Some(365) => structures::candle::CandleLengths::Yearly,
+            Some(n) => structures::candle::CandleLengths::Custom(n),
+        };
+
+        let dates = match v.get("dates") {
+            Some(Value::Array(ds)) => Some(
+                ds.iter()
+                    .map(|d| {
+                        d.as_str()
+                            .and_then(|d| d.parse::<chrono::NaiveDate>().ok())
+                            .ok_or(format!("Bad date {d}"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            _ => None,
+        };
+        if let Some(data) = numbers("data")? {
+            match dates {
+                Some(dates) if dates.len() == data.len() => {
+                    app.set_dated_data(dates.into_iter().zip(data).collect());
+                }
+                _ => app.set_app_data(data),
+            }
+            if let Some(Value::String(u)) = v.get("unit") {
+                app.axis_unit = Some(u.clone());
+            }
+        }
+        Ok(app)
+    }
+
+    // The range checks Calculate applies to typed inputs, for settings that
+    // arrive another way
+    pub fn validate_inputs(&self) -> Result<(), String> {
+        if self.filter.is_designed() {
+            // Back to a period so the cutoffs get Calculate's exact check
+            let check_cutoff =
+                |w: f64| math::cutoff_period_to_nyquist(NYQUIST_PERIOD / (w * self.fs), self.fs);
+            check_cutoff(self.cutoff_freq)?;
+            if self.band.uses_second_cutoff() {
+                check_cutoff(self.second_cutoff_freq)?;
+            }
+            check_order(self.filter, "order", self.order)?;
+            if self.band == structures::filters::FilterBand::Bandpass {
+                check_order(self.filter, "lowpass order", self.lowpass_order)?;
+            }
+        }
+        if self.filter.uses_pole_radius() {
+            check_pole_radius(self.pole_radius)?;
+        }
+        if self.filter.uses_notch_depth() {
+            check_notch_depth(self.notch_depth)?;
+        }
+        if self.filter == structures::filters::FilterType::COMB {
+            check_comb_period(self.smoothing_window)?;
+        }
+        if self.candle_length == structures::candle::CandleLengths::Custom(0) {
+            return Err(String::from("candle width must be at least 1"));
+        }
+        Ok(())
+    }
+
+    // Samples per day. The Bode window goes back to the full range since the
+    // old one may sit above the new Nyquist.
+    pub fn set_fs(&mut self, fs: f64) -> Result<(), String> {
+        if !(fs.is_finite() && fs > 0.) {
+            return Err(format!("Sample rate must be positive, got {fs}"));
+        }
+        self.fs = fs;
+        self.bode_range = math::BodeRange::full(fs);
+        self.input_generation += 1;
+        Ok(())
+    }
+
+    // Frequency window (cycles/day) for the Bode plots
+    pub fn set_bode_range(&mut self, f_min: f64, f_max: f64) -> Result<(), String> {
+        let range = math::BodeRange {
+            f_min,
+            f_max,
+            ..self.bode_range
+        };
+        range.validate(self.fs)?;
+        self.bode_range = range;
+        Ok(())
+    }
+}
+
+// Hash of some settings plus the exact bits of the sample slices they act on
+fn input_key<T: std::hash::Hash>(settings: T, samples: &[&[f64]]) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::hash::DefaultHasher::new();
+    settings.hash(&mut hasher);
+    for xs in samples {
+        xs.len().hash(&mut hasher);
+        xs.iter().for_each(|x| x.to_bits().hash(&mut hasher));
+    }
+    hasher.finish()
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     FilterChanged(structures::filters::FilterType),
+    BandChanged(structures::filters::FilterBand),
+    EdgeHandlingChanged(structures::filters::EdgeHandling),
+    FilterPhaseChanged(structures::filters::FilterPhase),
+    HarmonicsChanged(String),
+    FitFourier,
+    TrendModelChanged(structures::filters::TrendModel),
+    FitTrend,
+    KeepBinsChanged(u32),
+    Denoise,
+    ShowPeakModelToggled(bool),
+    ShowEnvelopeToggled(bool),
+    TimeLogYToggled(bool),
+    FftPaddingChanged(structures::filters::FftPadding),
+    FftPadLengthChanged(String),
+    DetrendChanged(structures::filters::DetrendMode),
+    SampleRateChanged(String),
+    SmoothingWindowChanged(String),
+    CustomBChanged(String),
+    CustomAChanged(String),
+    AddStage,
+    PinReference,
+    ClearReference,
+    NormalizeChanged(structures::filters::NormalizeMode),
+    GapModeChanged(structures::filters::GapMode),
+    RemoveStage(usize),
+    WindowChanged(structures::filters::Window),
+    SpectrumScaleChanged(structures::filters::SpectrumScale),
+    SpectrumModeChanged(structures::filters::SpectrumMode),
+    WelchSegmentChanged(String),
+    WelchOverlapChanged(String),
+    BodeXScaleChanged(structures::filters::BodeXScale),
+    DbFloorChanged(String),
+    GoertzelFreqChanged(String),
     CutoffChanged(String),
+    SecondCutoffChanged(String),
     OrderChanged(String),
+    LowpassOrderChanged(String),
     RippleChanged(String),
+    PoleRadiusChanged(String),
+    NotchDepthChanged(String),
     AttenuationChanged(String),
-    LoadDemo,
+    RootTolChanged(String),
+    SineFreqChanged(String),
+    SineAmplitudeChanged(String),
+    LoadSine,
+    SweepLoChanged(String),
+    BodeMinChanged(String),
+    BodeMaxChanged(String),
+    SweepHiChanged(String),
+    SweepStepsChanged(String),
+    RunSweep,
     Calculate,
+    // The app that ran the job, kept even on error for its partial results
+    ComputationDone(Box<App>, Result<(), String>),
     ClearOutput,
+    ResetParams,
+    ShowSectionsToggled(bool),
+    ExplainToggled(bool),
     CandleLengthsChanged(structures::candle::CandleLengths),
     OpenDataModal,
+    UseModalData,
     CloseDataModal,
     WeightSelectionChanged(String),
     NoOp,
     UpdateDate(iced_aw::date_picker::Date),
     SaveWeightSelection,
+    DeleteWeightSelection,
+    ClearWeightLog,
+    ConfirmClearWeightLog(bool),
+    BrowseLogFile,
+    // None when the dialog was cancelled
+    LogFilePicked(Option<PathBuf>),
+    WindowResized(iced::Size),
+    FileDropped(PathBuf),
+    // Enter or Escape that no focused widget used
+    KeyPressed(iced::keyboard::key::Named),
+    CustomCandleChanged(String),
+    CandlePaletteChanged(structures::candle::CandlePalette),
+    CandleStyleChanged(structures::candle::CandleStyle),
+    CandleSourceChanged(structures::candle::CandleSource),
+    CandleSmaToggled(usize, bool),
+    BollingerToggled(bool),
+    BollingerPeriodChanged(String),
+    BollingerKChanged(String),
+    StopEdgeChanged(String),
+    StopAttenChanged(String),
+    EstimateOrder,
+    DatasetNameChanged(String),
+    AddDataset,
+    LoadDatasets,
+    DatasetFilesPicked(Option<Vec<PathBuf>>),
+    ClearDatasets,
+    CopyCoefficients,
+    ExportCsv,
+    CsvPathPicked(Option<PathBuf>),
+    SessionNameChanged(String),
+    SaveSession,
+    LoadSession,
+    SessionPathPicked(Option<PathBuf>),
+    PlotKindChanged(views::export::PlotKind),
+    PlotSizeChanged(String),
+    ExportPlot(views::export::PlotKind),
+    PlotPathPicked(views::export::PlotKind, Option<PathBuf>),
 }
 
 pub fn fmt_tick(v: f64) -> String {
@@ -159,6 +1590,163 @@ pub fn fmt_tick(v: f64) -> String {
     }
 }
 
+// Sample i's x-axis label: its date when the series is dated, else the index
+pub fn fmt_x_tick(dates: Option<&[chrono::NaiveDate]>, i: usize) -> String {
+    match dates.and_then(|d| d.get(i)) {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => i.to_string(),
+    }
+}
+
+// Range checks shared by Calculate's typed inputs and App::validate_inputs
+pub fn check_order(
+    filter: structures::filters::FilterType,
+    what: &str,
+    v: usize,
+) -> Result<usize, String> {
+    let max = filter.max_order();
+    if !(1..=max).contains(&v) {
+        return Err(format!(
+            "{filter} {what} must be between 1 and {max}, got {v}"
+        ));
+    }
+    Ok(v)
+}
+
+pub fn check_pole_radius(v: f64) -> Result<f64, String> {
+    if !(v > 0.0 && v < 1.0) {
+        return Err(format!(
+            "pole radius must be strictly between 0 and 1, got {v}"
+        ));
+    }
+    Ok(v)
+}
+
+pub fn check_notch_depth(v: f64) -> Result<f64, String> {
+    if !(v > 0.0 && v <= 1.0) {
+        return Err(format!("notch depth must be in (0, 1], got {v}"));
+    }
+    Ok(v)
+}
+
+pub fn check_comb_period(v: usize) -> Result<usize, String> {
+    if !(2..=math::MAX_COMB_PERIOD).contains(&v) {
+        return Err(format!(
+            "comb period must be between 2 and {} samples, got {v}",
+            math::MAX_COMB_PERIOD
+        ));
+    }
+    Ok(v)
+}
+
+// Rate unit for a plural time unit, e.g. per_unit("cycles", "days") is "cycles/day"
+pub fn per_unit(what: &str, time_unit: &str) -> String {
+    let singular = match time_unit.strip_suffix('s') {
+        Some(s) if s.len() > 1 && !s.ends_with('s') => s,
+        _ => time_unit,
+    };
+    format!("{what}/{singular}")
+}
+
+// Comma-separated coefficients, optionally wrapped in [ ] as fmt_coefficients'
+// callers print them
+pub fn parse_coefficients(s: &str) -> Result<Vec<f64>, String> {
+    let inner = s.trim().trim_start_matches('[').trim_end_matches(']');
+    if inner.trim().is_empty() {
+        return Err(String::from("no coefficients given"));
+    }
+    inner
+        .split(',')
+        .map(|c| {
+            c.trim()
+                .parse::<f64>()
+                .map_err(|e| format!("'{}': {e}", c.trim()))
+        })
+        .collect()
+}
+
+// Difference-equation coefficients to 8 significant digits
+pub fn fmt_coefficients(c: &[f64]) -> String {
+    c.iter()
+        .map(|v| format!("{v:.7e}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Clipboard form of b/a, e.g. "{b: [...], a: [...]}"
+pub fn coefficients_clipboard(b: &[f64], a: &[f64]) -> String {
+    format!(
+        "{{b: [{}], a: [{}]}}",
+        fmt_coefficients(b),
+        fmt_coefficients(a)
+    )
+}
+
+// H(z) = (b0 + b1 z^-1 + ...) / (a0 + a1 z^-1 + ...), dropping ~0 terms.
+// An a of just 1 (FIR) leaves the denominator off.
+pub fn format_transfer_function(b: &[f64], a: &[f64]) -> String {
+    let num = format_z_polynomial(b);
+    let den = format_z_polynomial(a);
+    if den == "1" {
+        format!("H(z) = {num}")
+    } else {
+        format!("H(z) = ({num}) / ({den})")
+    }
+}
+
+fn format_z_polynomial(c: &[f64]) -> String {
+    let largest = c.iter().fold(0.0_f64, |m, v| m.max(v.abs()));
+    let mut out = String::new();
+    for (k, &v) in c.iter().enumerate() {
+        if v.abs() <= TF_ZERO_TOL * largest {
+            continue;
+        }
+        let sign = if v < 0.0 { "-" } else { "+" };
+        if out.is_empty() {
+            if v < 0.0 {
+                out.push('-');
+            }
+        } else {
+            out.push_str(&format!(" {sign} "));
+        }
+        // A unit coefficient is implied in front of z^-k
+        let mag = v.abs();
+        match k {
+            0 => out.push_str(&format_tf_coefficient(mag)),
+            _ if (mag - 1.0).abs() <= TF_ZERO_TOL => out.push_str(&format!("z^-{k}")),
+            _ => out.push_str(&format!("{} z^-{k}", format_tf_coefficient(mag))),
+        }
+    }
+    if out.is_empty() {
+        String::from("0")
+    } else {
+        out
+    }
+}
+
+// Up to 6 decimals, trailing zeros trimmed; scientific outside [1e-3, 1e4)
+fn format_tf_coefficient(v: f64) -> String {
+    if (1e-3..1e4).contains(&v) {
+        let s = format!("{v:.6}");
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        format!("{v:.4e}")
+    }
+}
+
+pub fn fmt_root(z: Complex<f64>, multiplicity: usize) -> String {
+    let s = if z.im >= 0.0 {
+        format!("{:+.6} +{:.6}j", z.re, z.im)
+    } else {
+        format!("{:+.6} {:.6}j", z.re, z.im)
+    };
+    if multiplicity > 1 {
+        format!("{s} \u{00D7}{multiplicity}")
+    } else {
+        s
+    }
+}
+
 pub fn panel_bg() -> Color {
     Color::from_rgb8(0x10, 0x10, 0x14)
 } // dark panel
@@ -175,12 +1763,155 @@ pub fn glow_purple() -> Color {
     Color::from_rgb8(0xB7, 0x63, 0xFF)
 } // accent
 
+// Distinct colors for any number of overlay series by stepping the hue
+// around the wheel by the golden angle
+pub fn series_color(i: usize) -> Color {
+    let hue = (i as f32 * 137.508) % 360.0;
+    let c = 0.75_f32;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = 0.2;
+    Color::from_rgb(r + m, g + m, b + m)
+}
+
+// Dark blue through teal and green to yellow for t in [0, 1], a rough viridis
+pub fn colormap(t: f32) -> Color {
+    const STOPS: [(f32, f32, f32); 5] = [
+        (0.27, 0.00, 0.33),
+        (0.23, 0.32, 0.55),
+        (0.13, 0.57, 0.55),
+        (0.37, 0.79, 0.38),
+        (0.99, 0.91, 0.14),
+    ];
+    let x = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let i = (x as usize).min(STOPS.len() - 2);
+    let f = x - i as f32;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    Color::from_rgb(
+        a.0 + f * (b.0 - a.0),
+        a.1 + f * (b.1 - a.1),
+        a.2 + f * (b.2 - a.2),
+    )
+}
+
+// One series from a CSV file: a lone column of values, "date,value" rows, or
+// anything wider where the second column holds the values (so an exported
+// index,raw,filtered file loads its raw column). A non-numeric first line is
+// taken as a header; blank or "NaN" cells become gaps.
+pub fn read_series_csv(
+    path: &std::path::Path,
+) -> Result<(Vec<f64>, Option<Vec<chrono::NaiveDate>>), String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut values = Vec::new();
+    let mut dates = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        let cell = if cells.len() > 1 { cells[1] } else { cells[0] };
+        let value = match cell {
+            "" => f64::NAN,
+            c => match c.parse::<f64>() {
+                Ok(v) => v,
+                Err(_) if i == 0 => continue,
+                Err(e) => return Err(format!("line {}: '{c}': {e}", i + 1)),
+            },
+        };
+        values.push(value);
+        if let Ok(d) = cells[0].parse::<chrono::NaiveDate>() {
+            dates.push(d);
+        }
+    }
+    if values.is_empty() {
+        return Err(format!("No values found in {}", path.display()));
+    }
+    // Dates only count when every row has one
+    let dates = (dates.len() == values.len()).then_some(dates);
+    Ok((values, dates))
+}
+
+// One series from a JSON object { "fs": 24.0, "unit": "hours", "values": [...] }.
+// fs must be positive and values a non-empty array of numbers, with null taken
+// as a gap; unit is optional.
+pub fn load_json_series(path: &std::path::Path) -> Result<structures::dataset::SeriesData, String> {
+    use serde_json::Value;
+    let file = std::fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let v: Value = serde_json::from_reader(std::io::BufReader::new(file))
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    let fs = match v.get("fs") {
+        Some(Value::Number(n)) => n.as_f64().unwrap_or(f64::NAN),
+        Some(other) => return Err(format!("fs must be a number, got {other}")),
+        None => return Err(String::from("Missing \"fs\"")),
+    };
+    if !(fs.is_finite() && fs > 0.) {
+        return Err(format!("fs must be positive, got {fs}"));
+    }
+    let unit = match v.get("unit") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(u)) => Some(u.clone()),
+        Some(other) => return Err(format!("unit must be a string, got {other}")),
+    };
+    let values = match v.get("values") {
+        Some(Value::Array(xs)) if !xs.is_empty() => xs
+            .iter()
+            .enumerate()
+            .map(|(i, x)| match x {
+                Value::Null => Ok(f64::NAN),
+                x => x
+                    .as_f64()
+                    .ok_or(format!("values[{i}] is not a number: {x}")),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(Value::Array(_)) => return Err(String::from("values is empty")),
+        Some(_) => return Err(String::from("values must be an array of numbers")),
+        None => return Err(String::from("Missing \"values\"")),
+    };
+    Ok(structures::dataset::SeriesData { fs, unit, values })
+}
+
+// 8-bit RGBA PNG
+pub fn write_png(
+    path: &std::path::Path,
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<(), String> {
+    if width == 0 || height == 0 || rgba.len() != 4 * width as usize * height as usize {
+        return Err(format!(
+            "{width}x{height} doesn't match {} bytes of RGBA",
+            rgba.len()
+        ));
+    }
+    let file = std::fs::File::create(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut encoder = png::Encoder::new(io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut w| w.write_image_data(rgba))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
 pub fn weight_file() -> Result<PathBuf, String> {
     let proj = ProjectDirs::from("", "", "fourier-fit")
         .ok_or("Could not determine config directory".to_string())?;
     Ok(proj.config_dir().join(DEFAULT_FILENAME))
 }
 
+pub fn session_file() -> Result<PathBuf, String> {
+    let proj = ProjectDirs::from("", "", "fourier-fit")
+        .ok_or("Could not determine config directory".to_string())?;
+    Ok(proj.config_dir().join(SESSION_FILENAME))
+}
+
 pub fn create_file_perhaps(file_path: &std::path::PathBuf) -> io::Result<()> {
     let ok_res = std::fs::exists(file_path)?;
     if !ok_res {
@@ -199,13 +1930,328 @@ fn is_file_empty(file_path: &std::path::Path) -> bool {
     }
 }
 
-pub fn demo_data() -> Vec<f64> {
-    // 512 samples of a noisy sine
-    let n = 512;
-    (0..n)
-        .map(|i| {
-            let t = i as f64 / n as f64;
-            (2.0 * std::f64::consts::PI * 5.0 * t).sin() + 0.15 * (2.0 * t).sin()
-        })
-        .collect()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use structures::filters::{FilterPhase, FilterType};
+
+    fn ramp_sine(n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| (i as f64 * 0.3).sin() + 0.01 * i as f64)
+            .collect()
+    }
+
+    #[test]
+    fn poles_on_the_unit_circle_are_not_stable() {
+        let mut app = App::new();
+        assert_eq!(app.is_stable(), None);
+        app.poles = Some(vec![Complex::new(0.5, 0.5), Complex::new(0.5, -0.5)]);
+        assert_eq!(app.is_stable(), Some(true));
+        app.poles = Some(vec![Complex::new(0.0, 1.0), Complex::new(0.0, -1.0)]);
+        assert_eq!(app.is_stable(), Some(false));
+        app.poles = Some(vec![Complex::new(1.2, 0.0)]);
+        assert_eq!(app.is_stable(), Some(false));
+    }
+
+    #[test]
+    fn integrator_pole_is_marginally_stable() {
+        let mut app = App::new();
+        app.set_filter_type(FilterType::INTEGRATOR);
+        let out = app.run_filter(&ramp_sine(32)).unwrap();
+        assert_eq!(
+            (out.b.as_slice(), out.a.as_slice()),
+            (&[1.0][..], &[1.0, -1.0][..])
+        );
+        // The root of a = [1, -1]
+        app.poles = Some(vec![Complex::new(1.0, 0.0)]);
+        assert_eq!(app.is_stable(), Some(false));
+        assert!(app.is_marginally_stable());
+        app.poles = Some(vec![Complex::new(1.0 + 1e-6, 0.0)]);
+        assert!(!app.is_marginally_stable());
+    }
+
+    #[test]
+    fn allpass_changes_the_signal_in_zero_phase_mode() {
+        let mut app = App::new();
+        app.set_filter_type(FilterType::ALL_PASS);
+        app.set_cutoff(0.25);
+        assert_eq!(app.phase, FilterPhase::ZeroPhase);
+        let data = ramp_sine(128);
+        let out = app.run_filter(&data).unwrap().filtered_data;
+        let diff = data
+            .iter()
+            .zip(&out)
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0, f64::max);
+        assert!(
+            diff > 0.1,
+            "all-pass left the input unchanged (max diff {diff})"
+        );
+    }
+
+    #[test]
+    fn per_unit_uses_the_singular_time_unit() {
+        assert_eq!(per_unit("cycles", "days"), "cycles/day");
+        assert_eq!(per_unit("samples", "hours"), "samples/hour");
+        assert_eq!(per_unit("cycles", "ms"), "cycles/ms");
+        assert_eq!(per_unit("cycles", "week"), "cycles/week");
+        let mut app = App::new();
+        assert_eq!(app.time_unit(), DEFAULT_TIME_UNIT);
+        app.axis_unit = Some(String::from("hours"));
+        assert_eq!(per_unit("cycles", app.time_unit()), "cycles/hour");
+    }
+
+    #[test]
+    fn bandwidth_is_measured_from_the_nominal_gain() {
+        // A lowpass peaking 1 dB above its 0 dB passband, falling 1 dB per step
+        let freqs: Vec<f64> = (1..=10).map(|i| i as f64 * 0.01).collect();
+        let mag_db = [0.0, 1.0, 0.0, -1.0, -2.0, -3.0, -4.0, -5.0, -6.0, -7.0];
+        let (lo, hi) = views::bode::bandwidth_3db(&freqs, &mag_db, Some(0.0)).unwrap();
+        assert_eq!(lo, 0.0);
+        assert!((hi - 0.06).abs() < 1e-9, "edge at {hi}");
+        // Against the peak the edge moves in to -2 dB
+        let (_, hi) = views::bode::bandwidth_3db(&freqs, &mag_db, None).unwrap();
+        assert!((hi - 0.05).abs() < 1e-9, "edge at {hi}");
+    }
+
+    #[test]
+    fn write_png_round_trips_rgba() {
+        let rgba: Vec<u8> = (0..3 * 2 * 4).map(|i| (i * 10) as u8).collect();
+        let path = std::env::temp_dir().join("ffit_write_png_test.png");
+        write_png(&path, &rgba, 3, 2).unwrap();
+        let decoder = png::Decoder::new(io::BufReader::new(std::fs::File::open(&path).unwrap()));
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(&buf[..info.buffer_size()], rgba.as_slice());
+        assert!(write_png(&path, &rgba, 4, 2).is_err());
+    }
+
+    #[test]
+    fn exported_spectrum_bins_sit_at_k_fs_over_n() {
+        let mut app = App::new();
+        app.set_fs(4.0).unwrap();
+        let data = ramp_sine(10);
+        app.set_app_data(data.clone());
+        app.filtered_data = Some(math::FilterData {
+            filtered_data: data,
+            b: vec![1.0],
+            a: vec![1.0],
+            sos: Vec::new(),
+        });
+        // Padded to 16 points, so 9 bins 0.25 apart
+        app.fft_padding = structures::filters::FftPadding::NextPow2;
+        app.data_spectrum = Some(vec![1.0; 9]);
+        let path = std::env::temp_dir().join("ffit_export_test.csv");
+        let (series, spectrum) = app.export_csv(&path).unwrap();
+        let bins = std::fs::read_to_string(&spectrum).unwrap();
+        std::fs::remove_file(&series).ok();
+        std::fs::remove_file(&spectrum).ok();
+        let freqs: Vec<f64> = bins
+            .lines()
+            .skip(1)
+            .map(|l| l.split(',').nth(1).unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(freqs.len(), 9);
+        for (k, f) in freqs.iter().enumerate() {
+            assert!((f - k as f64 * 0.25).abs() < 1e-12, "bin {k} at {f}");
+        }
+    }
+
+    #[test]
+    fn failing_dataset_leaves_the_previous_results() {
+        let mut app = App::new();
+        app.set_app_data(ramp_sine(64));
+        app.set_filter_type(FilterType::MEDIAN);
+        app.filter().unwrap();
+        let before = app.filtered_data.as_ref().unwrap().filtered_data.clone();
+        // All gaps, so the overlay fails after the main series has run
+        app.datasets.push(structures::dataset::NamedSeries::new(
+            String::from("gaps"),
+            vec![f64::NAN; 8],
+        ));
+        app.set_smoothing_window(5);
+        assert!(app.filter().unwrap_err().starts_with("gaps:"));
+        assert_eq!(app.filtered_data.unwrap().filtered_data, before);
+        assert!(app.datasets[0].filtered.is_none());
+    }
+
+    #[test]
+    fn sweep_rejects_a_top_band_past_nyquist() {
+        let app = App::new();
+        // Nyquist is 0.5; a 0.4 center puts its upper edge at 0.57
+        assert!(app.sweep_center(0.01, 0.4, 4).is_err());
+        assert!(app.sweep_center(0.0, 0.3, 4).is_err());
+        assert!(app.sweep_center(0.3, 0.1, 4).is_err());
+        assert!(app.sweep_center(0.01, 0.1, 1).is_err());
+    }
+
+    #[test]
+    fn median_removes_a_spike_keeps_a_step_and_rejects_other_bands() {
+        let mut app = App::new();
+        app.set_filter_type(FilterType::MEDIAN);
+        app.set_smoothing_window(3);
+        let mut data = vec![0.0; 6];
+        data.extend([1.0; 6]);
+        data[2] = 10.0;
+        let out = app.run_filter(&data).unwrap();
+        assert_eq!(out.filtered_data[2], 0.0);
+        assert_eq!(&out.filtered_data[5..7], &[0.0, 1.0]);
+        assert!(out.a.is_empty());
+        app.band = structures::filters::FilterBand::Highpass;
+        assert!(app.run_filter(&data).unwrap_err().contains("not a"));
+    }
+
+    #[test]
+    fn transfer_function_formatting() {
+        assert_eq!(
+            format_transfer_function(&[0.5, 0.0, -0.25], &[1.0, -1.0]),
+            "H(z) = (0.5 - 0.25 z^-2) / (1 - z^-1)"
+        );
+        // FIR leaves the denominator off; tiny terms and trailing zeros drop
+        assert_eq!(
+            format_transfer_function(&[1.0, 1e-15, 2.0e-5], &[1.0]),
+            "H(z) = 1 + 2.0000e-5 z^-2"
+        );
+        assert_eq!(format_transfer_function(&[0.0], &[2.0]), "H(z) = (0) / (2)");
+    }
+
+    #[test]
+    fn bode_cache_is_hit_until_the_cutoff_changes() {
+        let mut app = App::new();
+        let data = ramp_sine(64);
+        app.set_cutoff(0.2);
+        app.filtered_data = Some(app.run_filter(&data).unwrap());
+        app.generate_bode().unwrap();
+        let first = app.bode_key;
+        assert!(first.is_some());
+        // A stand-in curve survives a rerun only if the cache short-circuits
+        let marker = (vec![1.0], vec![-1.0]);
+        app.bode_plot = Some(marker.clone());
+        app.filtered_data = Some(app.run_filter(&data).unwrap());
+        app.generate_bode().unwrap();
+        assert_eq!(app.bode_key, first);
+        assert_eq!(app.bode_plot.as_ref(), Some(&marker));
+
+        app.set_cutoff(0.3);
+        app.filtered_data = Some(app.run_filter(&data).unwrap());
+        app.generate_bode().unwrap();
+        assert_ne!(app.bode_key, first);
+        assert_ne!(app.bode_plot.as_ref(), Some(&marker));
+    }
+
+    #[test]
+    fn reference_sine_loads_at_the_requested_frequency() {
+        let mut app = App::new();
+        app.set_fs(4.0).unwrap();
+        app.load_reference_sine(0.25, 2.0).unwrap();
+        let data = app.raw_data.as_ref().unwrap();
+        assert_eq!(data.len(), REFERENCE_SINE_LEN);
+        // 0.25 cycles/day at 4 samples/day repeats every 16 samples
+        for i in 0..REFERENCE_SINE_LEN - 16 {
+            assert!((data[i] - data[i + 16]).abs() < 1e-9);
+        }
+        let peak = data.iter().fold(0.0_f64, |m, v| m.max(v.abs()));
+        assert!((peak - 2.0).abs() < 1e-9, "amplitude {peak}");
+        assert!(app.load_reference_sine(2.0, 1.0).is_err());
+        assert!(app.load_reference_sine(0.0, 1.0).is_err());
+        assert!(app.load_reference_sine(0.25, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn repeated_roots_are_grouped_and_labeled_with_multiplicity() {
+        let roots = [
+            Complex::new(0.5, 0.0),
+            Complex::new(0.5 + 1e-6, 0.0),
+            Complex::new(-1.0, 0.0),
+            Complex::new(0.5, -1e-6),
+        ];
+        let groups = math::group_roots(&roots, 1e-3);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].1, 3);
+        assert!((groups[0].0 - Complex::new(0.5, 0.0)).norm() < 1e-5);
+        assert_eq!(groups[1], (Complex::new(-1.0, 0.0), 1));
+        assert_eq!(
+            fmt_root(Complex::new(0.5, 0.0), 3),
+            "+0.500000 +0.000000j \u{00D7}3"
+        );
+        assert_eq!(
+            fmt_root(Complex::new(-1.0, -0.25), 1),
+            "-1.000000 -0.250000j"
+        );
+    }
+
+    #[test]
+    fn dropping_edge_gaps_trims_the_dates_too() {
+        let day = |d| chrono::NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let mut app = App::new();
+        app.set_gap_mode(structures::filters::GapMode::DropLeadingTrailing);
+        app.set_dated_data(vec![
+            (day(1), f64::NAN),
+            (day(2), 1.0),
+            (day(3), 2.0),
+            (day(4), 3.0),
+            (day(5), f64::NAN),
+        ]);
+        assert_eq!(app.raw_data.as_deref(), Some(&[1.0, 2.0, 3.0][..]));
+        assert_eq!(app.raw_dates, Some(vec![day(2), day(3), day(4)]));
+        // Switching back to interpolation restores every date
+        app.set_gap_mode(structures::filters::GapMode::LinearInterpolate);
+        assert_eq!(app.raw_dates.as_ref().map(Vec::len), Some(5));
+    }
+
+    #[test]
+    fn csv_files_load_as_named_overlays() {
+        let dir = std::env::temp_dir();
+        let a = dir.join("ffit_overlay_a.csv");
+        let b = dir.join("ffit_overlay_b.csv");
+        let bad = dir.join("ffit_overlay_bad.csv");
+        std::fs::write(&a, "value\n1\n2\n3\n").unwrap();
+        std::fs::write(&b, "2024-01-01,4\n2024-01-02,\n2024-01-03,6\n").unwrap();
+        std::fs::write(&bad, "value\n1\nnot a number\n").unwrap();
+        let mut app = App::new();
+        assert_eq!(app.load_datasets(&[a.clone(), b.clone()]), Ok(2));
+        let names: Vec<&str> = app.datasets.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["ffit_overlay_a", "ffit_overlay_b"]);
+        assert_eq!(app.datasets[0].data, [1.0, 2.0, 3.0]);
+        // The gap is interpolated like the main data's
+        assert_eq!(app.datasets[1].data, [4.0, 5.0, 6.0]);
+        // One unreadable file keeps the whole batch out
+        assert!(app.load_datasets(&[a.clone(), bad.clone()]).is_err());
+        assert_eq!(app.datasets.len(), 2);
+        for p in [a, b, bad] {
+            let _ = std::fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn sessions_use_stable_keys_and_loaded_values_are_range_checked() {
+        let path = std::env::temp_dir().join("ffit_session_keys_test.json");
+        let mut app = App::new();
+        app.filter = structures::filters::FilterType::BESSEL;
+        app.gap_mode = structures::filters::GapMode::DropLeadingTrailing;
+        app.cutoff_freq = 0.2;
+        app.save_session(&path).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(saved["filter"], "bessel");
+        assert_eq!(saved["gap_mode"], "drop_leading_trailing");
+        let loaded = App::load_session(&path).unwrap();
+        assert_eq!(loaded.filter, structures::filters::FilterType::BESSEL);
+        assert!(loaded.validate_inputs().is_ok());
+
+        // Older files stored the display label; a hand-edited order is caught
+        let mut old = saved.clone();
+        old["filter"] = "Butterworth".into();
+        old["order"] = 500.into();
+        std::fs::write(&path, old.to_string()).unwrap();
+        let loaded = App::load_session(&path).unwrap();
+        assert_eq!(loaded.filter, structures::filters::FilterType::BUTTERWORTH);
+        let err = loaded.validate_inputs().unwrap_err();
+        assert!(err.contains("between 1 and 20"), "{err}");
+        let _ = std::fs::remove_file(path);
+    }
 }
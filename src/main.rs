@@ -1,18 +1,25 @@
 use ffit::logic;
-use ffit::structures::data_modal;
+use ffit::structures::{data_modal, session};
 use ffit::views;
 use ffit::*;
+use iced::keyboard::key::Named;
 use iced::widget::Canvas;
 use iced::widget::canvas::Cache;
 use iced::{
-    Alignment, Element, Length, Theme,
-    widget::{button, column, container, pick_list, row, stack, text, text_input},
+    Alignment, Element, Length, Task, Theme,
+    widget::{
+        button, checkbox, column, container, pick_list, row, scrollable, slider, stack, text,
+        text_input,
+    },
 };
 
 const BOLD: iced::Font = iced::Font::with_name("Inter ExtraBold");
 
 pub fn main() -> iced::Result {
+    let window_size = session::SessionState::new(session_file().ok()).window_size();
     iced::application(Gui::default, Gui::update, Gui::view)
+        .subscription(Gui::subscription)
+        .window_size(window_size)
         .theme(Theme::Dark)
         .centered()
         .run()
@@ -26,11 +33,62 @@ struct Gui {
     // Data modal state
     modal_state: data_modal::DataModalState,
 
+    // Persisted window geometry and dialog directories
+    session: session::SessionState,
+
     // Store inputs
     cutoff_s: String,
+    second_cutoff_s: String,
     order_s: String,
+    lowpass_order_s: String,
+    window_len_s: String,
+    custom_b_s: String,
+    custom_a_s: String,
     ripple_s: String,
+    pole_radius_s: String,
+    notch_depth_s: String,
     attenuation_s: String,
+    root_tol_s: String,
+    sine_freq_s: String,
+    sine_amp_s: String,
+    harmonics_s: String,
+    trend_model: structures::filters::TrendModel,
+    // K for the keep-largest-bins rebuild
+    keep_bins: u32,
+    fs_s: String,
+    sweep_lo_s: String,
+    bode_min_s: String,
+    stop_edge_s: String,
+    stop_atten_s: String,
+    candle_custom_s: String,
+    fft_pad_s: String,
+    session_name_s: String,
+    plot_kind: views::export::PlotKind,
+    plot_size_s: String,
+    db_floor_s: String,
+    welch_segment_s: String,
+    welch_overlap_s: String,
+    // Frequency (cycles/day) for the single-tone Goertzel readout
+    goertzel_s: String,
+    bode_max_s: String,
+    sweep_hi_s: String,
+    sweep_steps_s: String,
+    dataset_name_s: String,
+    show_sections: bool,
+    show_peak_model: bool,
+    show_envelope: bool,
+    // Log-scale y axis on the time-series plot
+    ts_log_y: bool,
+    // SMA overlays shown on the candle view, a subset of CANDLE_SMAS
+    candle_smas: Vec<(usize, iced::Color)>,
+    // Bollinger period and width, applied to the candle view when shown
+    show_bollinger: bool,
+    bollinger: (usize, f64),
+    bollinger_period_s: String,
+    bollinger_k_s: String,
+    explain: bool,
+    // A Calculate job is running in the background
+    calculating: bool,
 
     // Output
     status: String,
@@ -40,6 +98,10 @@ struct Gui {
     ts_cache: Cache,
     fft_cache: Cache,
     bode_cache: Cache,
+    nyquist_cache: Cache,
+    step_cache: Cache,
+    acf_cache: Cache,
+    phase_cache: Cache,
     candles_cache: Cache,
 }
 
@@ -56,15 +118,62 @@ impl Gui {
             Some(p) => format!("Config location: {}", p.to_string_lossy()),
             None => modal_state.date_status.clone(),
         };
-        app.set_app_data(modal_state.get_vals_sorted_by_date());
+        app.set_dated_data(modal_state.get_entries_sorted_by_date());
+        let session = session::SessionState::new(match session_file() {
+            Ok(f) if create_file_perhaps(&f).is_ok() => Some(f),
+            _ => None,
+        });
 
         Self {
             app,
             modal_state,
+            session,
             cutoff_s: "".into(),
+            second_cutoff_s: "".into(),
             order_s: "".into(),
+            lowpass_order_s: "".into(),
+            window_len_s: "7".into(),
+            custom_b_s: String::new(),
+            custom_a_s: String::new(),
             ripple_s: "".into(),
+            pole_radius_s: "".into(),
+            notch_depth_s: "".into(),
             attenuation_s: "".into(),
+            root_tol_s: DEFAULT_ROOT_TOL.to_string(),
+            sine_freq_s: "".into(),
+            sine_amp_s: "".into(),
+            harmonics_s: DEFAULT_FOURIER_HARMONICS.to_string(),
+            trend_model: structures::filters::TrendModel::Linear,
+            keep_bins: DEFAULT_KEEP_BINS as u32,
+            fs_s: DEFAULT_SAMPLE_RATE.to_string(),
+            sweep_lo_s: "".into(),
+            bode_min_s: "".into(),
+            stop_edge_s: "".into(),
+            stop_atten_s: "".into(),
+            candle_custom_s: "1".into(),
+            fft_pad_s: "1024".into(),
+            session_name_s: DEFAULT_SAVED_SESSION_FILENAME.into(),
+            plot_kind: views::export::PlotKind::default(),
+            plot_size_s: format!("{}x{}", DEFAULT_PLOT_SIZE.0, DEFAULT_PLOT_SIZE.1),
+            db_floor_s: DEFAULT_DB_FLOOR.to_string(),
+            welch_segment_s: DEFAULT_WELCH_SEGMENT.to_string(),
+            welch_overlap_s: DEFAULT_WELCH_OVERLAP.to_string(),
+            goertzel_s: String::new(),
+            bode_max_s: "".into(),
+            sweep_hi_s: "".into(),
+            sweep_steps_s: DEFAULT_SWEEP_STEPS.to_string(),
+            dataset_name_s: "".into(),
+            show_sections: false,
+            show_peak_model: false,
+            show_envelope: false,
+            ts_log_y: false,
+            candle_smas: Vec::new(),
+            show_bollinger: false,
+            bollinger: (DEFAULT_BOLLINGER_PERIOD, DEFAULT_BOLLINGER_K),
+            bollinger_period_s: DEFAULT_BOLLINGER_PERIOD.to_string(),
+            bollinger_k_s: DEFAULT_BOLLINGER_K.to_string(),
+            explain: false,
+            calculating: false,
             status: error,
             zeros_out: String::new(),
             poles_out: String::new(),
@@ -72,111 +181,731 @@ impl Gui {
             ts_cache: Cache::new(),
             fft_cache: Cache::new(),
             bode_cache: Cache::new(),
+            nyquist_cache: Cache::new(),
+            step_cache: Cache::new(),
+            acf_cache: Cache::new(),
+            phase_cache: Cache::new(),
             candles_cache: Cache::new(),
         }
     }
 
-    fn update(&mut self, message: Message) {
+    fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::FilterChanged(t) => {
                 self.app.set_filter_type(t);
             }
+            Message::BandChanged(b) => {
+                self.app.set_band(b);
+            }
+            Message::EdgeHandlingChanged(e) => {
+                self.app.set_edge_handling(e);
+            }
+            Message::FilterPhaseChanged(p) => {
+                self.app.set_phase(p);
+            }
+            Message::SpectrumScaleChanged(scale) => {
+                self.app.set_spectrum_scale(scale);
+                self.fft_cache.clear();
+            }
+            Message::BodeXScaleChanged(scale) => {
+                self.app.bode_x_scale = scale;
+                self.bode_cache.clear();
+                self.phase_cache.clear();
+            }
+            Message::SpectrumModeChanged(mode) => {
+                self.app.set_spectrum_mode(mode);
+                self.fft_cache.clear();
+            }
+            Message::WelchSegmentChanged(s) => {
+                // Recomputed as soon as the pair is valid
+                if let Ok(n) = s.trim().parse::<usize>()
+                    && self.app.set_welch(n, self.app.welch_overlap).is_ok()
+                {
+                    self.refresh_spectra();
+                }
+                self.welch_segment_s = s;
+            }
+            Message::WelchOverlapChanged(s) => {
+                if let Ok(n) = s.trim().parse::<usize>()
+                    && self.app.set_welch(self.app.welch_segment, n).is_ok()
+                {
+                    self.refresh_spectra();
+                }
+                self.welch_overlap_s = s;
+            }
+            Message::GoertzelFreqChanged(s) => self.goertzel_s = s,
+            Message::DbFloorChanged(s) => {
+                // The spectrum takes it as soon as it parses, partial input
+                // keeps the old floor; the Bode plot picks it up on Calculate
+                if let Ok(v) = s.trim().parse::<f64>()
+                    && self.app.set_db_floor(v).is_ok()
+                {
+                    self.fft_cache.clear();
+                }
+                self.db_floor_s = s;
+            }
+            Message::WindowChanged(w) => {
+                self.app.set_spectrum_window(w);
+                // Re-window the existing output without a full Calculate
+                self.refresh_spectra();
+            }
+            Message::DetrendChanged(d) => {
+                self.app.set_detrend(d);
+                self.refresh_spectra();
+            }
+            Message::FftPaddingChanged(p) => {
+                self.app.set_fft_padding(p);
+                self.refresh_spectra();
+            }
+            // Only checked here; the padded FFT waits for Calculate
+            Message::FftPadLengthChanged(s) => {
+                self.fft_pad_s = s;
+                self.status = match self.fft_pad_len() {
+                    Ok(_) => String::new(),
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
             Message::CandleLengthsChanged(t) => {
-                self.app.candle_length = t;
+                self.app.set_candle_length(t);
+                self.candles_cache.clear();
+            }
+            Message::CandlePaletteChanged(p) => {
+                self.app.candle_palette = p;
+                self.candles_cache.clear();
+            }
+            Message::CandleSmaToggled(period, on) => {
+                self.candle_smas = CANDLE_SMAS
+                    .into_iter()
+                    .filter(|&(p, _)| {
+                        if p == period {
+                            on
+                        } else {
+                            self.candle_smas.iter().any(|&(q, _)| q == p)
+                        }
+                    })
+                    .collect();
+                self.candles_cache.clear();
+            }
+            Message::BollingerToggled(b) => {
+                self.show_bollinger = b;
+                self.candles_cache.clear();
+            }
+            Message::BollingerPeriodChanged(s) => {
+                match s.trim().parse::<usize>() {
+                    Ok(n) if n >= 2 => {
+                        self.bollinger.0 = n;
+                        self.candles_cache.clear();
+                    }
+                    Ok(_) => {
+                        self.status = String::from("Error: Bollinger period must be at least 2")
+                    }
+                    Err(e) => self.status = format!("Bollinger period parse error: {e}"),
+                }
+                self.bollinger_period_s = s;
+            }
+            Message::BollingerKChanged(s) => {
+                match s.trim().parse::<f64>() {
+                    Ok(k) if k.is_finite() && k > 0.0 => {
+                        self.bollinger.1 = k;
+                        self.candles_cache.clear();
+                    }
+                    Ok(_) => self.status = String::from("Error: Bollinger k must be positive"),
+                    Err(e) => self.status = format!("Bollinger k parse error: {e}"),
+                }
+                self.bollinger_k_s = s;
+            }
+            Message::CandleStyleChanged(style) => {
+                self.app.set_candle_style(style);
+                self.candles_cache.clear();
+            }
+            Message::CandleSourceChanged(source) => {
+                self.app.set_candle_source(source);
+                self.candles_cache.clear();
+            }
+            Message::StopEdgeChanged(s) => self.stop_edge_s = s,
+            Message::StopAttenChanged(s) => self.stop_atten_s = s,
+            Message::EstimateOrder => {
+                let fs = self.app.fs;
+                let period = |s: &str, what: &str| {
+                    s.trim()
+                        .parse::<f64>()
+                        .map_err(|e| format!("{what} parse error: {e}"))
+                        .and_then(|p| math::cutoff_period_to_nyquist(p, fs))
+                };
+                // Without a ripple input the cutoff is taken as the -3 dB point
+                let gpass = if self.app.filter.uses_ripple() {
+                    self.ripple_s
+                        .trim()
+                        .parse::<f64>()
+                        .map_err(|e| format!("ripple parse error: {e}"))
+                } else {
+                    Ok(3.0)
+                };
+                let estimate = period(&self.cutoff_s, "cutoff").and_then(|wp| {
+                    let ws = period(&self.stop_edge_s, "stop edge")?;
+                    let gstop = self
+                        .stop_atten_s
+                        .trim()
+                        .parse::<f64>()
+                        .map_err(|e| format!("stop attenuation parse error: {e}"))?;
+                    math::estimate_order(wp, ws, gpass?, gstop, self.app.filter)
+                });
+                self.status = match estimate {
+                    Ok(n) if n > self.app.filter.max_order() => format!(
+                        "Estimated {} order {n} is above the maximum of {}; relax the spec",
+                        self.app.filter,
+                        self.app.filter.max_order()
+                    ),
+                    Ok(n) => {
+                        self.order_s = n.to_string();
+                        format!("Estimated {} order: {n}", self.app.filter)
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
+            Message::CustomCandleChanged(s) => {
+                match s.trim().parse::<usize>() {
+                    Ok(0) => self.status = String::from("Error: candle width must be at least 1"),
+                    Ok(n) => {
+                        self.app
+                            .set_candle_length(structures::candle::CandleLengths::Custom(n));
+                        self.candles_cache.clear();
+                    }
+                    Err(e) => self.status = format!("candle width parse error: {e}"),
+                }
+                self.candle_custom_s = s;
+            }
+            Message::SampleRateChanged(s) => {
+                // Applied as soon as it parses; run Calculate to redo the plots
+                match s.trim().parse::<f64>() {
+                    Ok(v) => match self.app.set_fs(v) {
+                        Ok(()) => {
+                            self.fft_cache.clear();
+                            self.bode_cache.clear();
+                            self.nyquist_cache.clear();
+                            self.phase_cache.clear();
+                        }
+                        Err(e) => self.status = format!("Error: {e}"),
+                    },
+                    Err(e) => self.status = format!("sample rate parse error: {e}"),
+                }
+                self.fs_s = s;
             }
             Message::CutoffChanged(s) => self.cutoff_s = s,
+            Message::SecondCutoffChanged(s) => self.second_cutoff_s = s,
             Message::OrderChanged(s) => self.order_s = s,
+            Message::LowpassOrderChanged(s) => self.lowpass_order_s = s,
+            Message::SmoothingWindowChanged(s) => self.window_len_s = s,
+            Message::CustomBChanged(s) => self.custom_b_s = s,
+            Message::CustomAChanged(s) => self.custom_a_s = s,
             Message::RippleChanged(s) => self.ripple_s = s,
+            Message::PoleRadiusChanged(s) => self.pole_radius_s = s,
+            Message::NotchDepthChanged(s) => self.notch_depth_s = s,
             Message::AttenuationChanged(s) => self.attenuation_s = s,
+            Message::RootTolChanged(s) => self.root_tol_s = s,
 
-            Message::LoadDemo => {
-                self.app.set_app_data(demo_data());
-                self.status = String::from("Loaded demo data");
+            Message::SineFreqChanged(s) => self.sine_freq_s = s,
+            Message::SineAmplitudeChanged(s) => self.sine_amp_s = s,
+            Message::HarmonicsChanged(s) => self.harmonics_s = s,
+            Message::FitFourier => {
+                let n = match self.harmonics_s.trim().parse::<usize>() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = format!("harmonics parse error: {e}");
+                        return Task::none();
+                    }
+                };
+                if let Err(e) = self.app.fit_fourier(n) {
+                    self.status = format!("Error: {e}");
+                    return Task::none();
+                }
+                let periods = self
+                    .app
+                    .fourier_fit
+                    .as_ref()
+                    .map(|f| {
+                        f.harmonics
+                            .iter()
+                            .map(|h| format!("{:.1}", 1.0 / (h.frequency * self.app.fs)))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    })
+                    .unwrap_or_default();
+                self.status = format!(
+                    "Fourier fit with {n} harmonic(s), periods ({}): {periods}",
+                    self.app.time_unit()
+                );
+                self.ts_cache.clear();
+            }
+            Message::TrendModelChanged(model) => self.trend_model = model,
+            Message::FitTrend => {
+                self.status = match self.app.fit_trend(self.trend_model) {
+                    Ok(()) => match &self.app.trend {
+                        Some(t) => format!("{} trend: {}", t.model, t.equation),
+                        None => String::new(),
+                    },
+                    Err(e) => format!("Error: {e}"),
+                };
+                self.ts_cache.clear();
+            }
+            Message::KeepBinsChanged(k) => {
+                self.keep_bins = k;
+                // Live once the overlay is on, so dragging shows each K
+                if self.app.denoised.is_some() {
+                    if let Err(e) = self.app.denoise(k as usize) {
+                        self.status = format!("Error: {e}");
+                    }
+                    self.ts_cache.clear();
+                }
+            }
+            Message::Denoise => {
+                self.status = match self.app.denoise(self.keep_bins as usize) {
+                    Ok(()) => format!("Rebuilt from DC and the {} largest bins", self.keep_bins),
+                    Err(e) => format!("Error: {e}"),
+                };
+                self.ts_cache.clear();
+            }
+            Message::LoadSine => {
+                let freq = match self.sine_freq_s.trim().parse::<f64>() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = format!("sine frequency parse error: {e}");
+                        return Task::none();
+                    }
+                };
+                let amplitude = match self.sine_amp_s.trim().parse::<f64>() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = format!("sine amplitude parse error: {e}");
+                        return Task::none();
+                    }
+                };
+                self.status = match self.app.load_reference_sine(freq, amplitude) {
+                    Ok(()) => {
+                        format!(
+                            "Loaded reference sine ({freq} {}, amplitude {amplitude})",
+                            per_unit("cycles", self.app.time_unit())
+                        )
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
+
+            Message::SweepLoChanged(s) => self.sweep_lo_s = s,
+            Message::BodeMinChanged(s) => self.bode_min_s = s,
+            Message::BodeMaxChanged(s) => self.bode_max_s = s,
+            Message::SweepHiChanged(s) => self.sweep_hi_s = s,
+            Message::SweepStepsChanged(s) => self.sweep_steps_s = s,
+            Message::RunSweep => {
+                let f_lo = match self.sweep_lo_s.trim().parse::<f64>() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = format!("sweep start parse error: {e}");
+                        return Task::none();
+                    }
+                };
+                let f_hi = match self.sweep_hi_s.trim().parse::<f64>() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = format!("sweep end parse error: {e}");
+                        return Task::none();
+                    }
+                };
+                let steps = match self.sweep_steps_s.trim().parse::<usize>() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = format!("sweep steps parse error: {e}");
+                        return Task::none();
+                    }
+                };
+                match self.app.sweep_center(f_lo, f_hi, steps) {
+                    Ok(sweep) => {
+                        self.status = format!(
+                            "Swept {steps} bandpass centers from {f_lo} to {f_hi} {}",
+                            per_unit("cycles", self.app.time_unit())
+                        );
+                        self.app.center_sweep = Some(sweep);
+                    }
+                    Err(e) => self.status = format!("Error: {e}"),
+                }
+                self.bode_cache.clear();
+                self.nyquist_cache.clear();
+            }
+
+            Message::DatasetNameChanged(s) => self.dataset_name_s = s,
+            Message::AddDataset => {
+                self.status = match self.app.add_dataset(&self.dataset_name_s) {
+                    Ok(()) => {
+                        self.dataset_name_s.clear();
+                        format!("{} overlay dataset(s)", self.app.datasets.len())
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
+                self.ts_cache.clear();
+            }
+            Message::LoadDatasets => {
+                let dir = self.session.load_dir();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("CSV series", &["csv"])
+                            .set_directory(dir)
+                            .pick_files()
+                            .await
+                            .map(|files| files.iter().map(|f| f.path().to_path_buf()).collect())
+                    },
+                    Message::DatasetFilesPicked,
+                );
+            }
+            Message::DatasetFilesPicked(None) => {}
+            Message::DatasetFilesPicked(Some(paths)) => {
+                self.status = match self.app.load_datasets(&paths) {
+                    Ok(n) => {
+                        if let Some(first) = paths.first() {
+                            self.session.record_load(first);
+                        }
+                        format!(
+                            "Loaded {n} overlay dataset(s), {} in total; press Calculate to filter them",
+                            self.app.datasets.len()
+                        )
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
+                self.ts_cache.clear();
+                self.fft_cache.clear();
+            }
+            Message::NormalizeChanged(mode) => {
+                self.app.set_normalize(mode);
+                self.status = format!("Normalization: {mode}; run Calculate to refilter");
+                self.ts_cache.clear();
+                self.candles_cache.clear();
+            }
+            Message::GapModeChanged(mode) => {
+                self.app.set_gap_mode(mode);
+                self.status = format!("Gap handling: {mode}; run Calculate to refilter");
+                self.ts_cache.clear();
+                self.candles_cache.clear();
+            }
+            Message::AddStage => {
+                self.status = match self.app.push_stage() {
+                    Ok(()) => format!(
+                        "Stage {} added; Calculate now runs the current settings after it",
+                        self.app.stages.len()
+                    ),
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
+            Message::PinReference => {
+                self.status = match self.app.pin_reference() {
+                    Ok(()) => format!("Pinned {} as the Bode reference", self.app.reference_label),
+                    Err(e) => format!("Error: {e}"),
+                };
+                self.bode_cache.clear();
+                self.phase_cache.clear();
+            }
+            Message::ClearReference => {
+                self.app.clear_reference();
+                self.bode_cache.clear();
+                self.phase_cache.clear();
+            }
+            Message::RemoveStage(i) => {
+                self.status = match self.app.remove_stage(i) {
+                    Ok(()) => format!("Removed stage {}; run Calculate to update", i + 1),
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
+            Message::ClearDatasets => {
+                self.app.clear_datasets();
+                self.ts_cache.clear();
+                self.fft_cache.clear();
             }
 
             Message::ClearOutput => {
                 self.status.replace_range(.., "");
+                self.app.datasets.iter_mut().for_each(|d| d.clear_output());
+                self.app.center_sweep = None;
+                self.app.fourier_fit = None;
+                self.app.trend = None;
+                self.app.denoised = None;
                 self.zeros_out.clear();
                 self.poles_out.clear();
                 self.plot_cache.clear();
                 self.ts_cache.clear();
                 self.fft_cache.clear();
                 self.bode_cache.clear();
+                self.nyquist_cache.clear();
+                self.step_cache.clear();
+                self.acf_cache.clear();
+                self.phase_cache.clear();
                 self.candles_cache.clear();
             }
 
+            Message::ResetParams => {
+                self.app.reset_params();
+                self.cutoff_s.clear();
+                self.second_cutoff_s.clear();
+                self.order_s.clear();
+                self.lowpass_order_s.clear();
+                self.window_len_s = self.app.smoothing_window.to_string();
+                self.custom_b_s.clear();
+                self.custom_a_s.clear();
+                self.ripple_s.clear();
+                self.pole_radius_s.clear();
+                self.notch_depth_s.clear();
+                self.attenuation_s.clear();
+                self.root_tol_s = self.app.root_tol.to_string();
+                self.status = String::from("Filter parameters reset to defaults");
+            }
+
             Message::Calculate => {
                 self.status.replace_range(.., "");
 
                 // Parse inputs
-                let cutoff = match self.cutoff_s.trim().parse::<f64>() {
-                    Ok(v) => match math::cutoff_period_to_nyquist(v) {
-                        Ok(w) => w,
+                let cutoff = if !self.app.filter.is_designed() {
+                    self.app.cutoff_freq
+                } else {
+                    match self.cutoff_s.trim().parse::<f64>() {
+                        Ok(v) => match math::cutoff_period_to_nyquist(v, self.app.fs) {
+                            Ok(w) => w,
+                            Err(e) => {
+                                self.status = format!("Error: {e}");
+                                return Task::none();
+                            }
+                        },
                         Err(e) => {
-                            self.status = format!("Error: {e}");
-                            return;
+                            self.status = format!("cutoff parse error: {e}");
+                            return Task::none();
                         }
-                    },
-                    Err(e) => {
-                        self.status = format!("cutoff parse error: {e}");
-                        return;
                     }
                 };
-                let order = match self.order_s.trim().parse::<usize>() {
-                    Ok(v) => v,
-                    Err(e) => {
-                        self.status = format!("order parse error: {e}");
-                        return;
+                if self.app.filter == structures::filters::FilterType::CUSTOM {
+                    let b = match parse_coefficients(&self.custom_b_s) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            self.status = format!("b coefficient parse error: {e}");
+                            return Task::none();
+                        }
+                    };
+                    let a = match parse_coefficients(&self.custom_a_s) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            self.status = format!("a coefficient parse error: {e}");
+                            return Task::none();
+                        }
+                    };
+                    self.app.set_custom_coefficients(b, a);
+                }
+                if self.app.band.uses_second_cutoff() && self.app.filter.is_designed() {
+                    match self.second_cutoff_s.trim().parse::<f64>() {
+                        Ok(v) => match math::cutoff_period_to_nyquist(v, self.app.fs) {
+                            Ok(w) => self.app.set_second_cutoff(w),
+                            Err(e) => {
+                                self.status = format!("Error: {e}");
+                                return Task::none();
+                            }
+                        },
+                        Err(e) => {
+                            self.status = format!("second cutoff parse error: {e}");
+                            return Task::none();
+                        }
+                    }
+                }
+                // The moving average is sized by its window instead of an order
+                let (order, smoothing_window) = if self.app.filter.uses_window() {
+                    match self.window_len_s.trim().parse::<usize>() {
+                        Ok(v) if self.app.filter == structures::filters::FilterType::COMB => {
+                            match check_comb_period(v) {
+                                Ok(v) => (self.app.order, v),
+                                Err(e) => {
+                                    self.status = e;
+                                    return Task::none();
+                                }
+                            }
+                        }
+                        Ok(v) => (self.app.order, v),
+                        Err(e) => {
+                            self.status = format!("window parse error: {e}");
+                            return Task::none();
+                        }
+                    }
+                } else if !self.app.filter.is_designed() {
+                    (self.app.order, self.app.smoothing_window)
+                } else {
+                    match self.order_s.trim().parse::<usize>() {
+                        Ok(v) => match check_order(self.app.filter, "order", v) {
+                            Ok(v) => (v, self.app.smoothing_window),
+                            Err(e) => {
+                                self.status = e;
+                                return Task::none();
+                            }
+                        },
+                        Err(e) => {
+                            self.status = format!("order parse error: {e}");
+                            return Task::none();
+                        }
                     }
                 };
-                let ripple = match self.ripple_s.trim().parse::<f64>() {
-                    Ok(v) => v,
-                    Err(e) => {
-                        self.status = format!("ripple parse error: {e}");
-                        return;
+                // Grayed-out fields keep whatever the app already holds
+                let lowpass_order = if self.app.band != structures::filters::FilterBand::Bandpass
+                    || !self.app.filter.is_designed()
+                {
+                    self.app.lowpass_order
+                } else {
+                    match self.lowpass_order_s.trim().parse::<usize>() {
+                        Ok(v) => match check_order(self.app.filter, "lowpass order", v) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                self.status = e;
+                                return Task::none();
+                            }
+                        },
+                        Err(e) => {
+                            self.status = format!("lowpass order parse error: {e}");
+                            return Task::none();
+                        }
                     }
                 };
-                let attenuation = match self.attenuation_s.trim().parse::<f64>() {
-                    Ok(v) => v,
+                let ripple = if !self.app.filter.uses_ripple() {
+                    self.app.ripple
+                } else {
+                    match self.ripple_s.trim().parse::<f64>() {
+                        Ok(v) => v,
+                        Err(e) => {
+                            self.status = format!("ripple parse error: {e}");
+                            return Task::none();
+                        }
+                    }
+                };
+                let pole_radius = if !self.app.filter.uses_pole_radius() {
+                    self.app.pole_radius
+                } else {
+                    match self.pole_radius_s.trim().parse::<f64>() {
+                        Ok(v) => match check_pole_radius(v) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                self.status = e;
+                                return Task::none();
+                            }
+                        },
+                        Err(e) => {
+                            self.status = format!("pole radius parse error: {e}");
+                            return Task::none();
+                        }
+                    }
+                };
+                let notch_depth = if !self.app.filter.uses_notch_depth() {
+                    self.app.notch_depth
+                } else {
+                    match self.notch_depth_s.trim().parse::<f64>() {
+                        Ok(v) => match check_notch_depth(v) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                self.status = e;
+                                return Task::none();
+                            }
+                        },
+                        Err(e) => {
+                            self.status = format!("notch depth parse error: {e}");
+                            return Task::none();
+                        }
+                    }
+                };
+                let attenuation = if !self.app.filter.uses_attenuation() {
+                    self.app.attenuation
+                } else {
+                    match self.attenuation_s.trim().parse::<f64>() {
+                        Ok(v) => v,
+                        Err(e) => {
+                            self.status = format!("attenuation parse error: {e}");
+                            return Task::none();
+                        }
+                    }
+                };
+
+                let root_tol = match self.root_tol_s.trim().parse::<f64>() {
+                    Ok(v) if v >= 0.0 => v,
+                    Ok(v) => {
+                        self.status = format!("root tolerance must be non-negative, got {v}");
+                        return Task::none();
+                    }
                     Err(e) => {
-                        self.status = format!("attenuation parse error: {e}");
-                        return;
+                        self.status = format!("root tolerance parse error: {e}");
+                        return Task::none();
                     }
                 };
 
                 self.app.set_cutoff(cutoff);
                 self.app.set_order(order);
+                self.app.set_lowpass_order(lowpass_order);
+                self.app.set_smoothing_window(smoothing_window);
                 self.app.set_ripple(ripple);
+                self.app.set_pole_radius(pole_radius);
+                self.app.set_notch_depth(notch_depth);
                 self.app.set_attenuation(attenuation);
-
-                // Run computation
-                if let Err(e) = self.app.filter() {
-                    self.status = format!("Error: {e}");
-                    return;
+                self.app.set_root_tol(root_tol);
+                if let structures::filters::FftPadding::Length(_) = self.app.fft_padding {
+                    match self.fft_pad_len() {
+                        Ok(n) => self
+                            .app
+                            .set_fft_padding(structures::filters::FftPadding::Length(n)),
+                        Err(e) => {
+                            self.status = format!("Error: {e}");
+                            return Task::none();
+                        }
+                    }
                 }
-                if let Err(e) = self.app.fft_filtered() {
+
+                // Blank Bode limits fall back to the full range
+                let full = math::BodeRange::full(self.app.fs);
+                let bode_limit = |s: &str, default: f64| match s.trim() {
+                    "" => Ok(default),
+                    t => t
+                        .parse::<f64>()
+                        .map_err(|e| format!("Bode range parse error: {e}")),
+                };
+                let bode_range = bode_limit(&self.bode_min_s, full.f_min).and_then(|f_min| {
+                    let f_max = bode_limit(&self.bode_max_s, full.f_max)?;
+                    self.app.set_bode_range(f_min, f_max)
+                });
+                if let Err(e) = bode_range {
                     self.status = format!("Error: {e}");
-                    return;
+                    return Task::none();
                 }
-                if let Err(e) = self.app.generate_bode() {
+                let db_floor = self
+                    .db_floor_s
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|e| format!("dB floor parse error: {e}"))
+                    .and_then(|v| self.app.set_db_floor(v));
+                if let Err(e) = db_floor {
                     self.status = format!("Error: {e}");
-                    return;
+                    return Task::none();
                 }
 
+                return self.spawn_compute();
+            }
+            Message::ComputationDone(job, result) => {
+                self.calculating = false;
+                let merged = self.app.merge_results(*job);
+                self.status = match result.and(merged) {
+                    Ok(()) => String::new(),
+                    Err(e) => format!("Error: {e}"),
+                };
+
                 // Format output
                 self.zeros_out = match &self.app.zeros {
-                    Some(z) if !z.is_empty() => z
-                        .iter()
-                        .map(|c| format!("{:+.6} {:+.6}j", c.re, c.im))
+                    Some(z) if !z.is_empty() => math::group_roots(z, self.app.root_tol)
+                        .into_iter()
+                        .map(|(c, k)| fmt_root(c, k))
                         .collect::<Vec<_>>()
                         .join("\n"),
                     _ => "(none)".into(),
                 };
 
                 self.poles_out = match &self.app.poles {
-                    Some(p) if !p.is_empty() => p
-                        .iter()
-                        .map(|c| format!("{:+.6} {:+.6}j", c.re, c.im))
+                    Some(p) if !p.is_empty() => math::group_roots(p, self.app.root_tol)
+                        .into_iter()
+                        .map(|(c, k)| fmt_root(c, k))
                         .collect::<Vec<_>>()
                         .join("\n"),
                     _ => "(none)".into(),
@@ -185,15 +914,89 @@ impl Gui {
                 self.ts_cache.clear();
                 self.fft_cache.clear();
                 self.bode_cache.clear();
+                self.nyquist_cache.clear();
+                self.step_cache.clear();
+                self.acf_cache.clear();
+                self.phase_cache.clear();
                 self.candles_cache.clear();
             }
+            Message::ShowSectionsToggled(b) => self.show_sections = b,
+            Message::ShowPeakModelToggled(b) => {
+                self.show_peak_model = b;
+                self.ts_cache.clear();
+            }
+            Message::ShowEnvelopeToggled(b) => {
+                self.show_envelope = b;
+                self.ts_cache.clear();
+            }
+            Message::TimeLogYToggled(b) => {
+                self.ts_log_y = b;
+                self.ts_cache.clear();
+            }
+            Message::ExplainToggled(b) => {
+                self.explain = b;
+                self.plot_cache.clear();
+                self.bode_cache.clear();
+                self.nyquist_cache.clear();
+            }
+            Message::KeyPressed(Named::Enter) => {
+                // Same gating as the Calculate button
+                if !self.modal_state.show_modal && !self.calculating {
+                    return self.update(Message::Calculate);
+                }
+            }
+            Message::KeyPressed(Named::Escape) => {
+                return self.update(if self.modal_state.show_modal {
+                    Message::CloseDataModal
+                } else {
+                    Message::ClearOutput
+                });
+            }
+            Message::KeyPressed(_) => {}
             Message::WeightSelectionChanged(s) => self.modal_state.weight_entry = s,
-            Message::OpenDataModal => self.modal_state.show_modal = true,
+            Message::OpenDataModal => {
+                self.modal_state.show_modal = true;
+                self.modal_state.confirm_clear = false;
+            }
             Message::CloseDataModal => {
                 self.modal_state.show_modal = false;
-                let sorted = self.modal_state.get_vals_sorted_by_date();
+                let sorted = self.modal_state.get_entries_sorted_by_date();
                 self.status = format!("Total data points: {}", sorted.len());
-                self.app.set_app_data(sorted);
+                self.app.set_dated_data(sorted);
+            }
+            Message::UseModalData => {
+                self.modal_state.show_modal = false;
+                let sorted = self.modal_state.get_entries_sorted_by_date();
+                let n = sorted.len();
+                self.status = match self.app.set_dated_data(sorted) {
+                    Some(warning) => format!("Warning: {warning}"),
+                    None => format!("Using {n} logged data points, press Calculate to filter"),
+                };
+                self.ts_cache.clear();
+            }
+            Message::BrowseLogFile => {
+                let dir = self.session.load_dir();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("Dated series", &["csv", "json"])
+                            .set_directory(dir)
+                            .pick_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::LogFilePicked,
+                );
+            }
+            Message::LogFilePicked(None) => {}
+            Message::LogFilePicked(Some(path)) => {
+                self.modal_state.date_status = match self.modal_state.load_file(&path) {
+                    Ok(n) => {
+                        self.session.record_load(&path);
+                        format!("Merged {n} entries from {}", path.display())
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
             }
             Message::UpdateDate(d) => match logic::iced_date_to_local_datetime(d) {
                 Ok(date) => self.modal_state.switch_date_state(date),
@@ -205,131 +1008,1320 @@ impl Gui {
                     Err(e) => e,
                 }
             }
+            Message::DeleteWeightSelection => {
+                let date = self.modal_state.selected_datetime;
+                self.modal_state.date_status = if self.modal_state.delete_entry() {
+                    format!("Deleted entry on {date}")
+                } else {
+                    format!("No entry on {date}")
+                }
+            }
+            Message::ClearWeightLog => self.modal_state.confirm_clear = true,
+            Message::ConfirmClearWeightLog(true) => {
+                let n = self.modal_state.clear_entries();
+                self.modal_state.date_status = format!("Cleared {n} logged entries");
+            }
+            Message::ConfirmClearWeightLog(false) => self.modal_state.confirm_clear = false,
+            Message::WindowResized(size) => self.session.set_window_size(size),
+            Message::FileDropped(path) => {
+                // The running job would overwrite whatever gets loaded now
+                if self.calculating || self.modal_state.show_modal {
+                    self.status = String::from("Finish the current action before dropping a file");
+                    return Task::none();
+                }
+                let is_json = path
+                    .extension()
+                    .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+                if is_json {
+                    self.status = match load_json_series(&path).and_then(|series| {
+                        let n = series.values.len();
+                        self.app.set_series(series).map(|()| n)
+                    }) {
+                        Ok(n) => {
+                            self.session.record_load(&path);
+                            self.fs_s = self.app.fs.to_string();
+                            self.ts_cache.clear();
+                            self.candles_cache.clear();
+                            format!(
+                                "Loaded {n} points at {} {} from {}",
+                                self.app.fs,
+                                per_unit("samples", self.app.time_unit()),
+                                path.display()
+                            )
+                        }
+                        Err(e) => format!("Error: {e}"),
+                    };
+                    return Task::none();
+                }
+                self.status = match read_series_csv(&path) {
+                    Ok((values, dates)) => {
+                        self.session.record_load(&path);
+                        let n = values.len();
+                        let warning = match dates {
+                            Some(dates) => self
+                                .app
+                                .set_dated_data(dates.into_iter().zip(values).collect()),
+                            None => {
+                                self.app.set_app_data(values);
+                                None
+                            }
+                        };
+                        self.ts_cache.clear();
+                        self.candles_cache.clear();
+                        warning.unwrap_or(format!("Loaded {n} points from {}", path.display()))
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
+            Message::ExportCsv => {
+                let dir = self.session.save_dir();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_directory(dir)
+                            .set_file_name(DEFAULT_EXPORT_FILENAME)
+                            .save_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::CsvPathPicked,
+                );
+            }
+            Message::CsvPathPicked(None) => {}
+            Message::CsvPathPicked(Some(path)) => {
+                self.status = match self.app.export_csv(&path) {
+                    Ok((series, spectrum)) => {
+                        self.session.record_save(&series);
+                        format!("Exported {} and {}", series.display(), spectrum.display())
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
+            Message::SessionNameChanged(s) => self.session_name_s = s,
+            Message::SaveSession => {
+                let path = self.session_path();
+                self.status = match self.app.save_session(&path) {
+                    Ok(()) => {
+                        self.session.record_save(&path);
+                        format!("Saved session to {}", path.display())
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
+            Message::LoadSession => {
+                let dir = self.session.load_dir();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("Session", &["json"])
+                            .set_directory(dir)
+                            .pick_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::SessionPathPicked,
+                );
+            }
+            Message::SessionPathPicked(None) => {}
+            Message::SessionPathPicked(Some(path)) => {
+                // A job started while the dialog was open would overwrite the load
+                if self.calculating {
+                    self.status = String::from("Finish the current calculation before loading");
+                    return Task::none();
+                }
+                match App::load_session(&path) {
+                    Ok(app) => {
+                        self.session.record_load(&path);
+                        self.app = app;
+                        self.sync_inputs();
+                        (self.zeros_out, self.poles_out) = (String::new(), String::new());
+                        self.plot_cache.clear();
+                        self.ts_cache.clear();
+                        self.fft_cache.clear();
+                        self.bode_cache.clear();
+                        self.nyquist_cache.clear();
+                        self.step_cache.clear();
+                        self.acf_cache.clear();
+                        self.phase_cache.clear();
+                        self.candles_cache.clear();
+                        // Derived results were not saved, so rebuild them, but
+                        // only from settings Calculate would have accepted
+                        self.status = match self.app.validate_inputs() {
+                            Ok(()) if self.app.raw_data.is_some() => return self.spawn_compute(),
+                            Ok(()) => format!("Loaded session from {}", path.display()),
+                            Err(e) => format!(
+                                "Loaded session from {}, not calculated: {e}",
+                                path.display()
+                            ),
+                        };
+                    }
+                    Err(e) => self.status = format!("Error: {e}"),
+                }
+            }
+            Message::PlotKindChanged(kind) => self.plot_kind = kind,
+            Message::PlotSizeChanged(s) => self.plot_size_s = s,
+            Message::ExportPlot(kind) => {
+                // Catch a bad size before asking where to save
+                if let Err(e) = self.plot_size() {
+                    self.status = format!("Error: {e}");
+                    return Task::none();
+                }
+                let dir = self.session.save_dir();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("PNG image", &["png"])
+                            .set_directory(dir)
+                            .set_file_name(DEFAULT_PLOT_FILENAME)
+                            .save_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    move |path| Message::PlotPathPicked(kind, path),
+                );
+            }
+            Message::PlotPathPicked(_, None) => {}
+            Message::PlotPathPicked(kind, Some(path)) => {
+                self.status = match self.export_plot(kind, &path) {
+                    Ok(size) => {
+                        self.session.record_save(&path);
+                        format!(
+                            "Saved {kind} plot ({}x{}) to {}",
+                            size.width,
+                            size.height,
+                            path.display()
+                        )
+                    }
+                    Err(e) => format!("Error: {e}"),
+                };
+            }
+            Message::CopyCoefficients => {
+                if let Some(data) = self.app.filtered_data.as_ref() {
+                    self.status = String::from("Coefficients copied to clipboard");
+                    return iced::clipboard::write(coefficients_clipboard(&data.b, &data.a));
+                }
+                self.status = String::from("No coefficients yet, run Calculate first");
+            }
             Message::NoOp => {}
         }
+        Task::none()
+    }
+
+    // Relative names resolve against the last directory saved to
+    fn session_path(&self) -> std::path::PathBuf {
+        let name = std::path::PathBuf::from(self.session_name_s.trim());
+        if name.is_absolute() {
+            name
+        } else {
+            self.session.save_dir().join(name)
+        }
+    }
+
+    // A nonlinear run leaves no transfer function behind
+    fn empty_text(&self) -> &'static str {
+        if self.app.is_linear() {
+            "No data loaded"
+        } else {
+            "N/A for nonlinear filter"
+        }
+    }
+
+    // Plot programs take their cache so exports can draw with a fresh one
+    fn pz_view<'a>(&'a self, cache: &'a Cache) -> views::pz::PzPlotView<'a> {
+        views::pz::PzPlotView {
+            zeros: self.app.zeros.as_deref(),
+            poles: self.app.poles.as_deref(),
+            root_tol: self.app.root_tol,
+            explain: self.explain,
+            empty_text: self.empty_text(),
+            cache,
+        }
+    }
+
+    fn nyquist_view<'a>(&'a self, cache: &'a Cache) -> views::nyquist::NyquistView<'a> {
+        views::nyquist::NyquistView {
+            curve: self.app.nyquist.as_deref(),
+            empty_text: self.empty_text(),
+            cache,
+        }
+    }
+
+    fn bode_view<'a>(&'a self, cache: &'a Cache) -> views::bode::BodeView<'a> {
+        views::bode::BodeView {
+            freqs: if let Some(f) = self.app.bode_plot.as_ref() {
+                Some(&f.0)
+            } else {
+                None
+            },
+            mag_db: if let Some(m) = self.app.bode_plot.as_ref() {
+                Some(&m.1)
+            } else {
+                None
+            },
+            sweep: self.app.center_sweep.as_deref(),
+            explain: self.explain,
+            // All-pole lowpass designs roll off at -20 dB/decade per order;
+            // Chebyshev II and elliptic stopband zeros break that, so those
+            // show just the measured slope
+            theory_slope: (self.app.stages.is_empty()
+                && matches!(
+                    self.app.filter,
+                    structures::filters::FilterType::BUTTERWORTH
+                        | structures::filters::FilterType::CHEBYSHEV1
+                        | structures::filters::FilterType::BESSEL
+                )
+                && self.app.band == structures::filters::FilterBand::Lowpass)
+                .then(|| -20.0 * self.app.order as f64),
+            cutoff: self
+                .app
+                .bode_plot
+                .as_ref()
+                .filter(|_| self.app.stages.is_empty() && self.app.filter.is_designed())
+                .map(|_| 0.5 * self.app.fs * self.app.cutoff_freq),
+            x_scale: self.app.bode_x_scale,
+            cache,
+            time_unit: self.app.time_unit(),
+            y_unit: "dB",
+            empty_text: self.empty_text(),
+            reference: self
+                .app
+                .reference_bode
+                .as_ref()
+                .map(|(f, m)| (f.as_slice(), m.as_slice())),
+            reference_label: &self.app.reference_label,
+            mask: self.bode_mask(),
+        }
+    }
+
+    // Spec from the cutoff, ripple and the order estimator's stop edge/attenuation,
+    // for a single designed lowpass or highpass
+    fn bode_mask(&self) -> Option<views::bode::BodeMask> {
+        use structures::filters::FilterBand;
+        let highpass = match self.app.band {
+            FilterBand::Lowpass => false,
+            FilterBand::Highpass => true,
+            FilterBand::Bandstop | FilterBand::Bandpass => return None,
+        };
+        if !self.app.stages.is_empty() || !self.app.filter.is_designed() {
+            return None;
+        }
+        let nyquist = 0.5 * self.app.fs;
+        let stop_edge = self.stop_edge_s.trim().parse::<f64>().ok()?;
+        let stop_edge = nyquist * math::cutoff_period_to_nyquist(stop_edge, self.app.fs).ok()?;
+        let attenuation = self.stop_atten_s.trim().parse::<f64>().ok()?;
+        let pass_edge = nyquist * self.app.cutoff_freq;
+        // The stop edge has to sit on the far side of the transition band
+        if attenuation <= 0.0 || (stop_edge > pass_edge) == highpass {
+            return None;
+        }
+        Some(views::bode::BodeMask {
+            pass_edge,
+            stop_edge,
+            // Without a ripple input the cutoff is taken as the -3 dB point
+            ripple: if self.app.filter.uses_ripple() {
+                self.app.ripple
+            } else {
+                3.0
+            },
+            attenuation,
+            highpass,
+        })
+    }
+
+    fn phase_view<'a>(&'a self, cache: &'a Cache) -> views::bode::BodeView<'a> {
+        views::bode::BodeView {
+            freqs: self.app.bode_phase.as_ref().map(|p| p.0.as_slice()),
+            mag_db: self.app.bode_phase.as_ref().map(|p| p.1.as_slice()),
+            sweep: None,
+            explain: false,
+            theory_slope: None,
+            cutoff: None,
+            x_scale: self.app.bode_x_scale,
+            cache,
+            time_unit: self.app.time_unit(),
+            y_unit: "deg",
+            empty_text: self.empty_text(),
+            reference: self
+                .app
+                .reference_phase
+                .as_ref()
+                .map(|(f, p)| (f.as_slice(), p.as_slice())),
+            reference_label: &self.app.reference_label,
+            mask: None,
+        }
+    }
+
+    // Non-DC rfft bins of the raw data, at least 1 so the slider has a range
+    fn max_keep_bins(&self) -> u32 {
+        self.app.raw_data.as_ref().map_or(1, |d| d.len() / 2).max(1) as u32
+    }
+
+    // |X(f)| at the typed frequency, with the period it corresponds to
+    fn goertzel_readout(&self) -> String {
+        let s = self.goertzel_s.trim();
+        if s.is_empty() {
+            return String::new();
+        }
+        let Ok(freq) = s.parse::<f64>() else {
+            return format!("{s} is not a number");
+        };
+        match self.app.goertzel_at(freq) {
+            Ok(mag) if freq > 0.0 => format!(
+                "|X| = {} (period {} {})",
+                fmt_tick(mag),
+                fmt_tick(1.0 / freq),
+                self.app.time_unit()
+            ),
+            Ok(mag) => format!("|X| = {} (DC)", fmt_tick(mag)),
+            Err(e) => e,
+        }
+    }
+
+    // Main cycle length from the refined FFT peak, with the autocorrelation's
+    // estimate alongside once Calculate has run
+    fn dominant_period_readout(&self) -> String {
+        let Some(samples) = self.app.dominant_period else {
+            return String::new();
+        };
+        let unit = self.app.time_unit();
+        let mut s = format!(
+            "Dominant period \u{2248} {} {unit}",
+            fmt_tick(samples / self.app.fs)
+        );
+        if let Some(lag) = self.app.acf_period {
+            s.push_str(&format!(
+                " (autocorrelation: {})",
+                fmt_tick(lag as f64 / self.app.fs)
+            ));
+        }
+        s
+    }
+
+    fn raw_dates(&self) -> Option<&[chrono::NaiveDate]> {
+        self.app.raw_dates.as_deref()
+    }
+
+    fn candle_view<'a>(&'a self, cache: &'a Cache) -> views::candles::CandlePanelView<'a> {
+        views::candles::CandlePanelView {
+            zeros: self.app.zeros.as_deref(),
+            poles: self.app.poles.as_deref(),
+            root_tol: self.app.root_tol,
+            candles: self.app.candles.as_deref(),
+            filtered: self
+                .app
+                .filtered_data
+                .as_ref()
+                .map(|f| f.filtered_data.as_slice()),
+            dates: self.raw_dates(),
+            per_candle: self.app.candle_length.into(),
+            overlays: &self.candle_smas,
+            bollinger: self.show_bollinger.then_some(self.bollinger),
+            cache,
+            title: "Candle View",
+            theme: self.app.candle_palette.into(),
+        }
+    }
+
+    // The typed "WIDTHxHEIGHT", each side in 1..=MAX_PLOT_SIDE
+    fn plot_size(&self) -> Result<iced::Size<u32>, String> {
+        let (w, h) = self
+            .plot_size_s
+            .trim()
+            .split_once(['x', 'X'])
+            .ok_or(format!("size '{}' is not WIDTHxHEIGHT", self.plot_size_s))?;
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<u32>()
+                .map_err(|e| format!("plot size parse error: {e}"))
+        };
+        let size = iced::Size::new(parse(w)?, parse(h)?);
+        if !(1..=MAX_PLOT_SIDE).contains(&size.width) || !(1..=MAX_PLOT_SIDE).contains(&size.height)
+        {
+            return Err(format!(
+                "plot size must be 1 to {MAX_PLOT_SIDE} px per side, got {}x{}",
+                size.width, size.height
+            ));
+        }
+        Ok(size)
+    }
+
+    // The typed FFT pad length, in 1..=MAX_FFT_LEN samples
+    fn fft_pad_len(&self) -> Result<usize, String> {
+        let n = self
+            .fft_pad_s
+            .trim()
+            .parse::<usize>()
+            .map_err(|e| format!("pad length parse error: {e}"))?;
+        if !(1..=MAX_FFT_LEN).contains(&n) {
+            return Err(format!(
+                "pad length must be 1 to {MAX_FFT_LEN} samples, got {n}"
+            ));
+        }
+        Ok(n)
+    }
+
+    // Redraw one plot offscreen at the typed size and save it as PNG
+    fn export_plot(
+        &self,
+        kind: views::export::PlotKind,
+        path: &std::path::Path,
+    ) -> Result<iced::Size<u32>, String> {
+        use views::export::{PlotKind, rasterize};
+        let size = self.plot_size()?;
+        let cache = Cache::new();
+        let rgba = match kind {
+            PlotKind::BodeMagnitude => rasterize(&self.bode_view(&cache), size)?,
+            PlotKind::BodePhase => rasterize(&self.phase_view(&cache), size)?,
+            PlotKind::PoleZero => rasterize(&self.pz_view(&cache), size)?,
+            PlotKind::Nyquist => rasterize(&self.nyquist_view(&cache), size)?,
+            PlotKind::Candles => rasterize(&self.candle_view(&cache), size)?,
+        };
+        write_png(path, &rgba, size.width, size.height)?;
+        Ok(size)
+    }
+
+    // Run computation on a copy so the UI stays responsive
+    fn spawn_compute(&mut self) -> Task<Message> {
+        let mut job = self.app.clone();
+        self.calculating = true;
+        self.status = String::from("Calculating\u{2026}");
+        Task::perform(
+            async move {
+                let result = job.compute();
+                (Box::new(job), result)
+            },
+            |(app, result)| Message::ComputationDone(app, result),
+        )
+    }
+
+    // Refill the text inputs from the app after it was replaced wholesale
+    fn sync_inputs(&mut self) {
+        let period = |w: f64| (math::NYQUIST_PERIOD / (w * self.app.fs)).to_string();
+        let join = |c: &[f64]| {
+            c.iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        self.cutoff_s = period(self.app.cutoff_freq);
+        self.second_cutoff_s = period(self.app.second_cutoff_freq);
+        self.order_s = self.app.order.to_string();
+        self.lowpass_order_s = self.app.lowpass_order.to_string();
+        self.window_len_s = self.app.smoothing_window.to_string();
+        self.ripple_s = self.app.ripple.to_string();
+        self.pole_radius_s = self.app.pole_radius.to_string();
+        self.notch_depth_s = self.app.notch_depth.to_string();
+        self.attenuation_s = self.app.attenuation.to_string();
+        self.fs_s = self.app.fs.to_string();
+        self.custom_b_s = join(&self.app.custom_b);
+        self.custom_a_s = join(&self.app.custom_a);
+        if let structures::candle::CandleLengths::Custom(n) = self.app.candle_length {
+            self.candle_custom_s = n.to_string();
+        }
+    }
+
+    // Recompute spectra already on screen after a window or padding change
+    fn refresh_spectra(&mut self) {
+        if self.app.filtered_data.is_some()
+            && let Err(e) = self.app.fft_filtered()
+        {
+            self.status = format!("Error: {e}");
+        }
+        if self.app.raw_spectrum.is_some()
+            && let Err(e) = self.app.fft_raw()
+        {
+            self.status = format!("Error: {e}");
+        }
+        self.fft_cache.clear();
+        self.ts_cache.clear();
+        self.acf_cache.clear();
+    }
+
+    fn subscription(&self) -> iced::Subscription<Message> {
+        iced::Subscription::batch([
+            iced::window::resize_events().map(|(_id, size)| Message::WindowResized(size)),
+            iced::event::listen_with(|event, status, _id| match event {
+                iced::Event::Window(iced::window::Event::FileDropped(path)) => {
+                    Some(Message::FileDropped(path))
+                }
+                // Keys a focused text input consumed (typing, Escape to
+                // unfocus) come through as Captured and are left alone
+                iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                    key: iced::keyboard::Key::Named(named @ (Named::Enter | Named::Escape)),
+                    ..
+                }) if status == iced::event::Status::Ignored => Some(Message::KeyPressed(named)),
+                _ => None,
+            }),
+        ])
     }
 
     fn view(&self) -> Element<'_, Message> {
-        let filter_options = [
-            structures::filters::FilterType::BUTTERWORTH,
-            structures::filters::FilterType::CHEBYSHEV1,
-            structures::filters::FilterType::CHEBYSHEV2,
+        let time_unit = self.app.time_unit();
+        let cycles_unit = per_unit("cycles", time_unit);
+        // Custom keeps the last valid width typed in
+        let custom_width = match self.app.candle_length {
+            structures::candle::CandleLengths::Custom(n) => n,
+            _ => self
+                .candle_custom_s
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .unwrap_or(1),
+        };
+        let pad_len = match self.app.fft_padding {
+            structures::filters::FftPadding::Length(n) => n,
+            _ => self.fft_pad_len().unwrap_or(1024),
+        };
+        let padding_options = [
+            structures::filters::FftPadding::Off,
+            structures::filters::FftPadding::NextPow2,
+            structures::filters::FftPadding::Length(pad_len),
         ];
         let candle_options = [
             structures::candle::CandleLengths::Weekly,
             structures::candle::CandleLengths::Monthly,
             structures::candle::CandleLengths::Yearly,
+            structures::candle::CandleLengths::Custom(custom_width),
         ];
 
         let controls = column![
             row![
-                text("Filter:").width(Length::Shrink),
-                pick_list(
-                    filter_options,
-                    Some(self.app.filter),
-                    Message::FilterChanged
-                )
-                .width(Length::Fill),
-                text("Candle Lengths:").width(Length::Shrink),
-                pick_list(
-                    candle_options,
-                    Some(self.app.candle_length),
-                    Message::CandleLengthsChanged
-                )
+                text("Filter:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::FilterType::ALL,
+                    Some(self.app.filter),
+                    Message::FilterChanged
+                )
+                .width(Length::Fill),
+                text("Band:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::FilterBand::ALL,
+                    Some(self.app.band),
+                    Message::BandChanged
+                ),
+                text("Candle Lengths:").width(Length::Shrink),
+                pick_list(
+                    candle_options,
+                    Some(self.app.candle_length),
+                    Message::CandleLengthsChanged
+                ),
+            ]
+            .push(
+                matches!(
+                    self.app.candle_length,
+                    structures::candle::CandleLengths::Custom(_)
+                )
+                .then(|| {
+                    text_input("samples", &self.candle_custom_s)
+                        .on_input_maybe(if !self.modal_state.show_modal {
+                            Some(Message::CustomCandleChanged)
+                        } else {
+                            None
+                        })
+                        .width(Length::Fixed(80.0))
+                })
+            )
+            .spacing(12)
+            .align_y(Alignment::Center),
+            (self.app.filter == structures::filters::FilterType::CUSTOM).then(|| {
+                row![
+                    text("b:").width(Length::Shrink),
+                    text_input("e.g. 0.2, 0.2", &self.custom_b_s)
+                        .on_input_maybe(if !self.modal_state.show_modal {
+                            Some(Message::CustomBChanged)
+                        } else {
+                            None
+                        })
+                        .width(Length::FillPortion(1)),
+                    text("a:").width(Length::Shrink),
+                    text_input("e.g. 1, -0.6", &self.custom_a_s)
+                        .on_input_maybe(if !self.modal_state.show_modal {
+                            Some(Message::CustomAChanged)
+                        } else {
+                            None
+                        })
+                        .width(Length::FillPortion(1)),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center)
+            }),
+            row![
+                text("Normalize:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::NormalizeMode::ALL,
+                    Some(self.app.normalize),
+                    Message::NormalizeChanged
+                ),
+                text("Gaps:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::GapMode::ALL,
+                    Some(self.app.gap_mode),
+                    Message::GapModeChanged
+                ),
+                text("Edge handling:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::EdgeHandling::ALL,
+                    Some(self.app.edge_handling),
+                    Message::EdgeHandlingChanged
+                )
+                .width(Length::Fill),
+                text("Phase:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::FilterPhase::ALL,
+                    Some(self.app.phase),
+                    Message::FilterPhaseChanged
+                ),
+                text("FFT window:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::Window::ALL,
+                    Some(self.app.spectrum_window),
+                    Message::WindowChanged
+                ),
+                text("Detrend:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::DetrendMode::ALL,
+                    Some(self.app.detrend),
+                    Message::DetrendChanged
+                ),
+                text("Padding:").width(Length::Shrink),
+                pick_list(
+                    padding_options,
+                    Some(self.app.fft_padding),
+                    Message::FftPaddingChanged
+                ),
+            ]
+            .push(
+                matches!(
+                    self.app.fft_padding,
+                    structures::filters::FftPadding::Length(_)
+                )
+                .then(|| {
+                    text_input("samples", &self.fft_pad_s)
+                        .on_input_maybe(if !self.modal_state.show_modal {
+                            Some(Message::FftPadLengthChanged)
+                        } else {
+                            None
+                        })
+                        .width(Length::Fixed(80.0))
+                })
+            )
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text("Scale:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::SpectrumScale::ALL,
+                    Some(self.app.spectrum_scale),
+                    Message::SpectrumScaleChanged
+                ),
+                text("Floor (dB):").width(Length::Shrink),
+                text_input("-120", &self.db_floor_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::DbFloorChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::Fixed(70.0)),
+                text("Bode x:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::BodeXScale::ALL,
+                    Some(self.app.bode_x_scale),
+                    Message::BodeXScaleChanged
+                ),
+                checkbox(self.show_sections)
+                    .label("Show SOS sections")
+                    .on_toggle_maybe(if !self.modal_state.show_modal {
+                        Some(Message::ShowSectionsToggled)
+                    } else {
+                        None
+                    }),
+                checkbox(self.show_peak_model)
+                    .label("Show peak model")
+                    .on_toggle_maybe(if !self.modal_state.show_modal {
+                        Some(Message::ShowPeakModelToggled)
+                    } else {
+                        None
+                    }),
+                checkbox(self.show_envelope)
+                    .label("Show envelope")
+                    .on_toggle_maybe(if !self.modal_state.show_modal {
+                        Some(Message::ShowEnvelopeToggled)
+                    } else {
+                        None
+                    }),
+                checkbox(self.ts_log_y).label("Log y axis").on_toggle_maybe(
+                    if !self.modal_state.show_modal {
+                        Some(Message::TimeLogYToggled)
+                    } else {
+                        None
+                    }
+                ),
+                checkbox(self.explain)
+                    .label("Explain this filter")
+                    .on_toggle_maybe(if !self.modal_state.show_modal {
+                        Some(Message::ExplainToggled)
+                    } else {
+                        None
+                    }),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text("Spectrum:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::SpectrumMode::ALL,
+                    Some(self.app.spectrum_mode),
+                    Message::SpectrumModeChanged
+                ),
+                text("Segment:").width(Length::Shrink),
+                text_input("samples", &self.welch_segment_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal
+                            && self.app.spectrum_mode != structures::filters::SpectrumMode::Fft
+                        {
+                            Some(Message::WelchSegmentChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::Fixed(70.0)),
+                text("Overlap:").width(Length::Shrink),
+                text_input("samples", &self.welch_overlap_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal
+                            && self.app.spectrum_mode != structures::filters::SpectrumMode::Fft
+                        {
+                            Some(Message::WelchOverlapChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::Fixed(70.0)),
+                text(format!("Tone ({cycles_unit}):")).width(Length::Shrink),
+                text_input("e.g. 0.05", &self.goertzel_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::GoertzelFreqChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::Fixed(80.0)),
+                text(self.goertzel_readout()),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text(format!("Sample rate ({}):", per_unit("samples", time_unit)))
+                    .width(Length::Shrink),
+                text_input("e.g. 24", &self.fs_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::SampleRateChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+                text(format!("Cutoff period ({time_unit}):")).width(Length::Shrink),
+                text_input("e.g. 4.2", &self.cutoff_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal && self.app.filter.is_designed() {
+                            Some(Message::CutoffChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+                text(format!("Band to ({time_unit}):")).width(Length::Shrink),
+                text_input("bandstop/bandpass only", &self.second_cutoff_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal && self.app.band.uses_second_cutoff() {
+                            Some(Message::SecondCutoffChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text("Order:").width(Length::Shrink),
+                text_input("e.g. 4", &self.order_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal && self.app.filter.is_designed() {
+                            Some(Message::OrderChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+                text("Lowpass order:").width(Length::Shrink),
+                text_input("bandpass only", &self.lowpass_order_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal
+                            && self.app.filter.is_designed()
+                            && self.app.band == structures::filters::FilterBand::Bandpass
+                        {
+                            Some(Message::LowpassOrderChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+                text("Window:").width(Length::Shrink),
+                text_input("e.g. 7", &self.window_len_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal && self.app.filter.uses_window() {
+                            Some(Message::SmoothingWindowChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+                text("Ripple (dB):").width(Length::Shrink),
+                text_input("e.g. 5", &self.ripple_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal && self.app.filter.uses_ripple() {
+                            Some(Message::RippleChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+                text("Pole radius:").width(Length::Shrink),
+                text_input("e.g. 0.9", &self.pole_radius_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal && self.app.filter.uses_pole_radius() {
+                            Some(Message::PoleRadiusChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+                text("Notch depth:").width(Length::Shrink),
+                text_input("e.g. 1", &self.notch_depth_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal && self.app.filter.uses_notch_depth() {
+                            Some(Message::NotchDepthChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+                text("Attenuation (dB):").width(Length::Shrink),
+                text_input("e.g. 40", &self.attenuation_s)
+                    .on_input_maybe(
+                        if !self.modal_state.show_modal && self.app.filter.uses_attenuation() {
+                            Some(Message::AttenuationChanged)
+                        } else {
+                            None
+                        }
+                    )
+                    .width(Length::FillPortion(1)),
+                text("Root tol:").width(Length::Shrink),
+                text_input("e.g. 1e-3", &self.root_tol_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::RootTolChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                button("Edit/Load Data").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::OpenDataModal)
+                } else {
+                    None
+                }),
+                button("Calculate").on_press_maybe(
+                    if !self.modal_state.show_modal && !self.calculating {
+                        Some(Message::Calculate)
+                    } else {
+                        None
+                    }
+                ),
+                button("Clear").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::ClearOutput)
+                } else {
+                    None
+                }),
+                button("Reset Parameters").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::ResetParams)
+                } else {
+                    None
+                })
+            ]
+            .spacing(12),
+            row![
+                text(format!("Reference sine ({cycles_unit}):")).width(Length::Shrink),
+                text_input("e.g. 0.1", &self.sine_freq_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::SineFreqChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+                text("Amplitude:").width(Length::Shrink),
+                text_input("e.g. 2", &self.sine_amp_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::SineAmplitudeChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+                button("Load Sine").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::LoadSine)
+                } else {
+                    None
+                }),
             ]
             .spacing(12)
             .align_y(Alignment::Center),
             row![
-                text("Cutoff period (days):").width(Length::Shrink),
-                text_input("e.g. 4.2", &self.cutoff_s)
+                text("Fourier harmonics:").width(Length::Shrink),
+                text_input("e.g. 3", &self.harmonics_s)
                     .on_input_maybe(if !self.modal_state.show_modal {
-                        Some(Message::CutoffChanged)
+                        Some(Message::HarmonicsChanged)
                     } else {
                         None
                     })
                     .width(Length::FillPortion(1)),
+                button("Fit").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::FitFourier)
+                } else {
+                    None
+                }),
             ]
             .spacing(12)
             .align_y(Alignment::Center),
             row![
-                text("Order:").width(Length::Shrink),
-                text_input("e.g. 4", &self.order_s)
+                text(format!("Keep top {} bins:", self.keep_bins)).width(Length::Shrink),
+                slider(
+                    1..=self.max_keep_bins(),
+                    self.keep_bins.min(self.max_keep_bins()),
+                    Message::KeepBinsChanged
+                )
+                .width(Length::FillPortion(1)),
+                button("Keep largest").on_press_maybe(
+                    if !self.modal_state.show_modal && self.app.raw_data.is_some() {
+                        Some(Message::Denoise)
+                    } else {
+                        None
+                    }
+                ),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text("Trend line:").width(Length::Shrink),
+                pick_list(
+                    structures::filters::TrendModel::ALL,
+                    Some(self.trend_model),
+                    Message::TrendModelChanged
+                ),
+                button("Fit trend").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::FitTrend)
+                } else {
+                    None
+                }),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text(format!(
+                    "Cascade: {} frozen stage(s)",
+                    self.app.stages.len()
+                ))
+                .width(Length::Shrink),
+                button("Add as Stage").on_press_maybe(
+                    if !self.modal_state.show_modal && self.app.filtered_data.is_some() {
+                        Some(Message::AddStage)
+                    } else {
+                        None
+                    }
+                ),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            column(self.app.stages.iter().enumerate().map(|(i, stage)| {
+                row![
+                    text(format!("{}. {stage}", i + 1)).width(Length::Fill),
+                    button("Remove").on_press_maybe(if !self.modal_state.show_modal {
+                        Some(Message::RemoveStage(i))
+                    } else {
+                        None
+                    }),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center)
+                .into()
+            }))
+            .spacing(4),
+            row![
+                text(if self.app.reference_bode.is_some() {
+                    format!("Bode reference: {}", self.app.reference_label)
+                } else {
+                    String::from("Bode reference: none")
+                })
+                .width(Length::Shrink),
+                button("Pin Reference").on_press_maybe(
+                    if !self.modal_state.show_modal && self.app.bode_plot.is_some() {
+                        Some(Message::PinReference)
+                    } else {
+                        None
+                    }
+                ),
+                button("Clear Reference").on_press_maybe(
+                    if !self.modal_state.show_modal && self.app.reference_bode.is_some() {
+                        Some(Message::ClearReference)
+                    } else {
+                        None
+                    }
+                ),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text("Overlay:").width(Length::Shrink),
+                text_input("dataset name", &self.dataset_name_s)
                     .on_input_maybe(if !self.modal_state.show_modal {
-                        Some(Message::OrderChanged)
+                        Some(Message::DatasetNameChanged)
                     } else {
                         None
                     })
                     .width(Length::FillPortion(1)),
-                text("Ripple (dB):").width(Length::Shrink),
-                text_input("e.g. 5", &self.ripple_s)
+                button("Add Current Data").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::AddDataset)
+                } else {
+                    None
+                }),
+                button("Load CSVs\u{2026}").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::LoadDatasets)
+                } else {
+                    None
+                }),
+                button("Clear Overlays").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::ClearDatasets)
+                } else {
+                    None
+                }),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text(format!("Stop edge ({time_unit}):")).width(Length::Shrink),
+                text_input("e.g. 3", &self.stop_edge_s)
                     .on_input_maybe(if !self.modal_state.show_modal {
-                        Some(Message::RippleChanged)
+                        Some(Message::StopEdgeChanged)
                     } else {
                         None
                     })
                     .width(Length::FillPortion(1)),
-                text("Attenuation (dB):").width(Length::Shrink),
-                text_input("e.g. 40", &self.attenuation_s)
+                text("Stop atten (dB):").width(Length::Shrink),
+                text_input("e.g. 40", &self.stop_atten_s)
                     .on_input_maybe(if !self.modal_state.show_modal {
-                        Some(Message::AttenuationChanged)
+                        Some(Message::StopAttenChanged)
                     } else {
                         None
                     })
                     .width(Length::FillPortion(1)),
+                button("Estimate order").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::EstimateOrder)
+                } else {
+                    None
+                }),
             ]
             .spacing(12)
             .align_y(Alignment::Center),
             row![
-                button("Edit/Load Data").on_press_maybe(if !self.modal_state.show_modal {
-                    Some(Message::OpenDataModal)
+                text("Export CSV:").width(Length::Shrink),
+                button("Export...").on_press_maybe(
+                    if !self.modal_state.show_modal && self.app.filtered_data.is_some() {
+                        Some(Message::ExportCsv)
+                    } else {
+                        None
+                    }
+                ),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text("Export plot:").width(Length::Shrink),
+                pick_list(
+                    views::export::PlotKind::ALL,
+                    Some(self.plot_kind),
+                    Message::PlotKindChanged
+                ),
+                text_input("1600x900", &self.plot_size_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::PlotSizeChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::Fixed(90.0)),
+                button("Save PNG...").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::ExportPlot(self.plot_kind))
                 } else {
                     None
                 }),
-                button("Calculate").on_press_maybe(if !self.modal_state.show_modal {
-                    Some(Message::Calculate)
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text("Session file:").width(Length::Shrink),
+                text_input(DEFAULT_SAVED_SESSION_FILENAME, &self.session_name_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::SessionNameChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::Fill),
+                button("Save Session").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::SaveSession)
                 } else {
                     None
                 }),
-                button("Clear").on_press_maybe(if !self.modal_state.show_modal {
-                    Some(Message::ClearOutput)
+                button("Load Session").on_press_maybe(
+                    if !self.modal_state.show_modal && !self.calculating {
+                        Some(Message::LoadSession)
+                    } else {
+                        None
+                    }
+                ),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text(format!("Bode range ({cycles_unit}):")).width(Length::Shrink),
+                text_input("from, e.g. 1e-4", &self.bode_min_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::BodeMinChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+                text_input("to, up to 0.5", &self.bode_max_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::BodeMaxChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            row![
+                text(format!("Bandpass sweep ({cycles_unit}):")).width(Length::Shrink),
+                text_input("from, e.g. 0.01", &self.sweep_lo_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::SweepLoChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+                text_input("to, e.g. 0.3", &self.sweep_hi_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::SweepHiChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+                text("Steps:").width(Length::Shrink),
+                text_input("e.g. 8", &self.sweep_steps_s)
+                    .on_input_maybe(if !self.modal_state.show_modal {
+                        Some(Message::SweepStepsChanged)
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+                button("Sweep").on_press_maybe(if !self.modal_state.show_modal {
+                    Some(Message::RunSweep)
                 } else {
                     None
                 }),
-                button("Demo Data").on_press_maybe(if !self.modal_state.show_modal {
-                    Some(Message::LoadDemo)
-                } else {
-                    None
-                })
             ]
-            .spacing(12),
+            .spacing(12)
+            .align_y(Alignment::Center),
             text(&self.status)
         ]
         .spacing(14);
 
-        let pz = Canvas::new(views::pz::PzPlotView {
-            zeros: self.app.zeros.as_deref(),
-            poles: self.app.poles.as_deref(),
-            cache: &self.plot_cache,
-        })
-        .width(Length::Fill)
-        .height(Length::FillPortion(1));
+        // A nonlinear run leaves no transfer function behind
+        let nonlinear = !self.app.is_linear();
+        let pz = Canvas::new(self.pz_view(&self.plot_cache))
+            .width(Length::Fill)
+            .height(Length::FillPortion(1));
 
-        let filter_tf_bode = Canvas::new(views::bode::BodeView {
-            freqs: if let Some(f) = self.app.bode_plot.as_ref() {
-                Some(&f.0)
-            } else {
-                None
-            },
-            mag_db: if let Some(m) = self.app.bode_plot.as_ref() {
-                Some(&m.1)
-            } else {
-                None
-            },
-            cache: &self.bode_cache,
-            x_label: "Frequency (cycles/day)",
-        })
-        .width(Length::Fill)
-        .height(Length::FillPortion(1));
+        // -3 dB edges of the magnitude response, with Q when both sides close.
+        // Designed stages are normalized to 0 dB in the passband; a custom b/a
+        // has no nominal gain, so its peak stands in. A bandstop's edges are
+        // the notch's, not a passband's, and ripple of 3 dB or more crosses
+        // the level inside the passband, so neither gets a readout.
+        let filters = || {
+            std::iter::once((self.app.filter, self.app.band, self.app.ripple))
+                .chain(self.app.stages.iter().map(|s| (s.filter, s.band, s.ripple)))
+        };
+        let ripple = filters()
+            .filter(|(f, _, _)| f.uses_ripple())
+            .map(|(_, _, r)| r)
+            .reduce(f64::max);
+        let nominal_db = filters()
+            .all(|(f, _, _)| f != structures::filters::FilterType::CUSTOM)
+            .then_some(0.0);
+        let bandwidth = self
+            .app
+            .bode_plot
+            .as_ref()
+            .filter(|_| {
+                !filters().any(|(_, b, _)| b == structures::filters::FilterBand::Bandstop)
+                    && ripple.is_none_or(|r| r < 3.0)
+            })
+            .and_then(|(f, m)| views::bode::bandwidth_3db(f, m, nominal_db));
+        let edges = match ripple {
+            Some(r) => format!("-3 dB (under {} dB ripple)", fmt_tick(r)),
+            None => String::from("-3 dB"),
+        };
+        let bandwidth_readout = text(match bandwidth {
+            Some((lo, hi)) if lo > 0.0 && hi.is_finite() => {
+                let (bw, f0) = (hi - lo, (lo * hi).sqrt());
+                format!(
+                    "{edges}: {} to {} {cycles_unit}, BW {}, f0 {}, Q {:.2}",
+                    fmt_tick(lo),
+                    fmt_tick(hi),
+                    fmt_tick(bw),
+                    fmt_tick(f0),
+                    f0 / bw
+                )
+            }
+            Some((_, hi)) if hi.is_finite() => {
+                format!("{edges}: below {} {cycles_unit}", fmt_tick(hi))
+            }
+            Some((lo, _)) => format!("{edges}: above {} {cycles_unit}", fmt_tick(lo)),
+            None => String::new(),
+        });
+
+        let stability_badge = match self.app.is_stable() {
+            Some(true) => text("stable").color(iced::Color::from_rgb8(0x2E, 0xE5, 0x9D)),
+            Some(false) if self.app.is_marginally_stable() => {
+                text("marginally stable").color(iced::Color::from_rgb8(0xFF, 0xD5, 0x4F))
+            }
+            Some(false) => text("UNSTABLE").color(iced::Color::from_rgb8(0xFF, 0x8C, 0x00)),
+            None => text(""),
+        };
+
+        let nyquist = Canvas::new(self.nyquist_view(&self.nyquist_cache))
+            .width(Length::Fill)
+            .height(Length::FillPortion(1));
+
+        let filter_tf_bode = Canvas::new(self.bode_view(&self.bode_cache))
+            .width(Length::Fill)
+            .height(Length::FillPortion(1));
+
+        let filter_phase_bode = Canvas::new(self.phase_view(&self.phase_cache))
+            .width(Length::Fill)
+            .height(Length::FillPortion(1));
 
         let filtered = self
             .app
@@ -340,39 +2332,337 @@ impl Gui {
         let ts = Canvas::new(views::time::TimeSeriesPlotView {
             raw: self.app.raw_data.as_deref(),
             filtered,
+            fit: self
+                .app
+                .fourier_fit
+                .as_ref()
+                .map(|f| f.reconstruction.as_slice()),
+            peak_model: self
+                .app
+                .peak_model
+                .as_deref()
+                .filter(|_| self.show_peak_model),
+            envelope: self
+                .app
+                .envelope
+                .as_ref()
+                .filter(|_| self.show_envelope)
+                .map(|(u, l)| (u.as_slice(), l.as_slice())),
+            denoised: self.app.denoised.as_ref().map(|(k, d)| (d.as_slice(), *k)),
+            trend: self
+                .app
+                .trend
+                .as_ref()
+                .map(|t| (t.values.as_slice(), t.equation.as_str())),
+            overlays: &self.app.datasets,
+            dates: self.raw_dates(),
+            time_axis: self.app.axis_unit.as_deref().map(|u| (self.app.fs, u)),
+            y_log: self.ts_log_y,
+            legend: (filtered.is_some()
+                && self.app.phase == structures::filters::FilterPhase::Causal)
+                .then_some("raw (blue) / filtered (red, causal: lags raw)"),
             cache: &self.ts_cache,
         })
         .width(Length::Fill)
         .height(Length::FillPortion(1));
 
-        let fft = Canvas::new(views::frequency::SpectralView {
-            fft_out: self.app.data_spectrum.as_deref(),
-            cache: &self.fft_cache,
+        let step = Canvas::new(views::time::TimeSeriesPlotView {
+            raw: self.app.step_response.as_deref(),
+            filtered: self.app.impulse_response.as_deref(),
+            fit: None,
+            peak_model: None,
+            envelope: None,
+            denoised: None,
+            trend: None,
+            overlays: &[],
+            dates: None,
+            time_axis: None,
+            y_log: false,
+            legend: Some("step (blue) / impulse (red), samples"),
+            cache: &self.step_cache,
         })
         .width(Length::Fill)
         .height(Length::FillPortion(1));
 
-        let candle_panel = Canvas::new(views::candles::CandlePanelView {
-            zeros: self.app.zeros.as_deref(),
-            poles: self.app.poles.as_deref(),
-            candles: self.app.candles.as_deref(),
-            cache: &self.candles_cache,
-            title: "Candle View",
+        let db_floor = match self.app.spectrum_scale {
+            structures::filters::SpectrumScale::Linear => None,
+            structures::filters::SpectrumScale::Decibel => Some(self.app.db_floor),
+        };
+        // Peaks and overlays are binned for the whole-record FFT, so Welch leaves them out
+        let acf = Canvas::new(views::time::TimeSeriesPlotView {
+            raw: self.app.autocorrelation.as_deref(),
+            filtered: None,
+            fit: None,
+            peak_model: None,
+            envelope: None,
+            denoised: None,
+            trend: None,
+            overlays: &[],
+            dates: None,
+            time_axis: None,
+            y_log: false,
+            legend: Some("autocorrelation of filtered vs lag (samples)"),
+            cache: &self.acf_cache,
         })
         .width(Length::Fill)
-        .height(Length::Fill);
+        .height(Length::FillPortion(1));
+        let acf_readout = text(match self.app.acf_period {
+            Some(lag) => format!(
+                "period \u{2248} {} {time_unit} ({lag} samples)",
+                fmt_tick(lag as f64 / self.app.fs)
+            ),
+            None if self.app.autocorrelation.is_some() => String::from("no significant period"),
+            None => String::new(),
+        });
+
+        let fft: Element<'_, Message> = match self.app.spectrum_mode {
+            structures::filters::SpectrumMode::Fft => Canvas::new(views::frequency::SpectralView {
+                fft_out: self.app.data_spectrum.as_deref(),
+                raw_fft: self.app.raw_spectrum.as_deref(),
+                overlays: &self.app.datasets,
+                fs: self.app.fs,
+                n_samples: filtered.map_or(0, |f| math::fft_len(f.len(), self.app.fft_padding)),
+                padding: self.app.fft_padding,
+                peaks: self.app.spectrum_peaks.as_deref().unwrap_or(&[]),
+                db_floor,
+                power: false,
+                time_unit,
+                cache: &self.fft_cache,
+            })
+            .width(Length::Fill)
+            .height(Length::FillPortion(1))
+            .into(),
+            structures::filters::SpectrumMode::Welch => {
+                Canvas::new(views::frequency::SpectralView {
+                    fft_out: self.app.data_welch.as_deref(),
+                    raw_fft: self.app.raw_welch.as_deref(),
+                    overlays: &[],
+                    fs: self.app.fs,
+                    n_samples: filtered.map_or(0, |f| self.app.welch_segment.min(f.len())),
+                    padding: self.app.fft_padding,
+                    peaks: &[],
+                    db_floor,
+                    power: true,
+                    time_unit,
+                    cache: &self.fft_cache,
+                })
+                .width(Length::Fill)
+                .height(Length::FillPortion(1))
+                .into()
+            }
+            structures::filters::SpectrumMode::Spectrogram => {
+                Canvas::new(views::spectrogram::SpectrogramView {
+                    frames: self.app.spectrogram.as_deref(),
+                    fs: self.app.fs,
+                    hop: self.app.welch_segment - self.app.welch_overlap,
+                    db_floor,
+                    time_unit,
+                    cache: &self.fft_cache,
+                })
+                .width(Length::Fill)
+                .height(Length::FillPortion(1))
+                .into()
+            }
+        };
+
+        let candle_panel = Canvas::new(self.candle_view(&self.candles_cache))
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        let mut left_panel = column![controls].padding(16).spacing(5);
+        if self.show_sections {
+            let rows: Vec<Element<'_, Message>> = match (
+                self.app.filtered_data.as_ref(),
+                self.app.section_bode.as_ref(),
+            ) {
+                (Some(data), Some(responses)) => data
+                    .sos
+                    .iter()
+                    .zip(responses)
+                    .enumerate()
+                    .map(|(i, (s, (freqs, mag)))| {
+                        row![
+                            column![
+                                text(format!("Section {}", i + 1)).font(BOLD),
+                                text(format!(
+                                    "b: [{:+.6}, {:+.6}, {:+.6}]",
+                                    s.b[0], s.b[1], s.b[2]
+                                )),
+                                text(format!(
+                                    "a: [{:+.6}, {:+.6}, {:+.6}]",
+                                    s.a[0], s.a[1], s.a[2]
+                                )),
+                            ]
+                            .width(Length::Fill),
+                            Canvas::new(views::sections::SectionResponseView { freqs, mag })
+                                .width(Length::Fixed(220.0))
+                                .height(Length::Fixed(80.0)),
+                        ]
+                        .spacing(12)
+                        .align_y(Alignment::Center)
+                        .into()
+                    })
+                    .collect(),
+                _ => vec![text("(run Calculate first)").into()],
+            };
+            left_panel = left_panel
+                .push(text("SOS Sections").font(BOLD))
+                .push(scrollable(column(rows).spacing(8)).height(Length::Fixed(220.0)));
+        }
+        let coefficients: Element<'_, Message> = match self.app.filtered_data.as_ref() {
+            Some(_) if nonlinear => text("(nonlinear filter, no coefficients)").into(),
+            Some(data) => column![
+                text(format_transfer_function(&data.b, &data.a)),
+                text(format!("b: [{}]", fmt_coefficients(&data.b))),
+                text(format!("a: [{}]", fmt_coefficients(&data.a))),
+            ]
+            .spacing(4)
+            .into(),
+            None => text("(run Calculate first)").into(),
+        };
+        let statistics: Element<'_, Message> = match self.app.raw_data.as_deref() {
+            Some(raw) => {
+                let raw_stats = stats::series_stats(raw);
+                let mut rows = column![stats_row("raw", &raw_stats)].spacing(4);
+                if self.app.gaps_repaired > 0 {
+                    rows = rows.push(text(format!(
+                        "gaps repaired: {} sample(s)",
+                        self.app.gaps_repaired
+                    )));
+                }
+                if let Some(f) = self.app.filtered_data.as_ref() {
+                    let filtered_stats = stats::series_stats(&f.filtered_data);
+                    rows = rows.push(stats_row("filtered", &filtered_stats));
+                    if let Some(pct) = stats::variance_removed(&raw_stats, &filtered_stats) {
+                        rows = rows.push(text(format!("variance removed: {pct:.1}%")));
+                    }
+                    if let Some(spectrum) = self.app.data_spectrum.as_deref() {
+                        rows = rows.push(text(thd_readout(spectrum)));
+                    }
+                    let snr =
+                        stats::snr_db(&f.filtered_data, &stats::residual(raw, &f.filtered_data));
+                    rows = rows.push(text(if snr == f64::INFINITY {
+                        String::from("SNR: +inf dB (filter removed nothing)")
+                    } else {
+                        format!("SNR (filtered / removed): {snr:.1} dB")
+                    }));
+                }
+                rows.into()
+            }
+            None => text("(no data loaded)").into(),
+        };
+        let left_panel = left_panel
+            .push(text("Statistics").font(BOLD))
+            .push(statistics)
+            .push(
+                row![
+                    text("Coefficients").font(BOLD),
+                    button("Copy").on_press_maybe(
+                        if !self.modal_state.show_modal
+                            && self.app.filtered_data.is_some()
+                            && !nonlinear
+                        {
+                            Some(Message::CopyCoefficients)
+                        } else {
+                            None
+                        }
+                    ),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center),
+            )
+            .push(scrollable(coefficients).height(Length::Fixed(90.0)))
+            .push(
+                row![
+                    text("Candle View").font(BOLD),
+                    pick_list(
+                        structures::candle::CandlePalette::ALL,
+                        Some(self.app.candle_palette),
+                        Message::CandlePaletteChanged
+                    ),
+                    pick_list(
+                        structures::candle::CandleStyle::ALL,
+                        Some(self.app.candle_style),
+                        Message::CandleStyleChanged
+                    ),
+                    pick_list(
+                        structures::candle::CandleSource::ALL,
+                        Some(self.app.candle_source),
+                        Message::CandleSourceChanged
+                    ),
+                ]
+                .extend(CANDLE_SMAS.into_iter().map(|(period, _)| {
+                    checkbox(self.candle_smas.iter().any(|&(p, _)| p == period))
+                        .label(format!("SMA {period}"))
+                        .on_toggle_maybe(if !self.modal_state.show_modal {
+                            Some(move |on| Message::CandleSmaToggled(period, on))
+                        } else {
+                            None
+                        })
+                        .into()
+                }))
+                .spacing(12)
+                .align_y(Alignment::Center),
+            )
+            .push(
+                row![
+                    checkbox(self.show_bollinger)
+                        .label("Bollinger bands")
+                        .on_toggle_maybe(if !self.modal_state.show_modal {
+                            Some(Message::BollingerToggled)
+                        } else {
+                            None
+                        }),
+                    text("Period:").width(Length::Shrink),
+                    text_input("candles", &self.bollinger_period_s)
+                        .on_input_maybe(if !self.modal_state.show_modal && self.show_bollinger {
+                            Some(Message::BollingerPeriodChanged)
+                        } else {
+                            None
+                        })
+                        .width(Length::Fixed(60.0)),
+                    text("k:").width(Length::Shrink),
+                    text_input("std devs", &self.bollinger_k_s)
+                        .on_input_maybe(if !self.modal_state.show_modal && self.show_bollinger {
+                            Some(Message::BollingerKChanged)
+                        } else {
+                            None
+                        })
+                        .width(Length::Fixed(60.0)),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center),
+            )
+            .push(candle_panel);
 
         let content = row![
-            column![controls, text("Candle View").font(BOLD), candle_panel]
-                .padding(16)
-                .spacing(5),
+            left_panel,
             column![
                 row![
-                    column![text("Pole/Zero Plot").font(BOLD), pz],
-                    column![text("Bode Plot").font(BOLD), filter_tf_bode]
+                    column![
+                        row![text("Pole/Zero Plot").font(BOLD), stability_badge].spacing(8),
+                        pz,
+                        text("Nyquist Plot").font(BOLD),
+                        nyquist
+                    ],
+                    column![
+                        row![text("Bode Plot").font(BOLD), bandwidth_readout].spacing(8),
+                        filter_tf_bode,
+                        filter_phase_bode
+                    ],
+                    column![
+                        text("Step / Impulse Response").font(BOLD),
+                        step,
+                        row![text("Autocorrelation").font(BOLD), acf_readout].spacing(8),
+                        acf
+                    ]
                 ]
                 .spacing(5),
-                text("Time Domain").font(BOLD),
+                row![
+                    text("Time Domain").font(BOLD),
+                    text(self.dominant_period_readout()).font(BOLD),
+                ]
+                .spacing(12),
                 ts,
                 text("Frequency Domain").font(BOLD),
                 fft
@@ -408,7 +2698,29 @@ impl Gui {
                 text(&self.modal_state.date_status),
                 text_input("", &self.modal_state.weight_entry)
                     .on_input(Message::WeightSelectionChanged),
-                row![button("Save").on_press(Message::SaveWeightSelection),].spacing(12),
+                row![
+                    button("Save").on_press(Message::SaveWeightSelection),
+                    button("Delete").on_press(Message::DeleteWeightSelection),
+                    button("Use Logged Data").on_press(Message::UseModalData),
+                    button("Browse\u{2026}").on_press(Message::BrowseLogFile),
+                ]
+                .spacing(12),
+                if self.modal_state.confirm_clear {
+                    row![
+                        text(format!(
+                            "Delete all {} entries?",
+                            self.modal_state.data.len()
+                        )),
+                        button("Confirm").on_press(Message::ConfirmClearWeightLog(true)),
+                        button("Cancel").on_press(Message::ConfirmClearWeightLog(false)),
+                    ]
+                } else {
+                    row![button("Clear all").on_press_maybe(
+                        (!self.modal_state.data.is_empty()).then_some(Message::ClearWeightLog)
+                    )]
+                }
+                .spacing(12)
+                .align_y(iced::Alignment::Center),
             ]
             .spacing(12)
             .padding(16),
@@ -450,3 +2762,26 @@ impl Gui {
         stack![main_stack, overlay].into()
     }
 }
+
+// THD of the filtered spectrum against its largest non-DC bin
+fn thd_readout(spectrum: &[f64]) -> String {
+    match math::fundamental_bin(spectrum) {
+        Some(k) => {
+            let thd = math::thd(spectrum, k, THD_HARMONICS);
+            if thd.is_nan() {
+                format!("THD (fundamental bin {k}): \u{2014}")
+            } else {
+                format!("THD (fundamental bin {k}): {:.2}%", 100.0 * thd)
+            }
+        }
+        None => String::from("THD: \u{2014}"),
+    }
+}
+
+fn stats_row<'a>(label: &str, s: &stats::SeriesStats) -> Element<'a, Message> {
+    text(format!(
+        "{label}: mean {:.3}, std {:.3}, min {:.3}, max {:.3}, rms {:.3} (n={})",
+        s.mean, s.std, s.min, s.max, s.rms, s.count
+    ))
+    .into()
+}
@@ -1,192 +1,1768 @@
 #[cfg(target_os = "macos")]
 extern crate accelerate_src;
+use crate::stats::series_stats;
+use crate::structures::filters::{
+    DetrendMode, EdgeHandling, FftPadding, FilterBand, FilterPhase, FilterType, Window,
+};
 use core::cmp::min;
 use ndarray::Array2;
 use ndarray_linalg::EigVals;
 use num_complex::Complex;
 use sci_rs::signal::filter::{
     design::{
-        DigitalFilter, FilterBandType, FilterOutputType, FilterType, Sos, SosFormatFilter,
-        butter_dyn, iirfilter_dyn,
+        BaFormatFilter, DigitalFilter, FilterBandType, FilterOutputType, SosFormatFilter,
+        ZpkFormatFilter, bilinear_zpk_dyn, butter_dyn, lp2bp_zpk_dyn, lp2lp_zpk_dyn, zpk2sos_dyn,
     },
-    sosfiltfilt_dyn,
+    sosfilt_dyn, sosfiltfilt_dyn,
 };
-use scirs2::fft::rfft;
-use scirs2::signal::filter;
+use scirs2::fft::{ifft, rfft};
+// Re-exported so library users can name the section type without sci_rs
+pub use sci_rs::signal::filter::design::Sos;
+use std::f64::consts::PI;
+
+type PzTuple = (Vec<Complex<f64>>, Vec<Complex<f64>>);
+// (center, freqs, mag_db) for one step of a bandpass center sweep
+pub type SweepStep = (f64, Vec<f64>, Vec<f64>);
+
+// (freqs, values) sampled over a BodeRange
+pub type FreqResponse = (Vec<f64>, Vec<f64>);
+
+// Log-spaced frequency grid for the Bode plots, in the same units as fs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodeRange {
+    pub f_min: f64,
+    pub f_max: f64,
+    pub n_points: usize,
+}
+
+impl BodeRange {
+    // Four decades below Nyquist up to Nyquist
+    pub fn full(fs: f64) -> Self {
+        Self {
+            f_min: fs * 1e-4,
+            f_max: fs * 0.5,
+            n_points: 100,
+        }
+    }
+
+    pub fn validate(&self, fs: f64) -> Result<(), String> {
+        if !(self.f_min.is_finite() && self.f_max.is_finite()) {
+            return Err(String::from("Bode range limits must be finite"));
+        }
+        if self.f_min <= 0. {
+            return Err(format!("Bode f_min must be positive, got {}", self.f_min));
+        }
+        if self.f_min >= self.f_max {
+            return Err(format!(
+                "Bode f_min ({}) must be below f_max ({})",
+                self.f_min, self.f_max
+            ));
+        }
+        if self.f_max > fs * 0.5 {
+            return Err(format!(
+                "Bode f_max ({}) is above Nyquist ({})",
+                self.f_max,
+                fs * 0.5
+            ));
+        }
+        if self.n_points < 2 {
+            return Err(String::from("Bode range needs at least 2 points"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for BodeRange {
+    fn default() -> Self {
+        Self::full(1.)
+    }
+}
+
+// Shortest resolvable period, in samples
+pub const NYQUIST_PERIOD: f64 = 2.;
+// Lowest cutoff, as a fraction of Nyquist, that designs reliably
+pub const MIN_NORMALIZED_CUTOFF: f64 = 1e-3;
+// Comb pole coefficient relative to its zeros; closer to 1 narrows the notches
+pub const COMB_POLE_FRAC: f64 = 0.9;
+// Longest comb period in samples; the period is the degree of b and a, so
+// their roots come from a companion matrix this wide
+pub const MAX_COMB_PERIOD: usize = 512;
+// Past this the Bessel polynomial's factorial coefficients lose precision
+pub const MAX_BESSEL_ORDER: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct FilterData {
+    pub filtered_data: Vec<f64>,
+    // Flattened transfer function, for the Bode and pole/zero views
+    pub b: Vec<f64>,
+    pub a: Vec<f64>,
+    // The cascade the data actually ran through; prefer it over b/a when
+    // applying high orders yourself
+    pub sos: Vec<Sos<f64>>,
+}
+
+// A stage's sections. One that only has b/a (custom, differentiator,
+// integrator) counts as a single section when it fits in a biquad; None when
+// it doesn't.
+fn stage_sections(f: &FilterData) -> Option<Vec<Sos<f64>>> {
+    if !f.sos.is_empty() {
+        return Some(f.sos.clone());
+    }
+    if f.b.len() > 3 || f.a.len() > 3 {
+        return None;
+    }
+    let pad = |c: &[f64]| std::array::from_fn(|i| c.get(i).map_or(0.0, |v| v / f.a[0]));
+    Some(vec![Sos::new(pad(&f.b), pad(&f.a))])
+}
+
+// `next` applied to the output of `prev`: the transfer functions multiply and
+// the sections run one after the other. A nonlinear stage (no coefficients)
+// leaves the whole chain without a transfer function, and a stage too long to
+// split into sections leaves it with b/a only.
+pub fn chain_filter_data(prev: FilterData, next: FilterData) -> FilterData {
+    if prev.a.is_empty() || next.a.is_empty() {
+        return FilterData {
+            filtered_data: next.filtered_data,
+            b: vec![],
+            a: vec![],
+            sos: vec![],
+        };
+    }
+    let sos = match (stage_sections(&prev), stage_sections(&next)) {
+        (Some(mut sos), Some(rest)) => {
+            sos.extend(rest);
+            sos
+        }
+        _ => vec![],
+    };
+    FilterData {
+        filtered_data: next.filtered_data,
+        b: poly_mul(&prev.b, &next.b),
+        a: poly_mul(&prev.a, &next.a),
+        sos,
+    }
+}
+
+// Period in days at fs samples/day, as a fraction of Nyquist. The result is
+// kept strictly inside (0, 1): at Nyquist the design is degenerate, and
+// below MIN_NORMALIZED_CUTOFF the poles crowd z = 1 and the design is
+// ill-conditioned.
+pub fn cutoff_period_to_nyquist(period: f64, fs: f64) -> Result<f64, String> {
+    let samples = period * fs;
+    if samples.is_nan() || samples <= NYQUIST_PERIOD {
+        return Err(format!(
+            "Period of {period} ({samples} samples at fs = {fs}) must be longer than the nyquist period of {NYQUIST_PERIOD} samples"
+        ));
+    }
+    let w = NYQUIST_PERIOD / samples;
+    if w < MIN_NORMALIZED_CUTOFF {
+        return Err(format!(
+            "Period of {period} ({samples} samples at fs = {fs}) is too long to design for; keep it at most {} samples",
+            NYQUIST_PERIOD / MIN_NORMALIZED_CUTOFF
+        ));
+    }
+    Ok(w)
+}
+
+// Minimum order meeting gpass_db loss at wp and gstop_db attenuation at ws
+// (both normalized to Nyquist), as in scipy's buttord/cheb1ord/cheb2ord/ellipord.
+// ws above wp is a lowpass spec, below it a highpass one.
+pub fn estimate_order(
+    wp: f64,
+    ws: f64,
+    gpass_db: f64,
+    gstop_db: f64,
+    kind: FilterType,
+) -> Result<usize, String> {
+    for (name, w) in [("wp", wp), ("ws", ws)] {
+        if !(w > 0. && w < 1.) {
+            return Err(format!("{name} must be between 0 and 1 (Nyquist), got {w}"));
+        }
+    }
+    if wp == ws {
+        return Err(String::from("Passband and stopband edges must differ"));
+    }
+    if !(gpass_db > 0. && gstop_db > gpass_db) {
+        return Err(format!(
+            "Need 0 < passband loss ({gpass_db} dB) < stopband attenuation ({gstop_db} dB)"
+        ));
+    }
+    // Prewarp both edges, then take the analog selectivity ratio (> 1)
+    let passb = (PI * wp / 2.0).tan();
+    let stopb = (PI * ws / 2.0).tan();
+    let nat = if ws > wp {
+        stopb / passb
+    } else {
+        passb / stopb
+    };
+    let gstop = 10f64.powf(gstop_db / 10.0) - 1.0;
+    let gpass = 10f64.powf(gpass_db / 10.0) - 1.0;
+
+    let n = match kind {
+        FilterType::BUTTERWORTH => (gstop / gpass).log10() / (2.0 * nat.log10()),
+        FilterType::CHEBYSHEV1 | FilterType::CHEBYSHEV2 => {
+            (gstop / gpass).sqrt().acosh() / nat.acosh()
+        }
+        FilterType::ELLIPTIC => {
+            let m0 = (1.0 / nat).powi(2);
+            let m1 = gpass / gstop;
+            ellipk(m0) * ellipk(1.0 - m1) / (ellipk(1.0 - m0) * ellipk(m1))
+        }
+        FilterType::BESSEL => {
+            return Err(String::from("Bessel has no closed-form order estimate"));
+        }
+        FilterType::MOVING_AVERAGE | FilterType::MEDIAN | FilterType::COMB => {
+            return Err(format!(
+                "A {kind} filter is sized by its window, not an order"
+            ));
+        }
+        FilterType::DIFFERENTIATOR | FilterType::INTEGRATOR => {
+            return Err(format!("A {kind} is a fixed operator with no order"));
+        }
+        FilterType::CUSTOM => {
+            return Err(String::from(
+                "Custom coefficients have no order to estimate",
+            ));
+        }
+        FilterType::ALL_PASS => {
+            return Err(String::from(
+                "An all-pass filter has a flat magnitude, so there is no order to estimate",
+            ));
+        }
+    };
+    Ok((n.ceil() as usize).max(1))
+}
+
+pub fn butterworth_filter(
+    data: &[f64],
+    cutoff_freq: f64,
+    order: usize,
+    band: FilterBand,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    let mut sos = butterworth_sos(order, vec![cutoff_freq], band_type(band))?;
+    normalize_passband_gain(&mut sos, band);
+    let (num, den) = sos_to_ba(&sos);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: num,
+        a: den,
+        sos,
+    })
+}
+
+pub fn chebyshev_filter_1(
+    data: &[f64],
+    cutoff_freq: f64,
+    order: usize,
+    ripple: f64,
+    band: FilterBand,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    let mut sos = chebyshev1_sos(order, vec![cutoff_freq], ripple, band_type(band))?;
+    normalize_passband_gain(&mut sos, band);
+    let (num, den) = sos_to_ba(&sos);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: num,
+        a: den,
+        sos,
+    })
+}
+
+pub fn chebyshev_filter_2(
+    data: &[f64],
+    cutoff_freq: f64,
+    order: usize,
+    attenuation: f64,
+    band: FilterBand,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    let mut sos = chebyshev2_sos(order, vec![cutoff_freq], attenuation, band_type(band))?;
+    normalize_passband_gain(&mut sos, band);
+    let (num, den) = sos_to_ba(&sos);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: num,
+        a: den,
+        sos,
+    })
+}
+
+// Output variance below this fraction of the input's (-120 dB) counts as flat
+const FLAT_OUTPUT_RATIO: f64 = 1e-12;
+
+// Bad coefficients can make the filter return all NaN or a flat line, which
+// would otherwise just show up as a blank plot
+pub fn check_filtered_output(input: &[f64], output: &[f64]) -> Result<(), String> {
+    if !output.is_empty() && output.iter().all(|v| !v.is_finite()) {
+        return Err(String::from(
+            "Filter output is entirely non-finite; the design is likely unstable (try a lower order or a less extreme cutoff)",
+        ));
+    }
+    // Rounding leaves a flattened output a little above zero, so compare it
+    // to the input; fewer than two finite samples count as flat
+    let input_var = series_stats(input).variance();
+    let output_var = series_stats(output).variance();
+    if input_var > 0.0 && (output_var.is_nan() || output_var <= FLAT_OUTPUT_RATIO * input_var) {
+        return Err(String::from(
+            "Filter output is flat although the input varies; the order may be too high or the cutoff at an extreme",
+        ));
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn elliptic_filter(
+    data: &[f64],
+    cutoff_freq: f64,
+    order: usize,
+    ripple: f64,
+    attenuation: f64,
+    band: FilterBand,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    let proto = ellipap(order, ripple, attenuation)?;
+    let mut sos = prototype_sos(order, proto, &[cutoff_freq], band_type(band))?;
+    normalize_passband_gain(&mut sos, band);
+    let (num, den) = sos_to_ba(&sos);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: num,
+        a: den,
+        sos,
+    })
+}
+
+pub fn bessel_filter(
+    data: &[f64],
+    cutoff_freq: f64,
+    order: usize,
+    band: FilterBand,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    let mut sos = prototype_sos(order, besselap(order)?, &[cutoff_freq], band_type(band))?;
+    normalize_passband_gain(&mut sos, band);
+    let (num, den) = sos_to_ba(&sos);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: num,
+        a: den,
+        sos,
+    })
+}
+
+// N-point boxcar, b = [1/N; N] and a = [1]. The zeros of 1 + z^-1 + ... +
+// z^-(N-1) are the N-th roots of unity other than 1, so the sections pair
+// conjugate roots (plus z = -1 for even N) and the filter runs through the
+// same sos paths as the IIR designs.
+pub fn moving_average(
+    data: &[f64],
+    window: usize,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    if window == 0 {
+        return Err(String::from("Moving average window must be at least 1"));
+    }
+    if window > data.len() {
+        return Err(format!(
+            "Moving average window {window} is longer than the {} samples",
+            data.len()
+        ));
+    }
+    let n = window as f64;
+    let mut sos: Vec<Sos<f64>> = (1..=(window - 1) / 2)
+        .map(|k| {
+            let c = (2.0 * PI * k as f64 / n).cos();
+            Sos::new([1.0, -2.0 * c, 1.0], [1.0, 0.0, 0.0])
+        })
+        .collect();
+    if window.is_multiple_of(2) {
+        sos.push(Sos::new([1.0, 1.0, 0.0], [1.0, 0.0, 0.0]));
+    }
+    if sos.is_empty() {
+        sos.push(Sos::new([1.0, 0.0, 0.0], [1.0, 0.0, 0.0]));
+    }
+    sos[0].b.iter_mut().for_each(|b| *b /= n);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: vec![1.0 / n; window],
+        a: vec![1.0],
+        sos,
+    })
+}
+
+// First difference y[n] = x[n] - x[n-1] (b = [1, -1], a = [1]), starting
+// from rest so y[0] = x[0]
+pub fn differentiate(data: &[f64]) -> Vec<f64> {
+    let mut prev = 0.0;
+    data.iter()
+        .map(|&x| {
+            let y = x - prev;
+            prev = x;
+            y
+        })
+        .collect()
+}
+
+// Running sum y[n] = y[n-1] + x[n] (b = [1], a = [1, -1]). The pole sits on
+// z = 1, so there is no DC gain to normalize and a NaN carries forward.
+pub fn integrate(data: &[f64]) -> Vec<f64> {
+    data.iter()
+        .scan(0.0, |acc, &x| {
+            *acc += x;
+            Some(*acc)
+        })
+        .collect()
+}
+
+// Comb notching f = k / period_samples for every k, DC included:
+// b = [1, 0.., -r], a = [1, 0.., -r * COMB_POLE_FRAC] with the taps period_samples
+// apart. notch_depth = r in (0, 1] leaves a gain of (1 - r) / (1 - r * COMB_POLE_FRAC)
+// at each notch, so 1 nulls it outright; the poles sit just inside the zeros and
+// keep the notches narrow.
+pub fn comb_filter(
+    data: &[f64],
+    period_samples: usize,
+    notch_depth: f64,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    if !(2..=MAX_COMB_PERIOD).contains(&period_samples) {
+        return Err(format!(
+            "Comb period must be between 2 and {MAX_COMB_PERIOD} samples, got {period_samples}"
+        ));
+    }
+    if !(notch_depth > 0. && notch_depth <= 1.) {
+        return Err(format!(
+            "Comb notch depth must be in (0, 1], got {notch_depth}"
+        ));
+    }
+    let mut b = vec![0.0; period_samples + 1];
+    let mut a = vec![0.0; period_samples + 1];
+    (b[0], b[period_samples]) = (1.0, -notch_depth);
+    (a[0], a[period_samples]) = (1.0, -notch_depth * COMB_POLE_FRAC);
+    custom_filter(data, &b, &a, phase)
+}
+
+// Band rejection between two normalized cutoffs, designed as `kind`
+#[allow(clippy::too_many_arguments)]
+pub fn bandstop_filter(
+    data: &[f64],
+    low_freq: f64,
+    high_freq: f64,
+    order: usize,
+    kind: FilterType,
+    ripple: f64,
+    attenuation: f64,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    if !(low_freq > 0. && low_freq < high_freq && high_freq < 1.) {
+        return Err(format!(
+            "Stop band {low_freq}..{high_freq} must be ordered and lie within (0, 1) of nyquist"
+        ));
+    }
+    let mut sos = family_sos(
+        kind,
+        vec![low_freq, high_freq],
+        FilterBandType::Bandstop,
+        order,
+        ripple,
+        attenuation,
+    )?;
+    normalize_passband_gain(&mut sos, FilterBand::Bandstop);
+    let (num, den) = sos_to_ba(&sos);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: num,
+        a: den,
+        sos,
+    })
+}
+
+// Bandpass as a highpass of hp_order at low_freq cascaded with a lowpass of
+// lp_order at high_freq, both designed as `kind`. The data runs once through
+// the combined sections; b/a are the product of the two stages, each scaled
+// to unity gain in its own passband.
+#[allow(clippy::too_many_arguments)]
+pub fn bandpass_cascade(
+    data: &[f64],
+    low_freq: f64,
+    high_freq: f64,
+    hp_order: usize,
+    lp_order: usize,
+    kind: FilterType,
+    ripple: f64,
+    attenuation: f64,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    if !(low_freq > 0. && low_freq < high_freq && high_freq < 1.) {
+        return Err(format!(
+            "Pass band {low_freq}..{high_freq} must be ordered and lie within (0, 1) of nyquist"
+        ));
+    }
+    let stage = |band: FilterBand, wn: f64, order: usize| {
+        let mut sos = design_sos(kind, band, wn, order, ripple, attenuation)?;
+        normalize_passband_gain(&mut sos, band);
+        let (num, den) = sos_to_ba(&sos);
+        Ok::<_, String>((sos, num, den))
+    };
+    let (mut sos, hp_b, hp_a) = stage(FilterBand::Highpass, low_freq, hp_order)?;
+    let (lp_sos, lp_b, lp_a) = stage(FilterBand::Lowpass, high_freq, lp_order)?;
+    sos.extend(lp_sos);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: poly_mul(&hp_b, &lp_b),
+        a: poly_mul(&hp_a, &lp_a),
+        sos,
+    })
+}
+
+// All-pass cascade with `order` poles: conjugate pairs at pole_radius *
+// e^(+-j pole_angle) (radians/sample), plus one real pole at pole_radius when
+// the order is odd. Each section's numerator is its denominator reversed, which
+// puts the zeros at the conjugate reciprocals of the poles, so |H| = 1 at every
+// frequency and only the phase changes.
+pub fn allpass_filter(
+    data: &[f64],
+    pole_radius: f64,
+    pole_angle: f64,
+    order: usize,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    if !(pole_radius > 0. && pole_radius < 1.) {
+        return Err(format!(
+            "All-pass pole radius must be strictly between 0 and 1, got {pole_radius}"
+        ));
+    }
+    if !(0. ..=PI).contains(&pole_angle) {
+        return Err(format!(
+            "All-pass pole angle must be between 0 and pi, got {pole_angle}"
+        ));
+    }
+    if order == 0 {
+        return Err(String::from("All-pass order must be at least 1"));
+    }
+    let r = pole_radius;
+    let c = -2.0 * r * pole_angle.cos();
+    let mut sos: Vec<Sos<f64>> = (0..order / 2)
+        .map(|_| Sos::new([r * r, c, 1.0], [1.0, c, r * r]))
+        .collect();
+    if !order.is_multiple_of(2) {
+        sos.push(Sos::new([-r, 1.0, 0.0], [1.0, -r, 0.0]));
+    }
+    let (num, den) = sos_to_ba(&sos);
+    let filtered = apply_sos(data, &sos, edge, phase)?;
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: num,
+        a: den,
+        sos,
+    })
+}
+
+// Run arbitrary b/a over the data with no design step. There are no sections
+// to pad per-section, so zero-phase always uses Gustafsson's initial conditions.
+pub fn custom_filter(
+    data: &[f64],
+    b: &[f64],
+    a: &[f64],
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    if b.is_empty() || a.is_empty() {
+        return Err(String::from(
+            "Custom filter needs at least one b and one a coefficient",
+        ));
+    }
+    if a[0] == 0.0 {
+        return Err(String::from("Custom filter needs a[0] != 0"));
+    }
+    if b.iter().chain(a).any(|c| !c.is_finite()) {
+        return Err(String::from("Custom coefficients must be finite"));
+    }
+    let filtered = match phase {
+        FilterPhase::Causal => difference_equation(b, a, data),
+        FilterPhase::ZeroPhase => filtfilt_gustafsson(data, b, a),
+    };
+    Ok(FilterData {
+        filtered_data: filtered,
+        b: b.to_vec(),
+        a: a.to_vec(),
+        sos: vec![],
+    })
+}
+
+// Reject a single period: the stop band spans center_period +- width/2 (periods in samples)
+pub fn notch_filter(
+    data: &[f64],
+    center_period: f64,
+    width: f64,
+    fs: f64,
+    order: usize,
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<FilterData, String> {
+    if width.is_nan() || width <= 0. {
+        return Err(format!("Notch width must be positive, got {width}"));
+    }
+    let low_freq = cutoff_period_to_nyquist(center_period + width / 2., fs)?;
+    let high_freq = cutoff_period_to_nyquist(center_period - width / 2., fs)?;
+    // Butterworth ignores ripple and attenuation
+    bandstop_filter(
+        data,
+        low_freq,
+        high_freq,
+        order,
+        FilterType::BUTTERWORTH,
+        0.,
+        0.,
+        edge,
+        phase,
+    )
+}
+
+fn band_type(band: FilterBand) -> FilterBandType {
+    match band {
+        FilterBand::Lowpass => FilterBandType::Lowpass,
+        FilterBand::Highpass => FilterBandType::Highpass,
+        FilterBand::Bandstop => FilterBandType::Bandstop,
+        FilterBand::Bandpass => FilterBandType::Bandpass,
+    }
+}
+
+// Causal runs the cascade forward once (output lags the input); zero-phase
+// runs it forward and backward with the chosen edge handling
+fn apply_sos(
+    data: &[f64],
+    sos: &[Sos<f64>],
+    edge: EdgeHandling,
+    phase: FilterPhase,
+) -> Result<Vec<f64>, String> {
+    if phase == FilterPhase::Causal {
+        let mut state = sos.to_vec();
+        return Ok(sosfilt_dyn(data.iter().copied(), &mut state));
+    }
+    match edge {
+        EdgeHandling::Padded => {
+            let min_cnt = min_len_for_sosfiltfilt(sos);
+            if data.len() < min_cnt {
+                return Err(format!(
+                    "Requires {} points for filtering. Got {}",
+                    min_cnt,
+                    data.len()
+                ));
+            }
+            Ok(sosfiltfilt_dyn(data.iter().copied(), sos))
+        }
+        EdgeHandling::Gustafsson => Ok(sosfiltfilt_gustafsson(data, sos)),
+    }
+}
+
+// Forward-backward filtering with Gustafsson's initial conditions. The optimal
+// states are solved for the whole cascade at once since individual sections do
+// not have unity gain, so the sections are collapsed to b/a first.
+pub fn sosfiltfilt_gustafsson(data: &[f64], sos: &[Sos<f64>]) -> Vec<f64> {
+    let (b, a) = sos_to_ba(sos);
+    filtfilt_gustafsson(data, &b, &a)
+}
+
+fn sos_to_ba(sos: &[Sos<f64>]) -> (Vec<f64>, Vec<f64>) {
+    sos.iter().fold((vec![1.0], vec![1.0]), |(b, a), section| {
+        (poly_mul(&b, &section.b), poly_mul(&a, &section.a))
+    })
+}
+
+fn filtfilt_gustafsson(x: &[f64], b_in: &[f64], a_in: &[f64]) -> Vec<f64> {
+    let n = x.len();
+    let order = b_in.len().max(a_in.len()) - 1;
+    let a0 = a_in[0];
+    if order == 0 {
+        let scale = (b_in[0] / a0).powi(2);
+        return x.iter().map(|v| v * scale).collect();
+    }
+    if n == 0 {
+        return vec![];
+    }
+    let mut b = vec![0.0; order + 1];
+    let mut a = vec![0.0; order + 1];
+    b[..b_in.len()].copy_from_slice(b_in);
+    a[..a_in.len()].copy_from_slice(a_in);
+    b.iter_mut().chain(a.iter_mut()).for_each(|v| *v /= a0);
+
+    let reversed = |v: &[f64]| v.iter().rev().copied().collect::<Vec<f64>>();
+    let zero_state = vec![0.0; order];
+
+    // Obs propagates an initial state to the output with zero input
+    let mut unit_state = zero_state.clone();
+    unit_state[0] = 1.0;
+    let obs0 = lfilter_state(&b, &a, &vec![0.0; n], &unit_state);
+    let obs: Vec<Vec<f64>> = (0..order)
+        .map(|k| {
+            std::iter::repeat_n(0.0, k)
+                .chain(obs0[..n - k.min(n)].iter().copied())
+                .collect()
+        })
+        .collect();
+    let obs_r: Vec<Vec<f64>> = obs.iter().map(|c| reversed(c)).collect();
+    // S filters the reversed, propagated initial conditions
+    let s: Vec<Vec<f64>> = obs_r
+        .iter()
+        .map(|c| lfilter_state(&b, &a, c, &zero_state))
+        .collect();
+    let s_r: Vec<Vec<f64>> = s.iter().map(|c| reversed(c)).collect();
+
+    // Columns of M = [S^R - O, O^R - S] and W = [S^R, O^R]
+    let sub = |u: &[f64], v: &[f64]| u.iter().zip(v).map(|(p, q)| p - q).collect::<Vec<f64>>();
+    let m_cols: Vec<Vec<f64>> = (0..order)
+        .map(|k| sub(&s_r[k], &obs[k]))
+        .chain((0..order).map(|k| sub(&obs_r[k], &s[k])))
+        .collect();
+    let w_cols: Vec<&Vec<f64>> = s_r.iter().chain(obs_r.iter()).collect();
+
+    // Naive forward-backward and backward-forward passes with zero state
+    let y_f = lfilter_state(&b, &a, x, &zero_state);
+    let y_fb = reversed(&lfilter_state(&b, &a, &reversed(&y_f), &zero_state));
+    let y_b = reversed(&lfilter_state(&b, &a, &reversed(x), &zero_state));
+    let y_bf = lfilter_state(&b, &a, &y_b, &zero_state);
+    let delta = sub(&y_bf, &y_fb);
+
+    let ic = lstsq_columns(&m_cols, &delta);
+    (0..n)
+        .map(|i| {
+            y_fb[i]
+                + w_cols
+                    .iter()
+                    .zip(&ic)
+                    .map(|(col, c)| col[i] * c)
+                    .sum::<f64>()
+        })
+        .collect()
+}
+
+// Transposed direct form II with an explicit initial state (a[0] == 1)
+fn lfilter_state(b: &[f64], a: &[f64], x: &[f64], zi: &[f64]) -> Vec<f64> {
+    let order = zi.len();
+    let mut z = zi.to_vec();
+    x.iter()
+        .map(|&xi| {
+            let y = b[0] * xi + z[0];
+            for k in 0..order - 1 {
+                z[k] = b[k + 1] * xi - a[k + 1] * y + z[k + 1];
+            }
+            z[order - 1] = b[order] * xi - a[order] * y;
+            y
+        })
+        .collect()
+}
+
+// Coefficient convolution, i.e. the product of two polynomials
+pub fn poly_mul(x: &[f64], y: &[f64]) -> Vec<f64> {
+    if x.is_empty() || y.is_empty() {
+        return vec![];
+    }
+    let mut out = vec![0.0; x.len() + y.len() - 1];
+    for (i, xi) in x.iter().enumerate() {
+        for (j, yj) in y.iter().enumerate() {
+            out[i + j] += xi * yj;
+        }
+    }
+    out
+}
+
+// Least squares over a few tall columns via modified Gram-Schmidt (orthogonalized
+// twice); columns that are numerically dependent get zero weight
+fn lstsq_columns(cols: &[Vec<f64>], rhs: &[f64]) -> Vec<f64> {
+    let dot = |u: &[f64], v: &[f64]| u.iter().zip(v).map(|(x, y)| x * y).sum::<f64>();
+    let k = cols.len();
+    let mut q: Vec<Option<Vec<f64>>> = Vec::with_capacity(k);
+    let mut r = vec![vec![0.0; k]; k];
+    for (j, col) in cols.iter().enumerate() {
+        let mut v = col.clone();
+        let norm0 = dot(&v, &v).sqrt();
+        for _ in 0..2 {
+            for (i, qi) in q.iter().enumerate() {
+                if let Some(qi) = qi {
+                    let c = dot(qi, &v);
+                    r[i][j] += c;
+                    v.iter_mut().zip(qi).for_each(|(vv, qq)| *vv -= c * qq);
+                }
+            }
+        }
+        let norm = dot(&v, &v).sqrt();
+        if norm > 0.0 && norm > norm0 * 1e-10 {
+            r[j][j] = norm;
+            v.iter_mut().for_each(|vv| *vv /= norm);
+            q.push(Some(v));
+        } else {
+            q.push(None);
+        }
+    }
+    let mut x = vec![0.0; k];
+    for j in (0..k).rev() {
+        if let Some(qj) = &q[j] {
+            let tail: f64 = ((j + 1)..k).map(|c| r[j][c] * x[c]).sum();
+            x[j] = (dot(qj, rhs) - tail) / r[j][j];
+        }
+    }
+    x
+}
+
+fn butterworth_sos(
+    order: usize,
+    wn: Vec<f64>,
+    band: FilterBandType,
+) -> Result<Vec<Sos<f64>>, String> {
+    let df = butter_dyn(
+        order,
+        wn,
+        Some(band),
+        Some(false),
+        Some(FilterOutputType::Sos),
+        None,
+    );
+
+    match df {
+        DigitalFilter::Sos(SosFormatFilter { sos }) => Ok(sos),
+        _ => Err("butter_dyn did not return SOS output".into()),
+    }
+}
+
+// Second-order sections for a single-cutoff IIR design, without running any
+// data through it. cutoff_freq is normalized to nyquist like the *_filter
+// functions; ripple and attenuation are only read by the families that use them.
+pub fn design_sos(
+    filter: FilterType,
+    band: FilterBand,
+    cutoff_freq: f64,
+    order: usize,
+    ripple: f64,
+    attenuation: f64,
+) -> Result<Vec<Sos<f64>>, String> {
+    match band {
+        FilterBand::Bandstop => {
+            return Err(String::from(
+                "Bandstop needs two cutoffs, use bandstop_filter",
+            ));
+        }
+        FilterBand::Bandpass => {
+            return Err(String::from(
+                "Bandpass needs two cutoffs, use bandpass_cascade",
+            ));
+        }
+        FilterBand::Lowpass | FilterBand::Highpass => {}
+    }
+    family_sos(
+        filter,
+        vec![cutoff_freq],
+        band_type(band),
+        order,
+        ripple,
+        attenuation,
+    )
+}
+
+// Dispatch on the design family once the band edges are known
+fn family_sos(
+    filter: FilterType,
+    wn: Vec<f64>,
+    band: FilterBandType,
+    order: usize,
+    ripple: f64,
+    attenuation: f64,
+) -> Result<Vec<Sos<f64>>, String> {
+    match filter {
+        FilterType::BUTTERWORTH => butterworth_sos(order, wn, band),
+        FilterType::CHEBYSHEV1 => chebyshev1_sos(order, wn, ripple, band),
+        FilterType::CHEBYSHEV2 => chebyshev2_sos(order, wn, attenuation, band),
+        FilterType::ELLIPTIC => {
+            prototype_sos(order, ellipap(order, ripple, attenuation)?, &wn, band)
+        }
+        FilterType::BESSEL => prototype_sos(order, besselap(order)?, &wn, band),
+        FilterType::ALL_PASS => Err(String::from(
+            "All-pass needs a pole radius, use allpass_filter",
+        )),
+        FilterType::MOVING_AVERAGE
+        | FilterType::MEDIAN
+        | FilterType::COMB
+        | FilterType::DIFFERENTIATOR
+        | FilterType::INTEGRATOR
+        | FilterType::CUSTOM => Err(format!("{filter} is not an IIR design")),
+    }
+}
+
+// Band edges are frequencies in cycles/sample
+pub fn butterworth_bandpass_ba(
+    order: usize,
+    f_lo: f64,
+    f_hi: f64,
+) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let nyquist_freq = 1. / NYQUIST_PERIOD;
+    if !(f_lo > 0. && f_lo < f_hi && f_hi < nyquist_freq) {
+        return Err(format!(
+            "Band edges {f_lo}..{f_hi} must be ordered and lie within (0, {nyquist_freq})"
+        ));
+    }
+    let df = butter_dyn(
+        order,
+        vec![f_lo / nyquist_freq, f_hi / nyquist_freq],
+        Some(FilterBandType::Bandpass),
+        Some(false),
+        Some(FilterOutputType::Ba),
+        None,
+    );
+    match df {
+        DigitalFilter::Ba(BaFormatFilter { b, a }) => Ok((b, a)),
+        _ => Err("butter_dyn did not return BA output".into()),
+    }
+}
+
+fn chebyshev1_sos(
+    order: usize,
+    wn: Vec<f64>,
+    ripple: f64,
+    band: FilterBandType,
+) -> Result<Vec<Sos<f64>>, String> {
+    prototype_sos(order, cheb1ap(order, ripple)?, &wn, band)
+}
+
+fn chebyshev2_sos(
+    order: usize,
+    wn: Vec<f64>,
+    attenuation: f64,
+    band: FilterBandType,
+) -> Result<Vec<Sos<f64>>, String> {
+    prototype_sos(order, cheb2ap(order, attenuation)?, &wn, band)
+}
+
+// sci_rs only ships the Butterworth analog prototype, so the other families
+// build their own and go through the same warp/transform/bilinear steps
+fn prototype_sos(
+    order: usize,
+    proto: ZpkFormatFilter<f64>,
+    wn: &[f64],
+    band: FilterBandType,
+) -> Result<Vec<Sos<f64>>, String> {
+    if wn.iter().any(|w| !(*w > 0. && *w < 1.)) {
+        return Err(format!(
+            "Critical frequencies {wn:?} must lie within (0, 1) of nyquist"
+        ));
+    }
+    let fs = 2.0;
+    let warped: Vec<f64> = wn.iter().map(|w| 2.0 * fs * (PI * w / fs).tan()).collect();
+    let zpk = match (band, warped.as_slice()) {
+        (FilterBandType::Lowpass, &[w]) => lp2lp_zpk_dyn(proto, Some(w)),
+        (FilterBandType::Highpass, &[w]) => lp2hp_zpk(proto, w),
+        (FilterBandType::Bandpass, &[lo, hi]) if lo < hi => {
+            lp2bp_zpk_dyn(proto, Some((lo * hi).sqrt()), Some(hi - lo))
+        }
+        (FilterBandType::Bandstop, &[lo, hi]) if lo < hi => {
+            lp2bs_zpk(proto, (lo * hi).sqrt(), hi - lo)
+        }
+        _ => {
+            return Err(String::from(
+                "Band type does not match the critical frequencies",
+            ));
+        }
+    };
+    Ok(zpk2sos_dyn(order, bilinear_zpk_dyn(zpk, fs), None, Some(false)).sos)
+}
+
+// sci_rs' lp2hp/lp2bs mishandle finite zeros, which only Butterworth lacks
+fn lp2hp_zpk(zpk: ZpkFormatFilter<f64>, wo: f64) -> ZpkFormatFilter<f64> {
+    let degree = zpk.p.len() - zpk.z.len();
+    let prod_neg = |v: &[Complex<f64>]| v.iter().map(|x| -x).product::<Complex<f64>>();
+    let k = zpk.k * (prod_neg(&zpk.z) / prod_neg(&zpk.p)).re;
+    let z = zpk
+        .z
+        .iter()
+        .map(|zi| wo / zi)
+        .chain(std::iter::repeat_n(Complex::new(0.0, 0.0), degree))
+        .collect();
+    let p = zpk.p.iter().map(|pi| wo / pi).collect();
+    ZpkFormatFilter::new(z, p, k)
+}
+
+fn lp2bs_zpk(zpk: ZpkFormatFilter<f64>, wo: f64, bw: f64) -> ZpkFormatFilter<f64> {
+    let degree = zpk.p.len() - zpk.z.len();
+    let prod_neg = |v: &[Complex<f64>]| v.iter().map(|x| -x).product::<Complex<f64>>();
+    let k = zpk.k * (prod_neg(&zpk.z) / prod_neg(&zpk.p)).re;
+    let split = |roots: &[Complex<f64>]| -> Vec<Complex<f64>> {
+        let hp: Vec<Complex<f64>> = roots.iter().map(|r| (bw / 2.0) / r).collect();
+        let root = |h: &Complex<f64>| (h * h - wo * wo).sqrt();
+        hp.iter()
+            .map(|h| h + root(h))
+            .chain(hp.iter().map(|h| h - root(h)))
+            .collect()
+    };
+    let mut z = split(&zpk.z);
+    z.extend(std::iter::repeat_n(Complex::new(0.0, wo), degree));
+    z.extend(std::iter::repeat_n(Complex::new(0.0, -wo), degree));
+    ZpkFormatFilter::new(z, split(&zpk.p), k)
+}
+
+fn validate_prototype(order: usize, db: f64, what: &str) -> Result<(), String> {
+    if order == 0 {
+        return Err(String::from("Filter order must be at least 1"));
+    }
+    if !(db.is_finite() && db > 0.) {
+        return Err(format!("{what} must be a positive number of dB, got {db}"));
+    }
+    Ok(())
+}
+
+// Chebyshev type I analog prototype (scipy cheb1ap)
+fn cheb1ap(order: usize, ripple: f64) -> Result<ZpkFormatFilter<f64>, String> {
+    validate_prototype(order, ripple, "Ripple")?;
+    let n = order as f64;
+    let eps = (10f64.powf(0.1 * ripple) - 1.0).sqrt();
+    let mu = (1.0 / eps).asinh() / n;
+    let p: Vec<Complex<f64>> = (0..order)
+        .map(|i| {
+            let theta = PI * (2.0 * i as f64 - n + 1.0) / (2.0 * n);
+            -Complex::new(mu, theta).sinh()
+        })
+        .collect();
+    let mut k = p.iter().map(|pi| -pi).product::<Complex<f64>>().re;
+    if order.is_multiple_of(2) {
+        k /= (1.0 + eps * eps).sqrt();
+    }
+    Ok(ZpkFormatFilter::new(vec![], p, k))
+}
+
+// Chebyshev type II analog prototype (scipy cheb2ap)
+fn cheb2ap(order: usize, attenuation: f64) -> Result<ZpkFormatFilter<f64>, String> {
+    validate_prototype(order, attenuation, "Attenuation")?;
+    let n = order as f64;
+    let de = 1.0 / (10f64.powf(0.1 * attenuation) - 1.0).sqrt();
+    let mu = (1.0 / de).asinh() / n;
+    let z: Vec<Complex<f64>> = (0..order)
+        .map(|i| 2.0 * i as f64 - n + 1.0)
+        .filter(|m| *m != 0.0)
+        .map(|m| -(Complex::i() / (m * PI / (2.0 * n)).sin()).conj())
+        .collect();
+    let p: Vec<Complex<f64>> = (0..order)
+        .map(|i| {
+            let q = -Complex::from_polar(1.0, PI * (2.0 * i as f64 - n + 1.0) / (2.0 * n));
+            Complex::new(mu.sinh() * q.re, mu.cosh() * q.im).inv()
+        })
+        .collect();
+    let prod_neg = |v: &[Complex<f64>]| v.iter().map(|x| -x).product::<Complex<f64>>();
+    let k = (prod_neg(&p) / prod_neg(&z)).re;
+    Ok(ZpkFormatFilter::new(z, p, k))
+}
+
+// Elliptic (Cauer) analog prototype (scipy ellipap)
+fn ellipap(order: usize, ripple: f64, attenuation: f64) -> Result<ZpkFormatFilter<f64>, String> {
+    validate_prototype(order, ripple, "Ripple")?;
+    validate_prototype(order, attenuation, "Attenuation")?;
+    if attenuation <= ripple {
+        return Err(String::from(
+            "Elliptic attenuation must exceed the passband ripple",
+        ));
+    }
+    let eps_sq = 10f64.powf(0.1 * ripple) - 1.0;
+    let eps = eps_sq.sqrt();
+    if order == 1 {
+        let p = -(1.0 / eps_sq).sqrt();
+        return Ok(ZpkFormatFilter::new(vec![], vec![Complex::new(p, 0.0)], -p));
+    }
+    let ck1_sq = eps_sq / (10f64.powf(0.1 * attenuation) - 1.0);
+    if ck1_sq == 0.0 {
+        return Err(String::from(
+            "Cannot design an elliptic filter with these specs",
+        ));
+    }
+    let k1 = ellipk(ck1_sq);
+    let m = ellipdeg(order, ck1_sq);
+    let capk = ellipk(m);
+
+    let jacobi: Vec<(f64, f64, f64)> = ((1 - order % 2)..order)
+        .step_by(2)
+        .map(|j| ellipj(j as f64 * capk / order as f64, m))
+        .collect();
+    let mut z: Vec<Complex<f64>> = jacobi
+        .iter()
+        .filter(|(sn, ..)| sn.abs() > f64::EPSILON)
+        .map(|(sn, ..)| Complex::new(0.0, 1.0 / (m.sqrt() * sn)))
+        .collect();
+    z.extend(z.clone().iter().map(|zi| zi.conj()));
+
+    let r = arc_jac_sc1(1.0 / eps, ck1_sq)?;
+    let v0 = capk * r / (order as f64 * k1);
+    let (sv, cv, dv) = ellipj(v0, 1.0 - m);
+    let mut p: Vec<Complex<f64>> = jacobi
+        .iter()
+        .map(|(s, c, d)| -Complex::new(c * d * sv * cv, s * dv) / (1.0 - (d * sv).powi(2)))
+        .collect();
+    let scale = p.iter().map(|pi| pi.norm_sqr()).sum::<f64>().sqrt();
+    let conj: Vec<Complex<f64>> = p
+        .iter()
+        .filter(|pi| order.is_multiple_of(2) || pi.im.abs() > f64::EPSILON * scale)
+        .map(|pi| pi.conj())
+        .collect();
+    p.extend(conj);
+
+    let prod_neg = |v: &[Complex<f64>]| v.iter().map(|x| -x).product::<Complex<f64>>();
+    let mut k = (prod_neg(&p) / prod_neg(&z)).re;
+    if order.is_multiple_of(2) {
+        k /= (1.0 + eps_sq).sqrt();
+    }
+    Ok(ZpkFormatFilter::new(z, p, k))
+}
+
+// Bessel/Thomson analog prototype, phase normalized so the asymptotes match
+// Butterworth (scipy besselap with norm='phase')
+fn besselap(order: usize) -> Result<ZpkFormatFilter<f64>, String> {
+    if order == 0 {
+        return Err(String::from("Filter order must be at least 1"));
+    }
+    if order > MAX_BESSEL_ORDER {
+        return Err(format!(
+            "Bessel order {order} is above the supported maximum of {MAX_BESSEL_ORDER}"
+        ));
+    }
+    // Reverse Bessel polynomial, ascending: a_k = (2n-k)! / (2^(n-k) k! (n-k)!)
+    let n = order;
+    let coeffs: Vec<f64> = (0..=n)
+        .map(|k| {
+            let num: f64 = ((n - k + 1)..=(2 * n - k)).map(|v| v as f64).product();
+            let den: f64 = (1..=k).map(|v| v as f64).product();
+            num / den / 2f64.powi((n - k) as i32)
+        })
+        .collect();
+    // Substitute s -> s / scale up front so the roots sit near the unit circle
+    let scale = coeffs[0].powf(-1.0 / n as f64);
+    let scaled: Vec<f64> = coeffs
+        .iter()
+        .enumerate()
+        .map(|(k, c)| c / scale.powi(k as i32))
+        .collect();
+    let roots = durand_kerner(&scaled)?;
+
+    // Rebuild exact conjugate pairs so the section pairing sees them as such
+    let mut p: Vec<Complex<f64>> = Vec::with_capacity(n);
+    for r in roots {
+        if r.im.abs() <= 1e-10 * r.norm() {
+            p.push(Complex::new(r.re, 0.0));
+        } else if r.im > 0.0 {
+            p.push(r);
+            p.push(r.conj());
+        }
+    }
+    if p.len() != n {
+        return Err(String::from(
+            "Bessel poles did not come out in conjugate pairs",
+        ));
+    }
+    Ok(ZpkFormatFilter::new(vec![], p, 1.0))
+}
+
+// All complex roots of a real polynomial (ascending coefficients) by the
+// Durand-Kerner iteration
+fn durand_kerner(c: &[f64]) -> Result<Vec<Complex<f64>>, String> {
+    let deg = c.len() - 1;
+    let lead = c[deg];
+    let monic: Vec<f64> = c.iter().map(|v| v / lead).collect();
+    let eval = |x: Complex<f64>| {
+        monic
+            .iter()
+            .rev()
+            .fold(Complex::new(0.0, 0.0), |acc, a| acc * x + a)
+    };
+    let radius = monic[0].abs().powf(1.0 / deg as f64).max(1e-3);
+    let seed = Complex::new(0.4, 0.9);
+    let mut roots: Vec<Complex<f64>> = (0..deg).map(|k| seed.powu(k as u32) * radius).collect();
+    let mut last = f64::INFINITY;
+    for _ in 0..1000 {
+        let mut delta = 0.0_f64;
+        for i in 0..deg {
+            let denom = (0..deg)
+                .filter(|&j| j != i)
+                .map(|j| roots[i] - roots[j])
+                .product::<Complex<f64>>();
+            let step = eval(roots[i]) / denom;
+            roots[i] -= step;
+            delta = delta.max(step.norm() / roots[i].norm().max(1.0));
+        }
+        if delta < 1e-14 {
+            return Ok(roots);
+        }
+        last = delta;
+    }
+    // High degrees stall at a rounding floor well above 1e-14
+    if last < 1e-8 {
+        return Ok(roots);
+    }
+    Err(String::from("Polynomial root iteration did not converge"))
+}
+
+// Complete elliptic integral of the first kind K(m) via the AGM
+fn ellipk(m: f64) -> f64 {
+    PI / (2.0 * agm(1.0, (1.0 - m).sqrt()))
+}
+
+fn agm(mut a: f64, mut b: f64) -> f64 {
+    for _ in 0..64 {
+        if (a - b).abs() <= f64::EPSILON * a {
+            break;
+        }
+        (a, b) = (0.5 * (a + b), (a * b).sqrt());
+    }
+    a
+}
+
+// Degree equation: modulus m for an order n filter with selectivity m1
+fn ellipdeg(order: usize, m1: f64) -> f64 {
+    let k1 = ellipk(m1);
+    let k1p = PI / (2.0 * agm(1.0, m1.sqrt()));
+    let q1 = (-PI * k1p / k1).exp();
+    let q = q1.powf(1.0 / order as f64);
+    let num: f64 = (0..=7).map(|i| q.powi(i * (i + 1))).sum();
+    let den: f64 = 1.0 + 2.0 * (1..=8).map(|i| q.powi(i * i)).sum::<f64>();
+    16.0 * q * (num / den).powi(4)
+}
+
+// Jacobi elliptic functions (sn, cn, dn) by the descending Landen/AGM scheme
+fn ellipj(u: f64, m: f64) -> (f64, f64, f64) {
+    if m <= 0.0 {
+        return (u.sin(), u.cos(), 1.0);
+    }
+    if m >= 1.0 {
+        let sech = 1.0 / u.cosh();
+        return (u.tanh(), sech, sech);
+    }
+    let mut a = vec![1.0];
+    let mut c = vec![m.sqrt()];
+    let mut b = (1.0 - m).sqrt();
+    while c[c.len() - 1].abs() > f64::EPSILON && a.len() < 16 {
+        let an = a[a.len() - 1];
+        c.push(0.5 * (an - b));
+        a.push(0.5 * (an + b));
+        b = (an * b).sqrt();
+    }
+    let n = a.len() - 1;
+    let mut phi = 2f64.powi(n as i32) * a[n] * u;
+    let mut phi_next = phi;
+    for i in (1..=n).rev() {
+        phi_next = phi;
+        phi = 0.5 * (phi + (c[i] / a[i] * phi.sin()).asin());
+    }
+    (phi.sin(), phi.cos(), phi.cos() / (phi_next - phi).cos())
+}
+
+// Imaginary part of the inverse Jacobi sn at a purely imaginary argument,
+// i.e. the inverse of sc, via Landen transformations
+fn arc_jac_sc1(w: f64, m: f64) -> Result<f64, String> {
+    let complement = |k: Complex<f64>| ((1.0 - k) * (1.0 + k)).sqrt();
+    let mut ks = vec![m.sqrt()];
+    while ks[ks.len() - 1] != 0.0 {
+        if ks.len() > 10 {
+            return Err(String::from("Landen sequence failed to converge"));
+        }
+        let k = ks[ks.len() - 1];
+        let kp = ((1.0 - k) * (1.0 + k)).sqrt();
+        ks.push((1.0 - kp) / (1.0 + kp));
+    }
+    let capk = ks[1..].iter().map(|k| 1.0 + k).product::<f64>() * PI / 2.0;
+    let mut wn = Complex::new(0.0, w);
+    for pair in ks.windows(2) {
+        let (kn, knext) = (pair[0], pair[1]);
+        wn = 2.0 * wn / ((1.0 + knext) * (1.0 + complement(kn * wn)));
+    }
+    let z = capk * 2.0 / PI * wn.asin();
+    Ok(z.im)
+}
+
+// Unity gain at the passband reference: DC for lowpass and bandstop, Nyquist
+// (z = -1) for highpass where H(0) = 0. A bandpass has neither; bandpass_cascade
+// normalizes its two stages instead. The first section takes the whole
+// correction, so the sections that run the data and the b/a built from them
+// agree.
+fn normalize_passband_gain(sos: &mut [Sos<f64>], band: FilterBand) {
+    let eval = |c: &[f64]| -> f64 {
+        match band {
+            FilterBand::Lowpass | FilterBand::Bandstop => c.iter().sum(),
+            FilterBand::Bandpass => 1.0,
+            FilterBand::Highpass => c
+                .iter()
+                .enumerate()
+                .map(|(k, v)| if k % 2 == 0 { *v } else { -*v })
+                .sum(),
+        }
+    };
+    let (b, a) = sos_to_ba(sos);
+    let g = eval(&b) / eval(&a);
+    if let Some(first) = sos.first_mut() {
+        first.b.iter_mut().for_each(|bi| *bi /= g);
+    }
+}
+
+pub fn rfft_mag(data: &[f64]) -> Result<Vec<f64>, String> {
+    Ok(rfft_complex(data)?.into_iter().map(|x| x.norm()).collect())
+}
+
+// |X(f)| at a single frequency by the Goertzel recurrence, for when only one
+// cycle matters. Agrees with rfft_mag at bin k when target_freq = k * fs / n,
+// and interpolates the DTFT between bins otherwise. Empty data gives 0.
+pub fn goertzel(data: &[f64], target_freq: f64, fs: f64) -> f64 {
+    let w = 2.0 * PI * target_freq / fs;
+    let coeff = 2.0 * w.cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for &x in data {
+        (s1, s2) = (x + coeff * s1 - s2, s1);
+    }
+    (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0).sqrt()
+}
+
+// Fractional bin of the peak at peak_bin, from the vertex of the parabola
+// through it and its two neighbours. Peaks at either end, or with neighbours
+// that don't bend down around them, keep their integer bin.
+pub fn refine_peak(mag: &[f64], peak_bin: usize) -> f64 {
+    let k = peak_bin;
+    if k == 0 || k + 1 >= mag.len() {
+        return k as f64;
+    }
+    let (a, b, c) = (mag[k - 1], mag[k], mag[k + 1]);
+    let denom = a - 2.0 * b + c;
+    if denom.is_nan() || denom >= 0.0 || !a.is_finite() || !c.is_finite() {
+        return k as f64;
+    }
+    k as f64 + (0.5 * (a - c) / denom).clamp(-0.5, 0.5)
+}
+
+// Period of the largest non-DC spectral peak, in the units fs counts samples
+// per. The mean is removed and a Hann window applied, then the peak is placed
+// between bins with refine_peak on the log magnitudes, which on short records
+// is far finer than the bin spacing. Gaps count as the mean. None for fewer
+// than 4 samples or a flat series.
+pub fn estimate_dominant_period(data: &[f64], fs: f64) -> Option<f64> {
+    let finite = data.iter().filter(|x| x.is_finite());
+    let count = finite.clone().count();
+    if data.len() < 4 || count == 0 || fs.is_nan() || fs <= 0.0 {
+        return None;
+    }
+    let mean = finite.sum::<f64>() / count as f64;
+    let centered: Vec<f64> = data
+        .iter()
+        .map(|&x| if x.is_finite() { x - mean } else { 0.0 })
+        .collect();
+    let mag = rfft_mag(&apply_window(&centered, Window::Hann)).ok()?;
+    let k = (1..mag.len())
+        .filter(|&i| mag[i].is_finite())
+        .max_by(|&i, &j| mag[i].total_cmp(&mag[j]))?;
+    if mag[k] <= 0.0 {
+        return None;
+    }
+    // Log magnitudes make the windowed peak close to a true parabola
+    let log_mag: Vec<f64> = mag[k - 1..(k + 2).min(mag.len())]
+        .iter()
+        .map(|m| m.ln())
+        .collect();
+    let offset = refine_peak(&log_mag, 1) - 1.0;
+    let freq = (k as f64 + offset) * fs / data.len() as f64;
+    Some(1.0 / freq)
+}
+
+// Full rfft bins, for when the phase is needed as well as the magnitude
+pub fn rfft_complex(data: &[f64]) -> Result<Vec<Complex<f64>>, String> {
+    match rfft(data, None) {
+        Ok(r) => Ok(r),
+        Err(_) => Err(String::from("Could not take fft of data")),
+    }
+}
+
+// Inverse of rfft_complex for an n-sample real signal. The conjugate half is
+// rebuilt here and run through the complex ifft, since scirs2's own irfft
+// special-cases some short inputs.
+pub fn irfft(spectrum: &[Complex<f64>], n: usize) -> Result<Vec<f64>, String> {
+    if n == 0 || spectrum.len() != n / 2 + 1 {
+        return Err(format!(
+            "A {n}-sample signal needs {} rfft bins, got {}",
+            n / 2 + 1,
+            spectrum.len()
+        ));
+    }
+    let mut full = Vec::with_capacity(n);
+    full.extend_from_slice(spectrum);
+    // Bins above n/2 mirror 1..ceil(n/2); Nyquist (even n) has no mirror
+    full.extend(spectrum[1..n.div_ceil(2)].iter().rev().map(|c| c.conj()));
+    match ifft(&full, Some(n)) {
+        Ok(out) => Ok(out.into_iter().map(|c| c.re).collect()),
+        Err(_) => Err(String::from("Could not take inverse fft of spectrum")),
+    }
+}
+
+// x + j*H{x} via the FFT: keep DC (and Nyquist for even n), double the
+// positive frequencies, zero the negative ones and invert
+pub fn analytic_signal(data: &[f64]) -> Result<Vec<Complex<f64>>, String> {
+    let n = data.len();
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let half = rfft_complex(data)?;
+    let mut full = vec![Complex::new(0.0, 0.0); n];
+    full[0] = half[0];
+    for k in 1..n.div_ceil(2) {
+        full[k] = 2.0 * half[k];
+    }
+    if n.is_multiple_of(2) {
+        full[n / 2] = half[n / 2];
+    }
+    ifft(&full, Some(n)).map_err(|_| String::from("Could not take inverse fft of spectrum"))
+}
+
+// Instantaneous amplitude |x + j*H{x}|
+pub fn envelope(data: &[f64]) -> Result<Vec<f64>, String> {
+    Ok(analytic_signal(data)?.iter().map(|z| z.norm()).collect())
+}
+
+// Bollinger bands as (middle, upper, lower): the mean of each full window of
+// `period` closes, plus and minus k population standard deviations. Entry j
+// covers closes j..j + period, so the bands start at index period - 1 and are
+// empty when there are fewer closes than that.
+pub fn bollinger_bands(closes: &[f64], period: usize, k: f64) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    if period == 0 || closes.len() < period {
+        return (Vec::new(), Vec::new(), Vec::new());
+    }
+    let mut middle = Vec::with_capacity(closes.len() - period + 1);
+    let mut upper = Vec::with_capacity(middle.capacity());
+    let mut lower = Vec::with_capacity(middle.capacity());
+    for w in closes.windows(period) {
+        let mean = w.iter().sum::<f64>() / period as f64;
+        let sd = (w.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / period as f64).sqrt();
+        middle.push(mean);
+        upper.push(mean + k * sd);
+        lower.push(mean - k * sd);
+    }
+    (middle, upper, lower)
+}
+
+// Sample autocorrelation of the mean-removed series for lags 0..=max_lag
+// (clamped to n - 1), normalized so lag 0 is 1. Computed as the inverse FFT
+// of the power spectrum, zero padded to at least 2n so lags don't wrap.
+pub fn autocorrelation(data: &[f64], max_lag: usize) -> Result<Vec<f64>, String> {
+    let n = data.len();
+    if n < 2 {
+        return Err(format!("Autocorrelation needs at least 2 samples, got {n}"));
+    }
+    let mean = data.iter().sum::<f64>() / n as f64;
+    let centered: Vec<f64> = data.iter().map(|x| x - mean).collect();
+    let n_fft = next_pow2(2 * n);
+    let power: Vec<Complex<f64>> = rfft_complex(&pad_to(&centered, n_fft))?
+        .into_iter()
+        .map(|x| Complex::new(x.norm_sqr(), 0.0))
+        .collect();
+    let acov = irfft(&power, n_fft)?;
+    if acov[0] <= 0.0 {
+        return Err(String::from(
+            "Autocorrelation of a constant series is undefined",
+        ));
+    }
+    Ok(acov[..=max_lag.min(n - 1)]
+        .iter()
+        .map(|c| c / acov[0])
+        .collect())
+}
 
-type PzTuple = (Vec<Complex<f64>>, Vec<Complex<f64>>);
+// Lag of the first autocorrelation peak after the curve first dips below
+// zero, if it clears the 95% white-noise band 1.96/sqrt(n) for n samples
+pub fn acf_period(acf: &[f64], n: usize) -> Option<usize> {
+    let threshold = 1.96 / (n as f64).sqrt();
+    let start = acf.iter().position(|&r| r < 0.0)?;
+    (start.max(1)..acf.len().saturating_sub(1))
+        .find(|&k| acf[k] > acf[k - 1] && acf[k] >= acf[k + 1] && acf[k] > threshold)
+}
 
-pub const NYQUIST_PERIOD: f64 = 2.;
+// Keep DC and the keep_top_k largest remaining bins, zero the rest and invert
+pub fn spectral_denoise(data: &[f64], keep_top_k: usize) -> Result<Vec<f64>, String> {
+    let mut bins = rfft_complex(data)?;
+    let mut order: Vec<usize> = (1..bins.len()).collect();
+    order.sort_by(|&i, &j| bins[j].norm().total_cmp(&bins[i].norm()));
+    for &k in order.iter().skip(keep_top_k) {
+        bins[k] = Complex::new(0.0, 0.0);
+    }
+    irfft(&bins, data.len())
+}
 
-pub struct FilterData {
-    pub filtered_data: Vec<f64>,
-    pub b: Vec<f64>,
-    pub a: Vec<f64>,
+// Sum of one cosine per rfft peak (bin, |X_k|) with the matching phase, over n
+// samples at rate fs. Inverse of taking |rfft| at those bins; DC is left out.
+pub fn reconstruct_from_peaks(
+    peaks: &[(usize, f64)],
+    phases: &[f64],
+    n: usize,
+    fs: f64,
+) -> Vec<f64> {
+    let mut out = vec![0.0; n];
+    if n == 0 {
+        return out;
+    }
+    for (&(k, mag), &phase) in peaks.iter().zip(phases) {
+        // Nyquist has no mirrored negative-frequency bin
+        let scale = if 2 * k == n { 1.0 } else { 2.0 };
+        let amplitude = scale * mag / n as f64;
+        let freq = k as f64 * fs / n as f64;
+        for (i, y) in out.iter_mut().enumerate() {
+            *y += amplitude * (2.0 * PI * freq * i as f64 / fs + phase).cos();
+        }
+    }
+    out
+}
+
+// Magnitude spectrum after tapering, scaled by the window's coherent gain so
+// peak heights stay comparable between windows. The taper covers the real
+// samples only; any padding is appended after it.
+pub fn rfft_mag_windowed(
+    data: &[f64],
+    window: Window,
+    padding: FftPadding,
+) -> Result<Vec<f64>, String> {
+    let w = window_coefficients(data.len(), window);
+    let gain = w.iter().sum::<f64>() / w.len().max(1) as f64;
+    let tapered: Vec<f64> = data.iter().zip(&w).map(|(x, w)| x * w).collect();
+    let mag = rfft_mag(&pad_to(&tapered, fft_len(data.len(), padding)))?;
+    Ok(mag.into_iter().map(|m| m / gain).collect())
 }
 
-// Period in samples
-pub fn cutoff_period_to_nyquist(period: f64) -> Result<f64, String> {
-    if period < NYQUIST_PERIOD {
+// One-sided Welch power spectral density over segment/2 + 1 bins: windowed
+// segments of `segment` samples stepping by segment - overlap, |X|^2 averaged
+// and divided by the window power, so white noise of variance s^2 comes out
+// flat at 2 s^2 (divide by fs for a density per cycle/day). Samples past the
+// last full segment are left out.
+pub fn welch_psd(
+    data: &[f64],
+    segment: usize,
+    overlap: usize,
+    window: Window,
+) -> Result<Vec<f64>, String> {
+    if segment < 2 {
+        return Err(format!(
+            "Welch segment must be at least 2 samples, got {segment}"
+        ));
+    }
+    if overlap >= segment {
+        return Err(format!(
+            "Welch overlap ({overlap}) must be shorter than the segment ({segment})"
+        ));
+    }
+    if data.len() < segment {
         return Err(format!(
-            "Period of {period} is below the nyquist period of {NYQUIST_PERIOD}"
+            "Welch segment ({segment}) is longer than the data ({})",
+            data.len()
         ));
     }
-    Ok(NYQUIST_PERIOD / period)
+    let w = window_coefficients(segment, window);
+    let power = w.iter().map(|w| w * w).sum::<f64>();
+    let mut psd = vec![0.0; segment / 2 + 1];
+    let mut count = 0;
+    for start in (0..=data.len() - segment).step_by(segment - overlap) {
+        let tapered: Vec<f64> = data[start..start + segment]
+            .iter()
+            .zip(&w)
+            .map(|(x, w)| x * w)
+            .collect();
+        for (p, m) in psd.iter_mut().zip(rfft_mag(&tapered)?) {
+            *p += m * m;
+        }
+        count += 1;
+    }
+    // Fold the negative frequencies in; DC and an even-length Nyquist bin have no mirror
+    let last = psd.len() - 1;
+    for (k, p) in psd.iter_mut().enumerate() {
+        let one_sided = if k == 0 || (k == last && segment.is_multiple_of(2)) {
+            1.0
+        } else {
+            2.0
+        };
+        *p *= one_sided / (count as f64 * power);
+    }
+    Ok(psd)
 }
 
-pub fn butterworth_filter(
-    data: &[f64],
-    cutoff_freq: f64,
-    order: usize,
-) -> Result<FilterData, String> {
-    let (mut num, den) = match filter::butter(order, cutoff_freq, "lowpass") {
-        Ok(v) => v,
-        Err(_) => return Err(String::from("P-Z butterworth filter construction failed")),
-    };
-    normalize_lowpass_dc(&mut num, &den);
-    let sos = butterworth_sos(order, vec![cutoff_freq], FilterBandType::Lowpass)?;
-    let min_cnt = min_len_for_sosfiltfilt(&sos);
-    if data.len() < min_cnt {
+// Largest non-DC bin of a magnitude spectrum, taken as the fundamental.
+pub fn fundamental_bin(mag: &[f64]) -> Option<usize> {
+    (1..mag.len())
+        .filter(|&i| mag[i].is_finite())
+        .max_by(|&i, &j| mag[i].total_cmp(&mag[j]))
+}
+
+// Total harmonic distortion as a ratio: the RMS of harmonics 2 through
+// n_harmonics + 1 of the fundamental bin over the fundamental's magnitude.
+// Harmonics past the last bin (Nyquist) are skipped; NaN when the
+// fundamental has no power.
+pub fn thd(mag: &[f64], fundamental_bin: usize, n_harmonics: usize) -> f64 {
+    let fundamental = mag.get(fundamental_bin).copied().unwrap_or(0.0);
+    if fundamental_bin == 0 || fundamental == 0.0 || !fundamental.is_finite() {
+        return f64::NAN;
+    }
+    // Folded from +0.0, since an empty f64 sum is -0.0
+    let harmonic_power = (2..=n_harmonics + 1)
+        .map_while(|h| mag.get(h * fundamental_bin))
+        .fold(0.0, |acc, m| acc + m * m);
+    harmonic_power.sqrt() / fundamental.abs()
+}
+
+// Short-time Fourier transform magnitudes: one row per Hann-tapered frame of
+// `window` samples, frames starting every `hop` samples, each row window/2 + 1
+// bins. A trailing partial frame is dropped.
+pub fn stft(data: &[f64], window: usize, hop: usize) -> Result<Vec<Vec<f64>>, String> {
+    if window < 2 || hop == 0 {
+        return Err(format!(
+            "STFT needs a window of at least 2 and a non-zero hop, got {window} and {hop}"
+        ));
+    }
+    if data.len() < window {
         return Err(format!(
-            "Requires {} points for filtering. Got {}",
-            min_cnt,
+            "STFT window ({window}) is longer than the data ({})",
             data.len()
         ));
     }
-    let filtered = sosfiltfilt_dyn(data.iter().copied(), &sos);
-    Ok(FilterData {
-        filtered_data: filtered,
-        b: num,
-        a: den,
-    })
+    let w = window_coefficients(window, Window::Hann);
+    let gain = w.iter().sum::<f64>() / window as f64;
+    (0..=data.len() - window)
+        .step_by(hop)
+        .map(|start| {
+            let tapered: Vec<f64> = data[start..start + window]
+                .iter()
+                .zip(&w)
+                .map(|(x, w)| x * w)
+                .collect();
+            Ok(rfft_mag(&tapered)?.into_iter().map(|m| m / gain).collect())
+        })
+        .collect()
 }
 
-pub fn chebyshev_filter_1(
-    data: &[f64],
-    cutoff_freq: f64,
-    order: usize,
-    ripple: f64,
-) -> Result<FilterData, String> {
-    let (mut num, den) = match filter::cheby1(order, ripple, cutoff_freq, "lowpass") {
-        Ok(v) => v,
-        Err(_) => return Err(String::from("Butterworth filter construction failed")),
+// Remove the mean or least-squares line so DC and slow trends don't swamp the
+// spectrum. Non-finite samples pass through and are left out of the fit.
+pub fn detrend(data: &[f64], mode: DetrendMode) -> Vec<f64> {
+    let finite = || {
+        data.iter()
+            .enumerate()
+            .filter(|(_, y)| y.is_finite())
+            .map(|(i, &y)| (i as f64, y))
     };
-    normalize_lowpass_dc(&mut num, &den);
-    let sos = chebyshev1_sos(order, vec![cutoff_freq], ripple, FilterBandType::Lowpass)?;
-    let filtered = sosfiltfilt_dyn(data.iter().copied(), &sos);
-    Ok(FilterData {
-        filtered_data: filtered,
-        b: num,
-        a: den,
-    })
-}
-
-pub fn chebyshev_filter_2(
-    data: &[f64],
-    cutoff_freq: f64,
-    order: usize,
-    attenuation: f64,
-) -> Result<FilterData, String> {
-    let (mut num, den) = match filter::cheby2(order, attenuation, cutoff_freq, "lowpass") {
-        Ok(v) => v,
-        Err(_) => return Err(String::from("Butterworth filter construction failed")),
+    let n = finite().count() as f64;
+    if n == 0.0 {
+        return data.to_vec();
+    }
+    let mean_y = finite().map(|(_, y)| y).sum::<f64>() / n;
+    let (slope, intercept) = match mode {
+        DetrendMode::Off => return data.to_vec(),
+        DetrendMode::Constant => (0.0, mean_y),
+        DetrendMode::Linear => {
+            let mean_x = finite().map(|(x, _)| x).sum::<f64>() / n;
+            let sxx: f64 = finite().map(|(x, _)| (x - mean_x).powi(2)).sum();
+            let sxy: f64 = finite().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+            // A single sample has no slope
+            let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+            (slope, mean_y - slope * mean_x)
+        }
     };
-    normalize_lowpass_dc(&mut num, &den);
-    let sos = chebyshev2_sos(
-        order,
-        vec![cutoff_freq],
-        attenuation,
-        FilterBandType::Lowpass,
-    )?;
-    let filtered = sosfiltfilt_dyn(data.iter().copied(), &sos);
-    Ok(FilterData {
-        filtered_data: filtered,
-        b: num,
-        a: den,
-    })
+    data.iter()
+        .enumerate()
+        .map(|(i, y)| y - (intercept + slope * i as f64))
+        .collect()
 }
 
-fn butterworth_sos(
-    order: usize,
-    wn: Vec<f64>,
-    band: FilterBandType,
-) -> Result<Vec<Sos<f64>>, String> {
-    let df = butter_dyn(
-        order,
-        wn,
-        Some(band),
-        Some(false),
-        Some(FilterOutputType::Sos),
-        None,
-    );
+pub fn next_pow2(n: usize) -> usize {
+    n.max(1).next_power_of_two()
+}
 
-    match df {
-        DigitalFilter::Sos(SosFormatFilter { sos }) => Ok(sos),
-        _ => Err("butter_dyn did not return SOS output".into()),
+// Copy of data extended with zeros to len, unchanged if it is already longer
+pub fn pad_to(data: &[f64], len: usize) -> Vec<f64> {
+    let mut out = data.to_vec();
+    if len > out.len() {
+        out.resize(len, 0.0);
     }
+    out
 }
 
-fn chebyshev1_sos(
-    order: usize,
-    wn: Vec<f64>,
-    ripple: f64,
-    band: FilterBandType,
-) -> Result<Vec<Sos<f64>>, String> {
-    let df = iirfilter_dyn(
-        order,
-        wn,
-        Some(ripple),
-        None,
-        Some(band),
-        Some(FilterType::ChebyshevI),
-        Some(false),
-        Some(FilterOutputType::Sos),
-        None,
-    );
-    match df {
-        DigitalFilter::Sos(SosFormatFilter { sos }) => Ok(sos),
-        _ => Err("iirfilter_dyn did not return SOS output".into()),
+// Transform length for n samples. Padding interpolates the spectrum onto
+// finer bins (fs / fft_len) but adds no information: peaks get smoother, not
+// sharper, and resolution is still set by the n real samples.
+pub fn fft_len(n: usize, padding: FftPadding) -> usize {
+    match padding {
+        FftPadding::Off => n,
+        FftPadding::NextPow2 => next_pow2(n),
+        FftPadding::Length(len) => len.max(n),
     }
 }
 
-fn chebyshev2_sos(
-    order: usize,
-    wn: Vec<f64>,
-    attenuation: f64,
-    band: FilterBandType,
-) -> Result<Vec<Sos<f64>>, String> {
-    let df = iirfilter_dyn(
-        order,
-        wn,
-        None,
-        Some(attenuation),
-        Some(band),
-        Some(FilterType::ChebyshevII),
-        Some(false),
-        Some(FilterOutputType::Sos),
-        None,
-    );
-    match df {
-        DigitalFilter::Sos(SosFormatFilter { sos }) => Ok(sos),
-        _ => Err("iirfilter_dyn did not return SOS output".into()),
-    }
+// Local maxima (DC excluded) whose prominence above the surrounding valleys
+// reaches min_prominence, largest first, at most max_peaks of them
+pub fn find_spectral_peaks(
+    mag: &[f64],
+    max_peaks: usize,
+    min_prominence: f64,
+) -> Vec<(usize, f64)> {
+    let n = mag.len();
+    let mut peaks: Vec<(usize, f64)> = (1..n)
+        .filter(|&i| {
+            let m = mag[i];
+            m.is_finite() && m > mag[i - 1] && (i + 1 == n || m >= mag[i + 1])
+        })
+        .filter(|&i| {
+            // Lowest point on each side before reaching something taller
+            let base = |range: &mut dyn Iterator<Item = usize>| {
+                let mut low = mag[i];
+                for j in range {
+                    if mag[j] > mag[i] {
+                        break;
+                    }
+                    low = low.min(mag[j]);
+                }
+                low
+            };
+            let left = base(&mut (0..i).rev());
+            let right = base(&mut (i + 1..n));
+            mag[i] - left.max(right) >= min_prominence
+        })
+        .map(|i| (i, mag[i]))
+        .collect();
+    peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+    peaks.truncate(max_peaks);
+    peaks
 }
 
-fn normalize_lowpass_dc(b: &mut [f64], a: &[f64]) {
-    let sum_b: f64 = b.iter().sum();
-    let sum_a: f64 = a.iter().sum();
-    let g = sum_b / sum_a; // H(0)
-    for bi in b.iter_mut() {
-        *bi /= g; // force H(0) = 1 (unity DC gain)
-    }
+pub fn apply_window(data: &[f64], window: Window) -> Vec<f64> {
+    data.iter()
+        .zip(window_coefficients(data.len(), window))
+        .map(|(x, w)| x * w)
+        .collect()
 }
 
-pub fn rfft_mag(data: &[f64]) -> Result<Vec<f64>, String> {
-    let output = match rfft(data, None) {
-        Ok(r) => r,
-        Err(_) => return Err(String::from("Could not take fft of data")),
-    };
-    Ok(output.into_iter().map(|x| x.norm()).collect())
+// Symmetric window of n points
+fn window_coefficients(n: usize, window: Window) -> Vec<f64> {
+    if n < 2 {
+        return vec![1.0; n];
+    }
+    let span = (n - 1) as f64;
+    (0..n)
+        .map(|i| {
+            let t = 2.0 * PI * i as f64 / span;
+            match window {
+                Window::Rectangular => 1.0,
+                Window::Hann => 0.5 - 0.5 * t.cos(),
+                Window::Hamming => 0.54 - 0.46 * t.cos(),
+                Window::Blackman => 0.42 - 0.5 * t.cos() + 0.08 * (2.0 * t).cos(),
+            }
+        })
+        .collect()
 }
 
 // c in ascending order
@@ -241,17 +1817,107 @@ pub fn iir_zeros_poles_z(b: &[f64], a: &[f64]) -> Result<PzTuple, String> {
     Ok((zeros_z, poles_z))
 }
 
-pub fn bode_mag_logspace(b: &[f64], a: &[f64], fs: f64, n_points: usize) -> (Vec<f64>, Vec<f64>) {
-    let n_points = n_points.max(16);
+// Collapse roots that coincide to within tol into (root, multiplicity) pairs
+pub fn group_roots(roots: &[Complex<f64>], tol: f64) -> Vec<(Complex<f64>, usize)> {
+    let mut groups: Vec<(Complex<f64>, usize)> = Vec::new();
+    for &r in roots {
+        match groups
+            .iter_mut()
+            .find(|(g, _)| *g == r || (*g - r).norm() <= tol)
+        {
+            Some((g, k)) => {
+                if g.is_finite() {
+                    *g = (*g * *k as f64 + r) / (*k + 1) as f64;
+                }
+                *k += 1;
+            }
+            None => groups.push((r, 1)),
+        }
+    }
+    groups
+}
+
+pub fn bode_mag_logspace(
+    b: &[f64],
+    a: &[f64],
+    fs: f64,
+    range: &BodeRange,
+) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let (freqs, h) = freq_response_logspace(b, a, fs, range)?;
+    Ok((freqs, h.iter().map(|h| h.norm()).collect()))
+}
+
+// Same grid as bode_mag_logspace, magnitude in dB floored at floor_db
+pub fn bode_mag_db_logspace(
+    b: &[f64],
+    a: &[f64],
+    fs: f64,
+    range: &BodeRange,
+    floor_db: f64,
+) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let (freqs, mag) = bode_mag_logspace(b, a, fs, range)?;
+    Ok((freqs, mag.iter().map(|&m| to_db(m, floor_db)).collect()))
+}
+
+// Unwrapped phase of H(e^{jw}) in degrees over the same grid as the magnitude
+pub fn bode_phase_logspace(
+    b: &[f64],
+    a: &[f64],
+    fs: f64,
+    range: &BodeRange,
+) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let (freqs, h) = freq_response_logspace(b, a, fs, range)?;
+    // Phase is meaningless where H sits on a zero (e.g. z = -1 at Nyquist)
+    let floor = 1e-12 * h.iter().map(|h| h.norm()).fold(0.0, f64::max);
+    let wrapped: Vec<f64> = h
+        .iter()
+        .map(|h| if h.norm() <= floor { f64::NAN } else { h.arg() })
+        .collect();
+    let phase = unwrap_phase(&wrapped)
+        .into_iter()
+        .map(f64::to_degrees)
+        .collect();
+    Ok((freqs, phase))
+}
+
+// Radian phase made continuous: whenever a sample jumps by more than pi from
+// the previous one, whole turns of 2*pi are added or removed. Non-finite
+// samples stay NaN and the next finite one continues from the last good value.
+pub fn unwrap_phase(phase: &[f64]) -> Vec<f64> {
+    let mut offset = 0.0_f64;
+    let mut prev: Option<f64> = None;
+    phase
+        .iter()
+        .map(|&raw| {
+            if !raw.is_finite() {
+                return f64::NAN;
+            }
+            if let Some(p) = prev {
+                offset -= (2.0 * PI) * ((raw + offset - p) / (2.0 * PI)).round();
+            }
+            let unwrapped = raw + offset;
+            prev = Some(unwrapped);
+            unwrapped
+        })
+        .collect()
+}
 
-    let f_min = (fs * 1e-4).max(1e-9);
-    let f_max = (fs * 0.5).max(f_min * 10.0);
+// Complex H(e^{jw}) at log-spaced frequencies over the range
+fn freq_response_logspace(
+    b: &[f64],
+    a: &[f64],
+    fs: f64,
+    range: &BodeRange,
+) -> Result<(Vec<f64>, Vec<Complex<f64>>), String> {
+    range.validate(fs)?;
+    let n_points = range.n_points;
+    let (f_min, f_max) = (range.f_min, range.f_max);
 
     let log_fmin = f_min.ln();
     let log_fmax = f_max.ln();
 
     let mut freqs = Vec::with_capacity(n_points);
-    let mut mags = Vec::with_capacity(n_points);
+    let mut response = Vec::with_capacity(n_points);
 
     for i in 0..n_points {
         let t = i as f64 / (n_points - 1) as f64;
@@ -287,19 +1953,169 @@ pub fn bode_mag_logspace(b: &[f64], a: &[f64], fs: f64, n_points: usize) -> (Vec
 
         // H = num/den
         let den_mag2 = den_r * den_r + den_i * den_i;
-        let mag = if den_mag2 > 0.0 {
+        let h = if den_mag2 > 0.0 {
             let h_r = (num_r * den_r + num_i * den_i) / den_mag2;
             let h_i = (num_i * den_r - num_r * den_i) / den_mag2;
-            (h_r * h_r + h_i * h_i).sqrt()
+            Complex::new(h_r, h_i)
         } else {
-            f64::NAN
+            Complex::new(f64::NAN, f64::NAN)
         };
 
         freqs.push(f);
-        mags.push(mag);
+        response.push(h);
+    }
+
+    Ok((freqs, response))
+}
+
+// H(e^{jw}) at n points evenly spaced over w in [0, pi]. NaN where the
+// denominator vanishes (a pole sitting on the unit circle).
+pub fn nyquist_curve(b: &[f64], a: &[f64], n: usize) -> Vec<Complex<f64>> {
+    let eval = |coeffs: &[f64], z_inv: Complex<f64>| {
+        coeffs
+            .iter()
+            .rev()
+            .fold(Complex::new(0.0, 0.0), |acc, &c| acc * z_inv + c)
+    };
+    (0..n)
+        .map(|i| {
+            let omega = PI * i as f64 / (n.max(2) - 1) as f64;
+            let z_inv = Complex::from_polar(1.0, -omega);
+            let den = eval(a, z_inv);
+            if den.norm_sqr() > 0.0 {
+                eval(b, z_inv) / den
+            } else {
+                Complex::new(f64::NAN, f64::NAN)
+            }
+        })
+        .collect()
+}
+
+// Response of the causal difference equation to a unit step of length n
+pub fn step_response(b: &[f64], a: &[f64], n: usize) -> Vec<f64> {
+    difference_equation(b, a, &vec![1.0; n])
+}
+
+// Response of the causal difference equation to a unit impulse, n samples
+pub fn impulse_response(b: &[f64], a: &[f64], n: usize) -> Vec<f64> {
+    let mut x = vec![0.0; n];
+    if let Some(first) = x.first_mut() {
+        *first = 1.0;
+    }
+    difference_equation(b, a, &x)
+}
+
+// Direct-form evaluation from rest, a[0] normalized to 1
+fn difference_equation(b: &[f64], a: &[f64], x: &[f64]) -> Vec<f64> {
+    let a0 = match a.first() {
+        Some(&v) if v != 0.0 && v.is_finite() => v,
+        _ => return vec![f64::NAN; x.len()],
+    };
+    let b: Vec<f64> = b.iter().map(|v| v / a0).collect();
+    let a: Vec<f64> = a.iter().map(|v| v / a0).collect();
+
+    let mut y = vec![0.0; x.len()];
+    for i in 0..x.len() {
+        let mut acc = 0.0;
+        for (k, &bk) in b.iter().enumerate().take(i + 1) {
+            acc += bk * x[i - k];
+        }
+        // FIR (a == [1.0]) leaves this loop empty
+        for (k, &ak) in a.iter().enumerate().skip(1).take(i) {
+            acc -= ak * y[i - k];
+        }
+        y[i] = acc;
+    }
+    y
+}
+
+// 20 log10 of an amplitude, clamped at floor_db so zeros never reach -inf.
+// NaN stays NaN so plots can still break the line there.
+pub fn to_db(mag: f64, floor_db: f64) -> f64 {
+    if mag > 0.0 {
+        (20.0 * mag.log10()).max(floor_db)
+    } else if mag.is_nan() {
+        f64::NAN
+    } else {
+        floor_db
+    }
+}
+
+// First frequency where the response drops through -3 dB, interpolated in log f
+pub fn minus_3db_freq(freqs: &[f64], mag_db: &[f64]) -> Option<f64> {
+    let pts: Vec<(f64, f64)> = freqs
+        .iter()
+        .zip(mag_db)
+        .map(|(&f, &m)| (f, m))
+        .filter(|(f, m)| f.is_finite() && *f > 0.0 && m.is_finite())
+        .collect();
+    pts.windows(2).find_map(|w| {
+        let ((f0, m0), (f1, m1)) = (w[0], w[1]);
+        if m0 >= -3.0 && m1 < -3.0 {
+            let t = (m0 + 3.0) / (m0 - m1);
+            Some(10f64.powf(f0.log10() + t * (f1.log10() - f0.log10())))
+        } else {
+            None
+        }
+    })
+}
+
+// First frequency where the response crosses 3 dB below its peak, in either
+// direction, interpolated in log f
+pub fn peak_minus_3db_freq(freqs: &[f64], mag_db: &[f64]) -> Option<f64> {
+    let pts: Vec<(f64, f64)> = freqs
+        .iter()
+        .zip(mag_db)
+        .map(|(&f, &m)| (f, m))
+        .filter(|(f, m)| f.is_finite() && *f > 0.0 && m.is_finite())
+        .collect();
+    let level = pts.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max) - 3.0;
+    pts.windows(2).find_map(|w| {
+        let ((f0, m0), (f1, m1)) = (w[0], w[1]);
+        if (m0 - level) * (m1 - level) <= 0.0 && m0 != m1 {
+            let t = (m0 - level) / (m0 - m1);
+            Some(10f64.powf(f0.log10() + t * (f1.log10() - f0.log10())))
+        } else {
+            None
+        }
+    })
+}
+
+// Straight-line fit of dB against log10(f) from twice the -3 dB frequency up
+// to half the top frequency, away from the bilinear zero at Nyquist.
+// Returns (slope dB/decade, intercept dB, f_start, f_end)
+pub fn stopband_fit(freqs: &[f64], mag_db: &[f64]) -> Option<(f64, f64, f64, f64)> {
+    let f_3db = minus_3db_freq(freqs, mag_db)?;
+    let f_top = freqs
+        .iter()
+        .copied()
+        .filter(|f| f.is_finite())
+        .fold(0.0, f64::max);
+    let (f_start, f_end) = (2.0 * f_3db, 0.5 * f_top);
+    let pts: Vec<(f64, f64)> = freqs
+        .iter()
+        .zip(mag_db)
+        .filter(|(f, m)| **f >= f_start && **f <= f_end && m.is_finite())
+        .map(|(f, m)| (f.log10(), *m))
+        .collect();
+    if pts.len() < 3 {
+        return None;
+    }
+    let n = pts.len() as f64;
+    let mean_x = pts.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pts.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = pts.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = pts.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    if sxx <= 0.0 {
+        return None;
     }
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x, f_start, f_end))
+}
 
-    (freqs, mags)
+// Measured stopband rolloff in dB/decade; NaN when there is no usable stopband
+pub fn stopband_slope(freqs: &[f64], mag_db: &[f64]) -> f64 {
+    stopband_fit(freqs, mag_db).map_or(f64::NAN, |(slope, ..)| slope)
 }
 
 fn min_len_for_sosfiltfilt<
@@ -317,3 +2133,625 @@ fn min_len_for_sosfiltfilt<
     let edge = 3 * ntaps;
     edge + 1
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_signal(n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| (i as f64 * 0.2).sin() + 0.5 * (i as f64 * 1.9).cos() + 1.0)
+            .collect()
+    }
+
+    fn assert_close(x: &[f64], y: &[f64], tol: f64) {
+        assert_eq!(x.len(), y.len());
+        for (i, (a, b)) in x.iter().zip(y).enumerate() {
+            assert!((a - b).abs() < tol, "sample {i}: {a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn normalized_sections_match_the_reported_coefficients() {
+        let data = test_signal(200);
+        // Even-order Chebyshev I sits at -ripple dB at DC before normalizing
+        let out = chebyshev_filter_1(
+            &data,
+            0.2,
+            4,
+            5.0,
+            FilterBand::Lowpass,
+            EdgeHandling::Padded,
+            FilterPhase::Causal,
+        )
+        .unwrap();
+        let dc = out.b.iter().sum::<f64>() / out.a.iter().sum::<f64>();
+        assert!((dc - 1.0).abs() < 1e-9, "DC gain {dc}");
+        assert_close(
+            &out.filtered_data,
+            &difference_equation(&out.b, &out.a, &data),
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn notch_periods_are_in_sample_rate_units() {
+        // Hourly samples of a half-day cycle; at 1 sample/day 0.5 is past Nyquist
+        let fs = 24.0;
+        let data: Vec<f64> = (0..480)
+            .map(|i| (2.0 * PI * 2.0 * i as f64 / fs).sin())
+            .collect();
+        let out = notch_filter(
+            &data,
+            0.5,
+            0.2,
+            fs,
+            2,
+            EdgeHandling::Padded,
+            FilterPhase::ZeroPhase,
+        )
+        .unwrap();
+        let mid = &out.filtered_data[120..360];
+        let rms = (mid.iter().map(|v| v * v).sum::<f64>() / mid.len() as f64).sqrt();
+        assert!(rms < 0.05, "notch left rms {rms}");
+        assert!(
+            notch_filter(
+                &data,
+                0.5,
+                0.2,
+                1.0,
+                2,
+                EdgeHandling::Padded,
+                FilterPhase::ZeroPhase
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn chained_custom_stage_keeps_its_section() {
+        let data = test_signal(64);
+        let first = butterworth_filter(
+            &data,
+            0.3,
+            2,
+            FilterBand::Lowpass,
+            EdgeHandling::Padded,
+            FilterPhase::Causal,
+        )
+        .unwrap();
+        let custom = custom_filter(
+            &first.filtered_data,
+            &[0.5, 0.5],
+            &[1.0],
+            FilterPhase::Causal,
+        )
+        .unwrap();
+        let n = first.sos.len();
+        let chained = chain_filter_data(first, custom);
+        assert_eq!(chained.sos.len(), n + 1);
+        let last = chained.sos.last().unwrap();
+        assert_eq!((last.b, last.a), ([0.5, 0.5, 0.0], [1.0, 0.0, 0.0]));
+        // The padded section adds trailing zero coefficients
+        let (b, a) = sos_to_ba(&chained.sos);
+        assert_close(&b[..chained.b.len()], &chained.b, 1e-12);
+        assert_close(&a[..chained.a.len()], &chained.a, 1e-12);
+        assert!(
+            b[chained.b.len()..]
+                .iter()
+                .chain(&a[chained.a.len()..])
+                .all(|c| *c == 0.0)
+        );
+    }
+
+    #[test]
+    fn thd_of_a_known_harmonic_mix() {
+        // Fundamental at bin 4 with 3rd and 5th harmonics at 0.3 and 0.4
+        let n = 128;
+        let data: Vec<f64> = (0..n)
+            .map(|i| {
+                let w = 2.0 * PI * 4.0 * i as f64 / n as f64;
+                w.sin() + 0.3 * (3.0 * w).sin() + 0.4 * (5.0 * w).sin()
+            })
+            .collect();
+        let mag = rfft_mag(&data).unwrap();
+        let k = fundamental_bin(&mag).unwrap();
+        assert_eq!(k, 4);
+        let ratio = thd(&mag, k, 9);
+        assert!((ratio - 0.5).abs() < 1e-9, "THD {ratio}");
+        assert!(thd(&mag, 0, 9).is_nan());
+        // Harmonics past Nyquist are left out rather than wrapping
+        assert!(thd(&mag, 20, 9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn poly_mul_known_products() {
+        // (1 + x)(1 - x) = 1 - x^2
+        assert_eq!(poly_mul(&[1.0, 1.0], &[1.0, -1.0]), vec![1.0, 0.0, -1.0]);
+        // (1 + 2x)(3 + x + x^2) = 3 + 7x + 3x^2 + 2x^3
+        assert_eq!(
+            poly_mul(&[1.0, 2.0], &[3.0, 1.0, 1.0]),
+            vec![3.0, 7.0, 3.0, 2.0]
+        );
+        // A length-1 factor just scales
+        assert_eq!(poly_mul(&[2.0], &[1.0, -0.5, 0.25]), vec![2.0, -1.0, 0.5]);
+        assert_eq!(poly_mul(&[1.0, -0.5], &[4.0]), vec![4.0, -2.0]);
+        assert!(poly_mul(&[], &[1.0]).is_empty());
+    }
+
+    #[test]
+    fn refine_peak_finds_a_tone_between_bins() {
+        // A tone at bin 10.3 of a 256-point transform
+        let n = 256;
+        let data: Vec<f64> = (0..n)
+            .map(|i| (2.0 * PI * 10.3 * i as f64 / n as f64).sin())
+            .collect();
+        let mag = rfft_mag_windowed(&data, Window::Hann, FftPadding::Off).unwrap();
+        let log_mag: Vec<f64> = mag.iter().map(|m| m.ln()).collect();
+        let peak = fundamental_bin(&mag).unwrap();
+        assert_eq!(peak, 10);
+        let refined = refine_peak(&log_mag, peak);
+        assert!((refined - 10.3).abs() < 0.05, "refined to {refined}");
+        // Ends keep their integer bin
+        assert_eq!(refine_peak(&log_mag, 0), 0.0);
+        assert_eq!(
+            refine_peak(&log_mag, log_mag.len() - 1),
+            (log_mag.len() - 1) as f64
+        );
+    }
+
+    #[test]
+    fn bandpass_cascade_blocks_dc_and_nyquist() {
+        let data = test_signal(200);
+        // At even orders Chebyshev II and elliptic stop bands only come down to
+        // the 40 dB attenuation rather than a zero; the elliptic's is 1 dB
+        // closer, since normalizing lifts its passband out of the ripple
+        for (kind, tol) in [
+            (FilterType::BUTTERWORTH, 1e-6),
+            (FilterType::CHEBYSHEV1, 1e-6),
+            (FilterType::CHEBYSHEV2, 10f64.powf(-40.0 / 20.0) + 1e-6),
+            (FilterType::ELLIPTIC, 10f64.powf(-39.0 / 20.0) + 1e-6),
+            (FilterType::BESSEL, 1e-6),
+        ] {
+            let out = bandpass_cascade(
+                &data,
+                0.1,
+                0.4,
+                4,
+                4,
+                kind,
+                1.0,
+                40.0,
+                EdgeHandling::Padded,
+                FilterPhase::ZeroPhase,
+            )
+            .unwrap();
+            let at = |sign: f64| {
+                let eval = |c: &[f64]| {
+                    c.iter()
+                        .enumerate()
+                        .fold(0.0, |acc, (k, v)| acc + v * sign.powi(k as i32))
+                };
+                (eval(&out.b) / eval(&out.a)).abs()
+            };
+            assert!(at(1.0) < tol, "{kind} DC gain {}", at(1.0));
+            assert!(at(-1.0) < tol, "{kind} Nyquist gain {}", at(-1.0));
+        }
+    }
+
+    #[test]
+    fn gustafsson_zero_phase_is_time_symmetric_and_settles_the_edges() {
+        let data = test_signal(240);
+        let reversed: Vec<f64> = data.iter().rev().copied().collect();
+        let run = |x: &[f64], edge: EdgeHandling| {
+            butterworth_filter(x, 0.2, 4, FilterBand::Lowpass, edge, FilterPhase::ZeroPhase)
+                .unwrap()
+                .filtered_data
+        };
+        let gust = run(&data, EdgeHandling::Gustafsson);
+        // Forward-backward and backward-forward agree, so reversing the input
+        // just reverses the output (to the least-squares solve's precision)
+        let mut back = run(&reversed, EdgeHandling::Gustafsson);
+        back.reverse();
+        assert_close(&gust, &back, 1e-5);
+        // Away from the ends the initial conditions have died out
+        let padded = run(&data, EdgeHandling::Padded);
+        assert_close(&gust[100..140], &padded[100..140], 1e-6);
+
+        // A slow filter outlasts the 3 * ntaps odd padding, so on a step the
+        // padded ends are still ringing where Gustafsson's sit near the levels
+        let step: Vec<f64> = (0..200).map(|i| if i < 100 { 1.0 } else { 2.0 }).collect();
+        let slow = |edge: EdgeHandling| {
+            butterworth_filter(
+                &step,
+                0.02,
+                3,
+                FilterBand::Lowpass,
+                edge,
+                FilterPhase::ZeroPhase,
+            )
+            .unwrap()
+            .filtered_data
+        };
+        let (gust, padded) = (slow(EdgeHandling::Gustafsson), slow(EdgeHandling::Padded));
+        for (i, level) in [(0, 1.0), (199, 2.0)] {
+            assert!(
+                (gust[i] - level).abs() < (padded[i] - level).abs(),
+                "sample {i}: Gustafsson {} vs padded {}",
+                gust[i],
+                padded[i]
+            );
+        }
+
+        let custom = |x: &[f64]| {
+            custom_filter(x, &[0.5, 0.5], &[1.0, -0.2], FilterPhase::ZeroPhase)
+                .unwrap()
+                .filtered_data
+        };
+        let mut back = custom(&reversed);
+        back.reverse();
+        assert_close(&custom(&data), &back, 1e-9);
+    }
+
+    #[test]
+    fn filtered_output_check_rejects_nan_and_flat_output() {
+        let input = test_signal(16);
+        assert!(check_filtered_output(&input, &input).is_ok());
+        assert!(check_filtered_output(&input, &[f64::NAN; 16]).is_err());
+        assert!(check_filtered_output(&input, &[0.0; 16]).is_err());
+        // A flat input may come out flat
+        assert!(check_filtered_output(&[1.0; 16], &[1.0; 16]).is_ok());
+    }
+
+    #[test]
+    fn bessel_lowpass_has_unit_dc_gain_and_flatter_delay_than_butterworth() {
+        let data = test_signal(200);
+        let out = bessel_filter(
+            &data,
+            0.2,
+            4,
+            FilterBand::Lowpass,
+            EdgeHandling::Padded,
+            FilterPhase::Causal,
+        )
+        .unwrap();
+        let dc = out.b.iter().sum::<f64>() / out.a.iter().sum::<f64>();
+        assert!((dc - 1.0).abs() < 1e-9, "DC gain {dc}");
+        // Group delay -dphi/domega across the passband (-3 dB near 0.1
+        // cycles/sample) barely moves for Bessel, while a Butterworth of the
+        // same order peaks towards its cutoff
+        let range = BodeRange {
+            f_min: 0.005,
+            f_max: 0.1,
+            n_points: 40,
+        };
+        let spread = |b: &[f64], a: &[f64]| {
+            let (freqs, phase) = bode_phase_logspace(b, a, 1.0, &range).unwrap();
+            let delays: Vec<f64> = freqs
+                .windows(2)
+                .zip(phase.windows(2))
+                .map(|(f, p)| -(p[1] - p[0]) / (360.0 * (f[1] - f[0])))
+                .collect();
+            let (lo, hi) = delays
+                .iter()
+                .fold((f64::INFINITY, 0.0_f64), |(l, h), d| (l.min(*d), h.max(*d)));
+            hi - lo
+        };
+        let butter = butterworth_filter(
+            &data,
+            0.2,
+            4,
+            FilterBand::Lowpass,
+            EdgeHandling::Padded,
+            FilterPhase::Causal,
+        )
+        .unwrap();
+        let (bessel_spread, butter_spread) = (spread(&out.b, &out.a), spread(&butter.b, &butter.a));
+        assert!(
+            bessel_spread < butter_spread,
+            "Bessel delay spread {bessel_spread} vs Butterworth {butter_spread}"
+        );
+    }
+
+    #[test]
+    fn impulse_response_of_a_one_pole_filter() {
+        // y[n] = x[n] + 0.5 y[n-1] rings down as 0.5^n
+        let h = impulse_response(&[1.0], &[1.0, -0.5], 6);
+        assert_close(&h, &[1.0, 0.5, 0.25, 0.125, 0.0625, 0.03125], 1e-12);
+        // The step response is its running sum
+        let s = step_response(&[1.0], &[1.0, -0.5], 6);
+        let mut acc = 0.0;
+        for (hi, si) in h.iter().zip(&s) {
+            acc += hi;
+            assert!((acc - si).abs() < 1e-12);
+        }
+        assert!(impulse_response(&[1.0], &[1.0], 0).is_empty());
+    }
+
+    #[test]
+    fn bode_magnitude_is_in_db_with_a_floor() {
+        let range = BodeRange {
+            f_min: 0.01,
+            f_max: 0.5,
+            n_points: 16,
+        };
+        // Unity gain at every frequency is 0 dB
+        let (_, flat) = bode_mag_db_logspace(&[1.0], &[1.0], 1.0, &range, -120.0).unwrap();
+        assert!(flat.iter().all(|m| m.abs() < 1e-9));
+        // (1 + z^-1) / 2 is 1 at DC and 0 at Nyquist, which hits the floor
+        let (freqs, mag) = bode_mag_db_logspace(&[0.5, 0.5], &[1.0], 1.0, &range, -120.0).unwrap();
+        assert!((mag[0] - to_db((PI * freqs[0]).cos(), -120.0)).abs() < 1e-9);
+        assert_eq!(*mag.last().unwrap(), -120.0);
+        assert_eq!(to_db(0.1, -120.0), -20.0);
+    }
+
+    #[test]
+    fn windows_taper_to_the_edges_and_keep_peak_heights() {
+        let n = 64;
+        let tone: Vec<f64> = (0..n)
+            .map(|i| (2.0 * PI * 8.0 * i as f64 / n as f64).cos())
+            .collect();
+        for window in [
+            Window::Rectangular,
+            Window::Hann,
+            Window::Hamming,
+            Window::Blackman,
+        ] {
+            let w = window_coefficients(n, window);
+            assert_eq!(w.len(), n);
+            let mag = rfft_mag_windowed(&tone, window, FftPadding::Off).unwrap();
+            // Coherent gain scaling keeps an on-bin tone at about n/2; the
+            // symmetric tapers aren't exactly periodic in n
+            assert!(
+                (mag[8] - n as f64 / 2.0).abs() < 0.05,
+                "{window}: {}",
+                mag[8]
+            );
+        }
+        let hann = window_coefficients(n, Window::Hann);
+        assert!(hann[0].abs() < 1e-12);
+        assert!(
+            window_coefficients(n, Window::Rectangular)
+                .iter()
+                .all(|w| *w == 1.0)
+        );
+    }
+
+    #[test]
+    fn irfft_inverts_rfft_and_denoise_keeps_the_top_bins() {
+        for n in [7, 8] {
+            let data = test_signal(n);
+            let back = irfft(&rfft_complex(&data).unwrap(), n).unwrap();
+            assert_close(&back, &data, 1e-9);
+        }
+        assert!(irfft(&[Complex::new(1.0, 0.0)], 8).is_err());
+        // A tone plus a smaller one: keeping one bin drops the smaller tone
+        let n = 64;
+        let two: Vec<f64> = (0..n)
+            .map(|i| {
+                let t = 2.0 * PI * i as f64 / n as f64;
+                1.0 + (4.0 * t).sin() + 0.1 * (11.0 * t).sin()
+            })
+            .collect();
+        let kept = spectral_denoise(&two, 1).unwrap();
+        let expected: Vec<f64> = (0..n)
+            .map(|i| 1.0 + (4.0 * 2.0 * PI * i as f64 / n as f64).sin())
+            .collect();
+        assert_close(&kept, &expected, 1e-9);
+    }
+
+    #[test]
+    fn unwrap_phase_removes_two_pi_jumps_and_skips_nan() {
+        // A steady -1 rad/sample ramp, wrapped into (-pi, pi]
+        let ramp: Vec<f64> = (0..12).map(|i| -(i as f64)).collect();
+        let wrapped: Vec<f64> = ramp
+            .iter()
+            .map(|p| (p + PI).rem_euclid(2.0 * PI) - PI)
+            .collect();
+        assert_close(&unwrap_phase(&wrapped), &ramp, 1e-9);
+        let out = unwrap_phase(&[0.0, 3.0, f64::NAN, -3.0]);
+        assert!(out[2].is_nan());
+        assert!((out[3] - (2.0 * PI - 3.0)).abs() < 1e-9, "{}", out[3]);
+    }
+
+    #[test]
+    fn goertzel_matches_the_rfft_bin() {
+        let data = test_signal(50);
+        let mag = rfft_mag(&data).unwrap();
+        for k in [0, 3, 16, 25] {
+            let g = goertzel(&data, k as f64 * 2.0 / 50.0, 2.0);
+            assert!((g - mag[k]).abs() < 1e-9, "bin {k}: {g} vs {}", mag[k]);
+        }
+        assert_eq!(goertzel(&[], 0.1, 1.0), 0.0);
+    }
+
+    #[test]
+    fn bessel_caps_the_order_below_the_global_maximum() {
+        assert_eq!(FilterType::BESSEL.max_order(), MAX_BESSEL_ORDER);
+        assert_eq!(FilterType::BUTTERWORTH.max_order(), crate::MAX_ORDER);
+        assert!(besselap(MAX_BESSEL_ORDER).is_ok());
+        assert!(besselap(MAX_BESSEL_ORDER + 1).is_err());
+    }
+
+    #[test]
+    fn butterworth_stopband_falls_20_db_per_decade_per_order() {
+        let data = test_signal(200);
+        let range = BodeRange::full(1.0);
+        for order in [2, 3, 4] {
+            let out = butterworth_filter(
+                &data,
+                0.01,
+                order,
+                FilterBand::Lowpass,
+                EdgeHandling::Padded,
+                FilterPhase::Causal,
+            )
+            .unwrap();
+            let (freqs, mag) = bode_mag_db_logspace(&out.b, &out.a, 1.0, &range, -400.0).unwrap();
+            let slope = stopband_slope(&freqs, &mag);
+            let theory = -20.0 * order as f64;
+            assert!(
+                (slope - theory).abs() < 0.1 * theory.abs(),
+                "order {order}: {slope} dB/decade"
+            );
+        }
+        assert!(stopband_slope(&[0.1, 0.2], &[0.0, 0.0]).is_nan());
+    }
+
+    #[test]
+    fn elliptic_lowpass_meets_its_ripple_and_attenuation() {
+        let data = test_signal(200);
+        let (ripple, attenuation, fc) = (1.0, 40.0, 0.1);
+        let range = BodeRange {
+            f_min: 1e-3,
+            f_max: 0.5,
+            n_points: 2000,
+        };
+        for order in [3, 4, 5] {
+            let out = elliptic_filter(
+                &data,
+                2.0 * fc,
+                order,
+                ripple,
+                attenuation,
+                FilterBand::Lowpass,
+                EdgeHandling::Padded,
+                FilterPhase::Causal,
+            )
+            .unwrap();
+            let (freqs, mag) = bode_mag_db_logspace(&out.b, &out.a, 1.0, &range, -400.0).unwrap();
+            // The analog stopband edge sits 1/sqrt(m) above the passband edge;
+            // map it back through the bilinear prewarp
+            let ck1_sq = (10f64.powf(0.1 * ripple) - 1.0) / (10f64.powf(0.1 * attenuation) - 1.0);
+            let ratio = 1.0 / ellipdeg(order, ck1_sq).sqrt();
+            let f_stop = (ratio * (PI * fc).tan()).atan() / PI;
+            // DC is normalized to 0 dB, so an even order ripples between 0 and
+            // +ripple; the attenuation counts down from the passband peak, which
+            // the grid only samples to within a hundredth of a dB
+            let (pass, stop): (Vec<_>, Vec<_>) =
+                freqs.iter().zip(&mag).partition(|(f, _)| **f <= fc);
+            let (lo, hi) = pass
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(l, h), (_, m)| {
+                    (l.min(**m), h.max(**m))
+                });
+            assert!(
+                lo >= -ripple - 1e-6 && hi <= ripple + 1e-6,
+                "order {order}: {lo}..{hi} dB"
+            );
+            assert!(
+                hi - lo <= ripple + 1e-6,
+                "order {order}: ripple {} dB",
+                hi - lo
+            );
+            for (f, m) in stop.iter().filter(|(f, _)| **f >= f_stop * 1.001) {
+                assert!(
+                    **m <= hi - attenuation + 0.01,
+                    "order {order}: {m} dB at {f}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bandstop_follows_the_chosen_design() {
+        let data = test_signal(200);
+        let range = BodeRange {
+            f_min: 1e-3,
+            f_max: 0.5,
+            n_points: 2000,
+        };
+        let (lo, hi) = (0.1, 0.2);
+        let design = |kind| {
+            let out = bandstop_filter(
+                &data,
+                2.0 * lo,
+                2.0 * hi,
+                3,
+                kind,
+                1.0,
+                40.0,
+                EdgeHandling::Padded,
+                FilterPhase::Causal,
+            )
+            .unwrap();
+            bode_mag_db_logspace(&out.b, &out.a, 1.0, &range, -400.0).unwrap()
+        };
+        // Chebyshev II places its cutoffs at the attenuation edges, so the
+        // whole stop band sits below -40 dB where a Butterworth is only -3 dB
+        let (freqs, cheby2) = design(FilterType::CHEBYSHEV2);
+        let (_, butter) = design(FilterType::BUTTERWORTH);
+        assert!(cheby2[0].abs() < 1e-3, "DC {} dB", cheby2[0]);
+        for ((f, c), b) in freqs.iter().zip(&cheby2).zip(&butter) {
+            if (lo..=hi).contains(f) {
+                assert!(*c <= -40.0 + 0.01, "{f}: {c} dB");
+                if (*f - lo).abs() < 1e-3 {
+                    assert!(*b > -4.0, "{f}: {b} dB");
+                }
+            }
+        }
+        // Elliptic cutoffs are passband edges: outside them it stays in its ripple
+        let (freqs, elliptic) = design(FilterType::ELLIPTIC);
+        for (f, m) in freqs.iter().zip(&elliptic) {
+            if !(lo..=hi).contains(f) {
+                assert!((-1.0 - 1e-6..=1.0 + 1e-6).contains(m), "{f}: {m} dB");
+            }
+        }
+    }
+
+    #[test]
+    fn filtered_output_check_catches_a_nearly_flat_high_order_design() {
+        // At a cutoff this low each extra order squeezes the output further
+        // toward its mean; rounding keeps it just off exactly flat
+        let data = test_signal(400);
+        let design = |order| {
+            butterworth_filter(
+                &data,
+                5e-4,
+                order,
+                FilterBand::Lowpass,
+                EdgeHandling::Padded,
+                FilterPhase::ZeroPhase,
+            )
+            .unwrap()
+            .filtered_data
+        };
+        assert!(check_filtered_output(&data, &design(1)).is_ok());
+        let flat = design(5);
+        assert!(series_stats(&flat).variance() > 0.0);
+        assert!(check_filtered_output(&data, &flat).is_err());
+    }
+
+    #[test]
+    fn hann_leaks_less_than_rectangular_for_an_off_bin_tone() {
+        // Halfway between bins 8 and 9, the worst case for leakage
+        let n = 64;
+        let tone: Vec<f64> = (0..n)
+            .map(|i| (2.0 * PI * 8.5 * i as f64 / n as f64).cos())
+            .collect();
+        // Largest bin more than three away from the tone, relative to the peak
+        let leakage = |window| {
+            let mag = rfft_mag_windowed(&tone, window, FftPadding::Off).unwrap();
+            let peak = mag.iter().copied().fold(0.0, f64::max);
+            let far = mag
+                .iter()
+                .enumerate()
+                .filter(|(k, _)| (*k as f64 - 8.5).abs() > 3.0)
+                .map(|(_, m)| *m)
+                .fold(0.0, f64::max);
+            20.0 * (far / peak).log10()
+        };
+        let (rect, hann) = (leakage(Window::Rectangular), leakage(Window::Hann));
+        assert!(hann < rect - 20.0, "hann {hann} dB, rectangular {rect} dB");
+    }
+
+    #[test]
+    fn comb_period_is_bounded() {
+        let data = test_signal(2 * MAX_COMB_PERIOD);
+        for period in [1, MAX_COMB_PERIOD + 1] {
+            assert!(comb_filter(&data, period, 0.5, FilterPhase::Causal).is_err());
+        }
+        let out = comb_filter(&data, MAX_COMB_PERIOD, 0.5, FilterPhase::Causal).unwrap();
+        assert_eq!(out.b.len(), MAX_COMB_PERIOD + 1);
+    }
+}
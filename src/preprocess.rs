@@ -0,0 +1,82 @@
+use crate::stats::series_stats;
+use crate::structures::filters::{GapMode, NormalizeMode};
+use std::f64::consts::PI;
+
+// Pure tone of known frequency/amplitude for calibrating the pipeline end to end
+pub fn generate_sine(freq: f64, amplitude: f64, phase: f64, n: usize, fs: f64) -> Vec<f64> {
+    (0..n)
+        .map(|i| {
+            let t = i as f64 / fs;
+            amplitude * (2.0 * PI * freq * t + phase).sin()
+        })
+        .collect()
+}
+
+// Rescale by the statistics of the finite samples; gaps stay NaN. A constant
+// series has no spread to divide by and maps to zeros instead of NaN.
+pub fn normalize(data: &[f64], mode: NormalizeMode) -> Vec<f64> {
+    let stats = series_stats(data);
+    let (offset, scale) = match mode {
+        NormalizeMode::Off => return data.to_vec(),
+        NormalizeMode::ZScore => (stats.mean, stats.std),
+        NormalizeMode::MinMax => (stats.min, stats.max - stats.min),
+    };
+    if stats.count == 0 {
+        return data.to_vec();
+    }
+    data.iter()
+        .map(|&x| match x {
+            x if !x.is_finite() => x,
+            _ if scale == 0. => 0.,
+            x => (x - offset) / scale,
+        })
+        .collect()
+}
+
+// Remove or repair non-finite samples so the filters and FFTs see a clean
+// series. Clean input comes back unchanged under every mode.
+pub fn sanitize_series(data: &[f64], mode: GapMode) -> Result<Vec<f64>, String> {
+    let bad = data.iter().filter(|x| !x.is_finite()).count();
+    if bad == 0 {
+        return Ok(data.to_vec());
+    }
+    let (Some(first), Some(last)) = (
+        data.iter().position(|x| x.is_finite()),
+        data.iter().rposition(|x| x.is_finite()),
+    ) else {
+        return Err(String::from("Series has no finite samples"));
+    };
+    match mode {
+        GapMode::Fail => Err(format!("Series has {bad} missing or non-finite sample(s)")),
+        GapMode::DropLeadingTrailing => {
+            let inner = &data[first..=last];
+            let interior = inner.iter().filter(|x| !x.is_finite()).count();
+            if interior > 0 {
+                return Err(format!(
+                    "{interior} gap(s) inside the series can't be dropped, interpolate instead"
+                ));
+            }
+            Ok(inner.to_vec())
+        }
+        GapMode::LinearInterpolate => {
+            let mut out = data.to_vec();
+            out[..first].fill(data[first]);
+            out[last + 1..].fill(data[last]);
+            // Interior runs sit between the finite samples at prev and next
+            let mut prev = first;
+            for i in first + 1..=last {
+                if !data[i].is_finite() {
+                    continue;
+                }
+                let (y0, y1) = (data[prev], data[i]);
+                let span = (i - prev) as f64;
+                for (k, x) in out[prev + 1..i].iter_mut().enumerate() {
+                    let t = (k + 1) as f64 / span;
+                    *x = y0 + t * (y1 - y0);
+                }
+                prev = i;
+            }
+            Ok(out)
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::structures::filters::TrendModel;
+
+// A trend fitted over sample index, with its value at every input sample
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendLine {
+    pub model: TrendModel,
+    // (slope, intercept) for Linear, (a, b) for Power and Exponential
+    pub coefficients: (f64, f64),
+    // The fitted curve written out, x in samples
+    pub equation: String,
+    pub values: Vec<f64>,
+}
+
+fn sign(v: f64) -> char {
+    if v < 0.0 { '-' } else { '+' }
+}
+
+// Least-squares (slope, intercept) of y on x, over at least 2 distinct x
+fn least_squares(points: &[(f64, f64)]) -> Result<(f64, f64), String> {
+    let n = points.len();
+    if n < 2 {
+        return Err(format!("Trend fit needs at least 2 usable points, got {n}"));
+    }
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n as f64;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n as f64;
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let slope = sxy / sxx;
+    Ok((slope, mean_y - slope * mean_x))
+}
+
+// Least-squares line y = slope * i + intercept over the finite samples
+pub fn fit_linear(data: &[f64]) -> Result<(f64, f64), String> {
+    let points: Vec<(f64, f64)> = data
+        .iter()
+        .enumerate()
+        .filter(|(_, y)| y.is_finite())
+        .map(|(i, &y)| (i as f64, y))
+        .collect();
+    least_squares(&points)
+}
+
+// y = a (i + 1)^b, fitted as a line in log-log space. The index is shifted by
+// one so the first sample has a logarithm; every finite sample must be positive.
+pub fn fit_power(data: &[f64]) -> Result<(f64, f64), String> {
+    let points = positive_logs(data, |i| ((i + 1) as f64).ln(), "Power")?;
+    let (b, ln_a) = least_squares(&points)?;
+    Ok((ln_a.exp(), b))
+}
+
+// y = a e^(b i), fitted as a line through ln y; every finite sample must be positive
+pub fn fit_exponential(data: &[f64]) -> Result<(f64, f64), String> {
+    let points = positive_logs(data, |i| i as f64, "Exponential")?;
+    let (b, ln_a) = least_squares(&points)?;
+    Ok((ln_a.exp(), b))
+}
+
+fn positive_logs(
+    data: &[f64],
+    x: impl Fn(usize) -> f64,
+    what: &str,
+) -> Result<Vec<(f64, f64)>, String> {
+    let finite = data.iter().enumerate().filter(|(_, y)| y.is_finite());
+    if let Some((i, y)) = finite.clone().find(|(_, y)| **y <= 0.0) {
+        return Err(format!(
+            "{what} trend needs positive data, sample {i} is {y}"
+        ));
+    }
+    Ok(finite.map(|(i, y)| (x(i), y.ln())).collect())
+}
+
+pub fn fit_trend(data: &[f64], model: TrendModel) -> Result<TrendLine, String> {
+    let (p, q) = match model {
+        TrendModel::Linear => fit_linear(data)?,
+        TrendModel::Power => fit_power(data)?,
+        TrendModel::Exponential => fit_exponential(data)?,
+    };
+    let values = (0..data.len())
+        .map(|i| {
+            let x = i as f64;
+            match model {
+                TrendModel::Linear => p * x + q,
+                TrendModel::Power => p * (x + 1.0).powf(q),
+                TrendModel::Exponential => p * (q * x).exp(),
+            }
+        })
+        .collect();
+    let equation = match model {
+        TrendModel::Linear => format!("y = {p:.4}x {} {:.4}", sign(q), q.abs()),
+        TrendModel::Power => format!("y = {p:.4}(x+1)^{q:.4}"),
+        TrendModel::Exponential => format!("y = {p:.4}e^({q:.4}x)"),
+    };
+    Ok(TrendLine {
+        model,
+        coefficients: (p, q),
+        equation,
+        values,
+    })
+}
@@ -0,0 +1,49 @@
+// Running median over an odd window. Unlike the linear filters it removes
+// isolated spikes outright and keeps step edges sharp. The series is
+// reflected about its end samples to fill the window at the edges, and
+// non-finite samples are left out of each neighborhood.
+pub fn median_filter(data: &[f64], window: usize) -> Result<Vec<f64>, String> {
+    if window == 0 || window.is_multiple_of(2) {
+        return Err(format!(
+            "Median window must be odd and positive, got {window}"
+        ));
+    }
+    let n = data.len();
+    let half = (window / 2) as isize;
+    let mut neighborhood = Vec::with_capacity(window);
+    Ok((0..n as isize)
+        .map(|i| {
+            neighborhood.clear();
+            neighborhood.extend(
+                (i - half..=i + half)
+                    .map(|j| data[reflect(j, n)])
+                    .filter(|v| v.is_finite()),
+            );
+            median(&mut neighborhood)
+        })
+        .collect())
+}
+
+// Mirror an out-of-range index back into 0..n without repeating the end
+// sample, bouncing as often as a long window needs
+fn reflect(i: isize, n: usize) -> usize {
+    if n == 1 {
+        return 0;
+    }
+    let period = 2 * (n as isize - 1);
+    let m = i.rem_euclid(period);
+    (if m < n as isize { m } else { period - m }) as usize
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid]
+    } else {
+        0.5 * (values[mid - 1] + values[mid])
+    }
+}
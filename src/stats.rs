@@ -0,0 +1,91 @@
+// Summary statistics over the finite samples of a series. Gaps in imported
+// data show up as NaN, so those are skipped rather than poisoning every field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeriesStats {
+    // Number of finite samples the statistics were computed from
+    pub count: usize,
+    pub mean: f64,
+    // Population standard deviation
+    pub std: f64,
+    pub min: f64,
+    pub max: f64,
+    pub rms: f64,
+}
+
+impl SeriesStats {
+    pub fn variance(&self) -> f64 {
+        self.std * self.std
+    }
+}
+
+// All fields are NaN when there are no finite samples
+pub fn series_stats(data: &[f64]) -> SeriesStats {
+    let finite = data.iter().copied().filter(|v| v.is_finite());
+    let (count, sum, sum_sq, min, max) = finite.fold(
+        (0usize, 0.0, 0.0, f64::INFINITY, f64::NEG_INFINITY),
+        |(n, s, sq, lo, hi), v| (n + 1, s + v, sq + v * v, lo.min(v), hi.max(v)),
+    );
+    if count == 0 {
+        return SeriesStats {
+            count,
+            mean: f64::NAN,
+            std: f64::NAN,
+            min: f64::NAN,
+            max: f64::NAN,
+            rms: f64::NAN,
+        };
+    }
+    let n = count as f64;
+    let mean = sum / n;
+    // Second pass about the mean; the sum-of-squares shortcut loses precision
+    // for series with a large offset
+    let var = data
+        .iter()
+        .filter(|v| v.is_finite())
+        .map(|v| (v - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    SeriesStats {
+        count,
+        mean,
+        std: var.sqrt(),
+        min,
+        max,
+        rms: (sum_sq / n).sqrt(),
+    }
+}
+
+// Percentage of the raw variance that is no longer in the filtered series
+pub fn variance_removed(raw: &SeriesStats, filtered: &SeriesStats) -> Option<f64> {
+    let raw_var = raw.variance();
+    if raw_var.is_nan() || raw_var <= 0.0 || !filtered.variance().is_finite() {
+        return None;
+    }
+    Some(100.0 * (1.0 - filtered.variance() / raw_var))
+}
+
+// raw - filtered, the part of the series the filter took out. Non-finite where
+// either input is.
+pub fn residual(raw: &[f64], filtered: &[f64]) -> Vec<f64> {
+    raw.iter().zip(filtered).map(|(r, f)| r - f).collect()
+}
+
+// 10 log10(P_signal / P_noise) with power as the mean square over finite
+// samples. A zero noise power (nothing was filtered out) gives +inf rather
+// than a division by zero; NaN when either series has no finite samples.
+pub fn snr_db(signal: &[f64], noise: &[f64]) -> f64 {
+    let signal_power = mean_square(signal);
+    let noise_power = mean_square(noise);
+    if signal_power.is_nan() || noise_power.is_nan() {
+        return f64::NAN;
+    }
+    if noise_power == 0.0 {
+        return f64::INFINITY;
+    }
+    10.0 * (signal_power / noise_power).log10()
+}
+
+fn mean_square(data: &[f64]) -> f64 {
+    let rms = series_stats(data).rms;
+    rms * rms
+}
@@ -7,17 +7,15 @@ pub struct Candle {
     pub close: f64,
 }
 
+// Non-overlapping candles of num_per_candle samples; a short trailing chunk
+// still becomes a candle.
 pub fn vec_to_candles(data: &[f64], num_per_candle: usize) -> Result<Vec<Candle>, String> {
     if num_per_candle == 0 {
         return Err("Cannot have a chunk size of zero in candle making function".into());
     }
     let mut candles: Vec<Candle> =
         Vec::with_capacity((data.len() as f64 / num_per_candle as f64).ceil() as usize);
-    let chunks: Vec<&[f64]> = (0..data.len())
-        .step_by(num_per_candle)
-        .map(|i| &data[i..(i + num_per_candle + 1).min(data.len())])
-        .collect();
-    for (i, &chunk) in chunks.iter().enumerate() {
+    for (i, chunk) in data.chunks(num_per_candle).enumerate() {
         candles.push(Candle {
             t: i as f64,
             open: chunk[0],
@@ -33,12 +31,36 @@ pub fn vec_to_candles(data: &[f64], num_per_candle: usize) -> Result<Vec<Candle>
     Ok(candles)
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+// Heikin-Ashi candles: close is the OHLC average, open is the midpoint of the
+// previous HA candle's body (seeded from the first candle's open/close), and
+// the wicks stretch to cover both.
+pub fn to_heikin_ashi(candles: &[Candle]) -> Vec<Candle> {
+    let mut out: Vec<Candle> = Vec::with_capacity(candles.len());
+    for c in candles {
+        let close = (c.open + c.high + c.low + c.close) / 4.0;
+        let open = match out.last() {
+            Some(prev) => (prev.open + prev.close) / 2.0,
+            None => (c.open + c.close) / 2.0,
+        };
+        out.push(Candle {
+            t: c.t,
+            open,
+            close,
+            high: c.high.max(open).max(close),
+            low: c.low.min(open).min(close),
+        });
+    }
+    out
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CandleLengths {
     #[default]
     Weekly,
     Monthly,
     Yearly,
+    // Arbitrary number of samples per candle
+    Custom(usize),
 }
 
 impl From<CandleLengths> for usize {
@@ -47,17 +69,112 @@ impl From<CandleLengths> for usize {
             CandleLengths::Weekly => 7,
             CandleLengths::Monthly => 30,
             CandleLengths::Yearly => 365,
+            CandleLengths::Custom(n) => n,
         }
     }
 }
 
 impl std::fmt::Display for CandleLengths {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            CandleLengths::Weekly => "Weekly",
-            CandleLengths::Monthly => "Monthly",
-            CandleLengths::Yearly => "Yearly",
-        };
-        write!(f, "{s}")
+        match self {
+            CandleLengths::Weekly => write!(f, "Weekly"),
+            CandleLengths::Monthly => write!(f, "Monthly"),
+            CandleLengths::Yearly => write!(f, "Yearly"),
+            CandleLengths::Custom(n) => write!(f, "Custom ({n})"),
+        }
+    }
+}
+
+// Up/down color pairs for the candle view
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandlePalette {
+    #[default]
+    GreenRed,
+    // Blue/orange, distinguishable with red-green color blindness
+    BlueOrange,
+}
+
+impl CandlePalette {
+    pub const ALL: [CandlePalette; 2] = [CandlePalette::GreenRed, CandlePalette::BlueOrange];
+}
+
+impl std::fmt::Display for CandlePalette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CandlePalette::GreenRed => write!(f, "Green/Red"),
+            CandlePalette::BlueOrange => write!(f, "Blue/Orange (colorblind)"),
+        }
+    }
+}
+
+// How the candle view turns each chunk into a candle
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandleStyle {
+    #[default]
+    Standard,
+    HeikinAshi,
+}
+
+impl CandleStyle {
+    pub const ALL: [CandleStyle; 2] = [CandleStyle::Standard, CandleStyle::HeikinAshi];
+}
+
+impl std::fmt::Display for CandleStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CandleStyle::Standard => write!(f, "Standard"),
+            CandleStyle::HeikinAshi => write!(f, "Heikin-Ashi"),
+        }
+    }
+}
+
+// Which series the candle view aggregates
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandleSource {
+    #[default]
+    Raw,
+    Filtered,
+}
+
+impl CandleSource {
+    pub const ALL: [CandleSource; 2] = [CandleSource::Raw, CandleSource::Filtered];
+}
+
+impl std::fmt::Display for CandleSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CandleSource::Raw => write!(f, "Raw"),
+            CandleSource::Filtered => write!(f, "Filtered"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_partial_chunk_becomes_a_candle() {
+        let candles = vec_to_candles(&[1.0, 3.0, 2.0, 5.0, 4.0], 2).unwrap();
+        assert_eq!(candles.len(), 3);
+        let last = candles[2];
+        assert_eq!(
+            (last.t, last.open, last.high, last.low, last.close),
+            (2.0, 4.0, 4.0, 4.0, 4.0)
+        );
+        assert!(vec_to_candles(&[1.0], 0).is_err());
+    }
+
+    #[test]
+    fn candles_do_not_overlap() {
+        let candles = vec_to_candles(&[0.0, 1.0, 2.0, 3.0], 2).unwrap();
+        let ohlc: Vec<_> = candles
+            .iter()
+            .map(|c| (c.t, c.open, c.high, c.low, c.close))
+            .collect();
+        assert_eq!(
+            ohlc,
+            vec![(0.0, 0.0, 1.0, 0.0, 1.0), (1.0, 2.0, 3.0, 2.0, 3.0)]
+        );
     }
 }
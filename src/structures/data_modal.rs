@@ -1,6 +1,6 @@
 use serde_json;
 
-use crate::{DEFAULT_FILENAME, is_file_empty};
+use crate::{DEFAULT_FILENAME, is_file_empty, read_series_csv};
 
 pub struct DataModalState {
     pub show_modal: bool,
@@ -9,6 +9,8 @@ pub struct DataModalState {
     pub selected_datetime: chrono::NaiveDate,
     pub date_status: String,
     pub file: Option<std::path::PathBuf>,
+    // Set while "Clear all" waits for confirmation
+    pub confirm_clear: bool,
 }
 
 impl DataModalState {
@@ -50,6 +52,7 @@ impl DataModalState {
             selected_datetime: dt,
             date_status: dt_str,
             file: ret_f,
+            confirm_clear: false,
         }
     }
 
@@ -62,6 +65,61 @@ impl DataModalState {
         Ok(format!("Weight on {}: {}", self.selected_datetime, entry))
     }
 
+    // Remove the selected date's entry, true when there was one
+    pub fn delete_entry(&mut self) -> bool {
+        let removed = self.data.remove(&self.selected_datetime).is_some();
+        if removed {
+            self.weight_entry.clear();
+        }
+        removed
+    }
+
+    // Drop every entry, returning how many were removed
+    pub fn clear_entries(&mut self) -> usize {
+        let n = self.data.len();
+        self.data.clear();
+        self.weight_entry.clear();
+        self.confirm_clear = false;
+        n
+    }
+
+    // Merge dated entries from a "date,value" CSV or a { "date": value } JSON
+    // map like the log file itself, overwriting dates already logged. Gaps are
+    // skipped. Returns how many entries were merged.
+    pub fn load_file(&mut self, path: &std::path::Path) -> Result<usize, String> {
+        let is_json = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        let entries: Vec<(chrono::NaiveDate, f64)> = if is_json {
+            let file = std::fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            let map: std::collections::HashMap<chrono::NaiveDate, f64> =
+                serde_json::from_reader(std::io::BufReader::new(file))
+                    .map_err(|e| format!("{} is not a date to value map: {e}", path.display()))?;
+            map.into_iter().collect()
+        } else {
+            match read_series_csv(path)? {
+                (values, Some(dates)) => dates.into_iter().zip(values).collect(),
+                (_, None) => {
+                    return Err(format!(
+                        "{} needs a date on every row to be logged",
+                        path.display()
+                    ));
+                }
+            }
+        };
+        let mut merged = 0;
+        for (d, v) in entries.into_iter().filter(|(_, v)| v.is_finite()) {
+            self.data.insert(d, v);
+            merged += 1;
+        }
+        if merged == 0 {
+            return Err(format!("No values found in {}", path.display()));
+        }
+        // Refresh the entry box in case the selected date was overwritten
+        self.switch_date_state(self.selected_datetime);
+        Ok(merged)
+    }
+
     pub fn switch_date_state(&mut self, date_time: chrono::NaiveDate) {
         self.selected_datetime = date_time;
         self.date_status = format!("Current date: {}", self.selected_datetime);
@@ -72,9 +130,17 @@ impl DataModalState {
     }
 
     pub fn get_vals_sorted_by_date(&self) -> Vec<f64> {
-        let mut sorted_vec: Vec<(&chrono::NaiveDate, &f64)> = self.data.iter().collect();
-        sorted_vec.sort_by_key(|&k| k.0);
-        sorted_vec.iter().map(|&(_, &val)| val).collect()
+        self.get_entries_sorted_by_date()
+            .into_iter()
+            .map(|(_, val)| val)
+            .collect()
+    }
+
+    pub fn get_entries_sorted_by_date(&self) -> Vec<(chrono::NaiveDate, f64)> {
+        let mut sorted_vec: Vec<(chrono::NaiveDate, f64)> =
+            self.data.iter().map(|(&d, &v)| (d, v)).collect();
+        sorted_vec.sort_by_key(|&(d, _)| d);
+        sorted_vec
     }
 }
 
@@ -0,0 +1,39 @@
+// A labelled series kept alongside the main data for side-by-side comparison
+#[derive(Debug, Clone)]
+pub struct NamedSeries {
+    pub name: String,
+    pub data: Vec<f64>,
+    pub filtered: Option<Vec<f64>>,
+    pub spectrum: Option<Vec<f64>>,
+}
+
+impl NamedSeries {
+    pub fn new(name: String, data: Vec<f64>) -> Self {
+        Self {
+            name,
+            data,
+            filtered: None,
+            spectrum: None,
+        }
+    }
+
+    // Filtered output when available, otherwise the raw samples
+    pub fn display_data(&self) -> &[f64] {
+        self.filtered.as_deref().unwrap_or(&self.data)
+    }
+
+    pub fn clear_output(&mut self) {
+        self.filtered = None;
+        self.spectrum = None;
+    }
+}
+
+// A series loaded from JSON with its sampling metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesData {
+    // Samples per unit of time
+    pub fs: f64,
+    // What the time axis is measured in, when the file says
+    pub unit: Option<String>,
+    pub values: Vec<f64>,
+}
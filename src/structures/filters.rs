@@ -1,17 +1,80 @@
+#[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FilterType {
     #[default]
     BUTTERWORTH,
     CHEBYSHEV1,
     CHEBYSHEV2,
+    ELLIPTIC,
+    BESSEL,
+    // N-point boxcar, FIR
+    MOVING_AVERAGE,
+    // Running median, nonlinear
+    MEDIAN,
+    // User-supplied b/a, analyzed as given
+    CUSTOM,
+    // Flat magnitude, phase shaped by poles at the cutoff angle
+    ALL_PASS,
+    // Notches at every multiple of 1/window, sized by the window
+    COMB,
+    // First difference, FIR
+    DIFFERENTIATOR,
+    // Running sum, pole on z = 1
+    INTEGRATOR,
 }
 
 impl FilterType {
-    pub const ALL: [FilterType; 3] = [
+    pub const ALL: [FilterType; 12] = [
         FilterType::BUTTERWORTH,
         FilterType::CHEBYSHEV1,
         FilterType::CHEBYSHEV2,
+        FilterType::ELLIPTIC,
+        FilterType::BESSEL,
+        FilterType::MOVING_AVERAGE,
+        FilterType::MEDIAN,
+        FilterType::CUSTOM,
+        FilterType::ALL_PASS,
+        FilterType::COMB,
+        FilterType::DIFFERENTIATOR,
+        FilterType::INTEGRATOR,
     ];
+
+    // Whether the design consumes the passband ripple / stopband attenuation inputs
+    pub fn uses_ripple(&self) -> bool {
+        matches!(self, FilterType::CHEBYSHEV1 | FilterType::ELLIPTIC)
+    }
+    pub fn uses_attenuation(&self) -> bool {
+        matches!(self, FilterType::CHEBYSHEV2 | FilterType::ELLIPTIC)
+    }
+    pub fn uses_pole_radius(&self) -> bool {
+        *self == FilterType::ALL_PASS
+    }
+    pub fn uses_notch_depth(&self) -> bool {
+        *self == FilterType::COMB
+    }
+    // The moving average, median and comb take a window length instead of an
+    // order and cutoff
+    pub fn uses_window(&self) -> bool {
+        matches!(
+            self,
+            FilterType::MOVING_AVERAGE | FilterType::MEDIAN | FilterType::COMB
+        )
+    }
+    // Parameterless difference-equation operators
+    pub fn is_operator(&self) -> bool {
+        matches!(self, FilterType::DIFFERENTIATOR | FilterType::INTEGRATOR)
+    }
+    // Whether the cutoff and order inputs feed a design step
+    pub fn is_designed(&self) -> bool {
+        !self.uses_window() && !self.is_operator() && *self != FilterType::CUSTOM
+    }
+    // Highest order the design accepts
+    pub fn max_order(&self) -> usize {
+        match self {
+            FilterType::BESSEL => crate::math::MAX_BESSEL_ORDER,
+            _ => crate::MAX_ORDER,
+        }
+    }
 }
 
 impl std::fmt::Display for FilterType {
@@ -20,7 +83,470 @@ impl std::fmt::Display for FilterType {
             FilterType::BUTTERWORTH => "Butterworth",
             FilterType::CHEBYSHEV1 => "Chebyshev I",
             FilterType::CHEBYSHEV2 => "Chebyshev II",
+            FilterType::ELLIPTIC => "Elliptic",
+            FilterType::BESSEL => "Bessel",
+            FilterType::MOVING_AVERAGE => "Moving average",
+            FilterType::MEDIAN => "Median",
+            FilterType::CUSTOM => "Custom b/a",
+            FilterType::ALL_PASS => "All-pass",
+            FilterType::COMB => "Comb",
+            FilterType::DIFFERENTIATOR => "Differentiator",
+            FilterType::INTEGRATOR => "Integrator",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeHandling {
+    #[default]
+    Padded,
+    Gustafsson,
+}
+
+impl EdgeHandling {
+    pub const ALL: [EdgeHandling; 2] = [EdgeHandling::Padded, EdgeHandling::Gustafsson];
+}
+
+impl std::fmt::Display for EdgeHandling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            EdgeHandling::Padded => "Odd padding",
+            EdgeHandling::Gustafsson => "Gustafsson",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// Zero-phase runs filtfilt; causal is a single forward pass that delays the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterPhase {
+    #[default]
+    ZeroPhase,
+    Causal,
+}
+
+impl FilterPhase {
+    pub const ALL: [FilterPhase; 2] = [FilterPhase::ZeroPhase, FilterPhase::Causal];
+}
+
+impl std::fmt::Display for FilterPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            FilterPhase::ZeroPhase => "Zero-phase",
+            FilterPhase::Causal => "Causal",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// Taper applied to samples before the spectrum FFT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Window {
+    #[default]
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    pub const ALL: [Window; 4] = [
+        Window::Rectangular,
+        Window::Hann,
+        Window::Hamming,
+        Window::Blackman,
+    ];
+}
+
+impl std::fmt::Display for Window {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Window::Rectangular => "Rectangular",
+            Window::Hann => "Hann",
+            Window::Hamming => "Hamming",
+            Window::Blackman => "Blackman",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// Y axis of the spectrum plot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpectrumScale {
+    #[default]
+    Linear,
+    Decibel,
+}
+
+impl SpectrumScale {
+    pub const ALL: [SpectrumScale; 2] = [SpectrumScale::Linear, SpectrumScale::Decibel];
+}
+
+impl std::fmt::Display for SpectrumScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SpectrumScale::Linear => "Linear",
+            SpectrumScale::Decibel => "dB",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// What the spectrum plot shows: one FFT of the whole record, Welch's average
+// over overlapping segments, or those segments side by side in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpectrumMode {
+    #[default]
+    Fft,
+    Welch,
+    // Time x frequency heatmap of short FFTs
+    Spectrogram,
+}
+
+impl SpectrumMode {
+    pub const ALL: [SpectrumMode; 3] = [
+        SpectrumMode::Fft,
+        SpectrumMode::Welch,
+        SpectrumMode::Spectrogram,
+    ];
+}
+
+impl std::fmt::Display for SpectrumMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SpectrumMode::Fft => "FFT",
+            SpectrumMode::Welch => "Welch PSD",
+            SpectrumMode::Spectrogram => "Spectrogram",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// X axis of the Bode plots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodeXScale {
+    #[default]
+    Log,
+    Linear,
+}
+
+impl BodeXScale {
+    pub const ALL: [BodeXScale; 2] = [BodeXScale::Log, BodeXScale::Linear];
+}
+
+impl std::fmt::Display for BodeXScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            BodeXScale::Log => "Log",
+            BodeXScale::Linear => "Linear",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// What to do with NaN/infinite samples before filtering and FFTs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GapMode {
+    // Straight line across interior gaps, nearest value held at the ends
+    #[default]
+    LinearInterpolate,
+    // Trim gaps off the ends; interior gaps are an error
+    DropLeadingTrailing,
+    Fail,
+}
+
+impl GapMode {
+    pub const ALL: [GapMode; 3] = [
+        GapMode::LinearInterpolate,
+        GapMode::DropLeadingTrailing,
+        GapMode::Fail,
+    ];
+}
+
+impl std::fmt::Display for GapMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            GapMode::LinearInterpolate => "Interpolate",
+            GapMode::DropLeadingTrailing => "Drop leading/trailing",
+            GapMode::Fail => "Fail",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// Rescaling applied to loaded data before anything else sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalizeMode {
+    #[default]
+    Off,
+    // Zero mean, unit standard deviation
+    ZScore,
+    // Onto [0, 1]
+    MinMax,
+}
+
+impl NormalizeMode {
+    pub const ALL: [NormalizeMode; 3] = [
+        NormalizeMode::Off,
+        NormalizeMode::ZScore,
+        NormalizeMode::MinMax,
+    ];
+}
+
+impl std::fmt::Display for NormalizeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            NormalizeMode::Off => "Off",
+            NormalizeMode::ZScore => "Z-score",
+            NormalizeMode::MinMax => "Min-max",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// Trend removed from a series before its spectrum is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DetrendMode {
+    #[default]
+    Off,
+    // Subtract the mean
+    Constant,
+    // Subtract the least-squares line over sample index
+    Linear,
+}
+
+impl DetrendMode {
+    pub const ALL: [DetrendMode; 3] =
+        [DetrendMode::Off, DetrendMode::Constant, DetrendMode::Linear];
+}
+
+impl std::fmt::Display for DetrendMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            DetrendMode::Off => "Off",
+            DetrendMode::Constant => "Constant",
+            DetrendMode::Linear => "Linear",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// Curve fitted over sample index for the time-series trend line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TrendModel {
+    #[default]
+    Linear,
+    // a (x + 1)^b
+    Power,
+    // a e^(b x)
+    Exponential,
+}
+
+impl TrendModel {
+    pub const ALL: [TrendModel; 3] = [
+        TrendModel::Linear,
+        TrendModel::Power,
+        TrendModel::Exponential,
+    ];
+}
+
+impl std::fmt::Display for TrendModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TrendModel::Linear => "Linear",
+            TrendModel::Power => "Power",
+            TrendModel::Exponential => "Exponential",
         };
         write!(f, "{s}")
     }
 }
+
+// Zero padding applied to the spectrum FFT input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FftPadding {
+    #[default]
+    Off,
+    NextPow2,
+    // Pad up to this many samples; never truncates
+    Length(usize),
+}
+
+impl std::fmt::Display for FftPadding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FftPadding::Off => write!(f, "Off"),
+            FftPadding::NextPow2 => write!(f, "Next power of two"),
+            FftPadding::Length(n) => write!(f, "Length ({n})"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterBand {
+    #[default]
+    Lowpass,
+    Highpass,
+    Bandstop,
+    // A highpass at the lower edge cascaded with a lowpass at the upper,
+    // each with its own order
+    Bandpass,
+}
+
+impl FilterBand {
+    pub const ALL: [FilterBand; 4] = [
+        FilterBand::Lowpass,
+        FilterBand::Highpass,
+        FilterBand::Bandstop,
+        FilterBand::Bandpass,
+    ];
+
+    // Whether the band has a second edge from the second cutoff
+    pub fn uses_second_cutoff(&self) -> bool {
+        matches!(self, FilterBand::Bandstop | FilterBand::Bandpass)
+    }
+}
+
+impl std::fmt::Display for FilterBand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            FilterBand::Lowpass => "Lowpass",
+            FilterBand::Highpass => "Highpass",
+            FilterBand::Bandstop => "Bandstop",
+            FilterBand::Bandpass => "Bandpass",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// One filter of a cascade, frozen from the settings it was calculated with.
+// Cutoffs are normalized to Nyquist.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterStage {
+    pub filter: FilterType,
+    pub band: FilterBand,
+    pub cutoff_freq: f64,
+    pub second_cutoff_freq: f64,
+    pub order: usize,
+    // Order of a bandpass's lowpass stage; `order` sizes its highpass
+    pub lowpass_order: usize,
+    pub ripple: f64,
+    pub attenuation: f64,
+    pub window: usize,
+    // Radius of the all-pass poles
+    pub pole_radius: f64,
+    // Fraction of each comb notch removed
+    pub notch_depth: f64,
+}
+
+impl std::fmt::Display for FilterStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.filter.is_operator() {
+            return write!(f, "{}", self.filter);
+        }
+        if self.filter.uses_notch_depth() {
+            return write!(
+                f,
+                "{}, window {}, depth {:.3}",
+                self.filter, self.window, self.notch_depth
+            );
+        }
+        if self.filter.uses_window() {
+            return write!(f, "{}, window {}", self.filter, self.window);
+        }
+        // The band setting doesn't apply to an all-pass
+        if self.filter.uses_pole_radius() {
+            return write!(
+                f,
+                "{}, order {}, Wn {:.4}, r {:.3}",
+                self.filter, self.order, self.cutoff_freq, self.pole_radius
+            );
+        }
+        write!(
+            f,
+            "{} {}, order {}, Wn {:.4}",
+            self.filter, self.band, self.order, self.cutoff_freq
+        )?;
+        if self.band.uses_second_cutoff() {
+            write!(f, "..{:.4}", self.second_cutoff_freq)?;
+        }
+        if self.band == FilterBand::Bandpass {
+            write!(f, ", lowpass order {}", self.lowpass_order)?;
+        }
+        Ok(())
+    }
+}
+
+// Stable name an option is saved under in session files. Display labels are
+// for the UI and may be reworded; these must never change.
+pub trait SessionKey {
+    fn key(&self) -> &'static str;
+}
+
+impl SessionKey for FilterType {
+    fn key(&self) -> &'static str {
+        match self {
+            FilterType::BUTTERWORTH => "butterworth",
+            FilterType::CHEBYSHEV1 => "chebyshev1",
+            FilterType::CHEBYSHEV2 => "chebyshev2",
+            FilterType::ELLIPTIC => "elliptic",
+            FilterType::BESSEL => "bessel",
+            FilterType::MOVING_AVERAGE => "moving_average",
+            FilterType::MEDIAN => "median",
+            FilterType::CUSTOM => "custom",
+            FilterType::ALL_PASS => "all_pass",
+            FilterType::COMB => "comb",
+            FilterType::DIFFERENTIATOR => "differentiator",
+            FilterType::INTEGRATOR => "integrator",
+        }
+    }
+}
+
+impl SessionKey for FilterBand {
+    fn key(&self) -> &'static str {
+        match self {
+            FilterBand::Lowpass => "lowpass",
+            FilterBand::Highpass => "highpass",
+            FilterBand::Bandstop => "bandstop",
+            FilterBand::Bandpass => "bandpass",
+        }
+    }
+}
+
+impl SessionKey for EdgeHandling {
+    fn key(&self) -> &'static str {
+        match self {
+            EdgeHandling::Padded => "padded",
+            EdgeHandling::Gustafsson => "gustafsson",
+        }
+    }
+}
+
+impl SessionKey for FilterPhase {
+    fn key(&self) -> &'static str {
+        match self {
+            FilterPhase::ZeroPhase => "zero_phase",
+            FilterPhase::Causal => "causal",
+        }
+    }
+}
+
+impl SessionKey for NormalizeMode {
+    fn key(&self) -> &'static str {
+        match self {
+            NormalizeMode::Off => "off",
+            NormalizeMode::ZScore => "z_score",
+            NormalizeMode::MinMax => "min_max",
+        }
+    }
+}
+
+impl SessionKey for GapMode {
+    fn key(&self) -> &'static str {
+        match self {
+            GapMode::LinearInterpolate => "interpolate",
+            GapMode::DropLeadingTrailing => "drop_leading_trailing",
+            GapMode::Fail => "fail",
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod candle;
 pub mod data_modal;
+pub mod dataset;
 pub mod filters;
+pub mod session;
@@ -0,0 +1,115 @@
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+
+use crate::is_file_empty;
+
+pub const DEFAULT_WINDOW_WIDTH: f32 = 1024.;
+pub const DEFAULT_WINDOW_HEIGHT: f32 = 768.;
+
+// Bits of UI state that survive between runs
+pub struct SessionState {
+    pub last_load_dir: Option<PathBuf>,
+    pub last_save_dir: Option<PathBuf>,
+    pub window_width: f32,
+    pub window_height: f32,
+    pub file: Option<PathBuf>,
+}
+
+impl SessionState {
+    pub fn new(f: Option<PathBuf>) -> Self {
+        let mut state = Self {
+            last_load_dir: None,
+            last_save_dir: None,
+            window_width: DEFAULT_WINDOW_WIDTH,
+            window_height: DEFAULT_WINDOW_HEIGHT,
+            file: None,
+        };
+        let Some(file) = f else {
+            return state;
+        };
+        if !is_file_empty(&file)
+            && let Ok(ifile) = std::fs::File::open(&file)
+            && let Ok(v) = serde_json::from_reader::<_, Value>(ifile)
+        {
+            let dir = |key: &str| v.get(key).and_then(Value::as_str).map(PathBuf::from);
+            let size = |key: &str| {
+                v.get(key)
+                    .and_then(Value::as_f64)
+                    .filter(|s| s.is_finite() && *s > 0.)
+                    .map(|s| s as f32)
+            };
+            state.last_load_dir = dir("last_load_dir");
+            state.last_save_dir = dir("last_save_dir");
+            state.window_width = size("window_width").unwrap_or(DEFAULT_WINDOW_WIDTH);
+            state.window_height = size("window_height").unwrap_or(DEFAULT_WINDOW_HEIGHT);
+        }
+        state.file = Some(file);
+        state
+    }
+
+    pub fn window_size(&self) -> iced::Size {
+        iced::Size::new(self.window_width, self.window_height)
+    }
+
+    pub fn set_window_size(&mut self, size: iced::Size) {
+        self.window_width = size.width;
+        self.window_height = size.height;
+    }
+
+    // Directory a load dialog should open in
+    pub fn load_dir(&self) -> PathBuf {
+        existing_dir_or_home(self.last_load_dir.as_deref())
+    }
+
+    // Directory a save dialog should open in
+    pub fn save_dir(&self) -> PathBuf {
+        existing_dir_or_home(self.last_save_dir.as_deref())
+    }
+
+    // Remember the directory of a file picked in a load dialog
+    pub fn record_load(&mut self, picked: &Path) {
+        self.last_load_dir = picked.parent().map(Path::to_path_buf);
+    }
+
+    // Remember the directory of a file picked in a save dialog
+    pub fn record_save(&mut self, picked: &Path) {
+        self.last_save_dir = picked.parent().map(Path::to_path_buf);
+    }
+}
+
+fn existing_dir_or_home(dir: Option<&Path>) -> PathBuf {
+    match dir {
+        Some(d) if d.is_dir() => d.to_path_buf(),
+        _ => directories::BaseDirs::new()
+            .map(|b| b.home_dir().to_path_buf())
+            .unwrap_or_default(),
+    }
+}
+
+impl Default for SessionState {
+    fn default() -> Self {
+        SessionState::new(None)
+    }
+}
+
+impl Drop for SessionState {
+    fn drop(&mut self) {
+        if let Some(ofile) = self.file.as_deref() {
+            let file = match std::fs::File::create(ofile) {
+                Ok(f) => f,
+                Err(_) => {
+                    return;
+                }
+            };
+            let _ = serde_json::to_writer(
+                file,
+                &json!({
+                    "last_load_dir": self.last_load_dir,
+                    "last_save_dir": self.last_save_dir,
+                    "window_width": self.window_width,
+                    "window_height": self.window_height,
+                }),
+            );
+        }
+    }
+}
@@ -1,14 +1,72 @@
 use crate::*;
 use iced::widget::canvas;
-use iced::widget::canvas::{Cache, Fill, Frame, Geometry, Path, Stroke, Text};
+use iced::widget::canvas::{Cache, Fill, Frame, Geometry, LineDash, Path, Stroke, Text};
 use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
 
 pub struct BodeView<'a> {
     pub freqs: Option<&'a [f64]>,
     /// Magnitude in dB for each frequency.
     pub mag_db: Option<&'a [f64]>,
+    /// Optional family of (center, freqs, mag_db) responses overlaid with a gradient.
+    pub sweep: Option<&'a [math::SweepStep]>,
+    /// Annotate the -3 dB point and the fitted stopband rolloff.
+    pub explain: bool,
+    /// Stopband slope in dB/decade the design predicts, when it has a simple
+    /// one (-20 per order for an all-pole lowpass); compared against the fit.
+    pub theory_slope: Option<f64>,
+    /// Requested cutoff in x-axis units; when set, the measured -3 dB point is marked against it.
+    pub cutoff: Option<f64>,
+    /// Decades, or evenly spaced frequencies for zooming in on a narrow band.
+    pub x_scale: structures::filters::BodeXScale,
     pub cache: &'a Cache,
-    pub x_label: &'a str,
+    /// Time unit of the series, for the cycles-per-unit frequency labels.
+    pub time_unit: &'a str,
+    /// Unit printed on the y tick labels ("dB", or degrees for phase).
+    pub y_unit: &'a str,
+    /// Shown in place of the plot when there is no response to draw.
+    pub empty_text: &'a str,
+    /// Pinned (freqs, values) from an earlier design, drawn dashed on the same axes.
+    pub reference: Option<(&'a [f64], &'a [f64])>,
+    /// Legend text naming the pinned design.
+    pub reference_label: &'a str,
+    /// Pass/stop band spec to shade and check the magnitude curve against.
+    pub mask: Option<BodeMask>,
+}
+
+/// Design spec for a lowpass or highpass magnitude response, frequencies in x-axis units.
+#[derive(Debug, Clone, Copy)]
+pub struct BodeMask {
+    pub pass_edge: f64,
+    pub stop_edge: f64,
+    /// Largest allowed passband deviation from 0 dB.
+    pub ripple: f64,
+    /// Least allowed stopband attenuation, in dB below 0.
+    pub attenuation: f64,
+    pub highpass: bool,
+}
+
+impl BodeMask {
+    fn in_passband(&self, f: f64) -> bool {
+        if self.highpass {
+            f >= self.pass_edge
+        } else {
+            f <= self.pass_edge
+        }
+    }
+
+    fn in_stopband(&self, f: f64) -> bool {
+        if self.highpass {
+            f <= self.stop_edge
+        } else {
+            f >= self.stop_edge
+        }
+    }
+
+    /// Whether a magnitude of `db` at `f` breaks the spec; the transition band never does.
+    pub fn violates(&self, f: f64, db: f64) -> bool {
+        (self.in_passband(f) && db.abs() > self.ripple)
+            || (self.in_stopband(f) && db > -self.attenuation)
+    }
 }
 
 impl<'a> canvas::Program<Message> for BodeView<'a> {
@@ -80,45 +138,56 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
                 let plot_h = (bottom - top).max(1.0);
 
                 // Validate data
-                let (freqs, mag_db) = match (self.freqs, self.mag_db) {
-                    (Some(f), Some(m)) if f.len() == m.len() && f.len() >= 2 => (f, m),
-                    _ => {
-                        let size = 14.0;
-                        let x_bias = 1.5 * size;
-                        frame.fill_text(Text {
-                            content: "No data loaded".into(),
-                            position: Point::new(
-                                ((left + right) * 0.5) - x_bias,
-                                (top + bottom) * 0.5,
-                            ),
-                            color: label_color(),
-                            size: size.into(),
-                            align_x: iced::widget::text::Alignment::Center,
-                            align_y: iced::alignment::Vertical::Center,
-                            ..Text::default()
-                        });
-                        return;
-                    }
+                let main = match (self.freqs, self.mag_db) {
+                    (Some(f), Some(m)) if f.len() == m.len() && f.len() >= 2 => Some((f, m)),
+                    _ => None,
                 };
+                let sweep = self.sweep.unwrap_or(&[]);
+                let reference = self
+                    .reference
+                    .filter(|(f, m)| f.len() == m.len() && f.len() >= 2);
+                if main.is_none() && sweep.is_empty() && reference.is_none() {
+                    let size = 14.0;
+                    let x_bias = 1.5 * size;
+                    frame.fill_text(Text {
+                        content: self.empty_text.into(),
+                        position: Point::new(((left + right) * 0.5) - x_bias, (top + bottom) * 0.5),
+                        color: label_color(),
+                        size: size.into(),
+                        align_x: iced::widget::text::Alignment::Center,
+                        align_y: iced::alignment::Vertical::Center,
+                        ..Text::default()
+                    });
+                    return;
+                }
 
                 // Find finite ranges; for log-x strictly positive frequencies
+                let log_x = self.x_scale == structures::filters::BodeXScale::Log;
+                let usable = |f: f64| f.is_finite() && (f > 0.0 || !log_x);
                 let mut f_min = f64::INFINITY;
                 let mut f_max = f64::NEG_INFINITY;
                 let mut y_min = f64::INFINITY;
                 let mut y_max = f64::NEG_INFINITY;
 
-                for i in 0..freqs.len() {
-                    let f = freqs[i];
-                    let y = mag_db[i];
-                    if f.is_finite() && y.is_finite() && f > 0.0 {
-                        f_min = f_min.min(f);
-                        f_max = f_max.max(f);
-                        y_min = y_min.min(y);
-                        y_max = y_max.max(y);
+                let curves = main
+                    .into_iter()
+                    .chain(reference)
+                    .chain(sweep.iter().map(|(_, f, m)| (f.as_slice(), m.as_slice())));
+                for (freqs, mag_db) in curves {
+                    for (&f, &y) in freqs.iter().zip(mag_db) {
+                        if usable(f) && y.is_finite() {
+                            f_min = f_min.min(f);
+                            f_max = f_max.max(f);
+                            y_min = y_min.min(y);
+                            y_max = y_max.max(y);
+                        }
                     }
                 }
 
-                if !f_min.is_finite() || !f_max.is_finite() || f_min <= 0.0 || f_max <= 0.0 {
+                if !f_min.is_finite() || !f_max.is_finite() {
+                    return;
+                }
+                if log_x && (f_min <= 0.0 || f_max <= 0.0) {
                     frame.fill_text(Text {
                         content: "Bode X requires positive frequencies".into(),
                         position: Point::new(left, top),
@@ -132,6 +201,12 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
                 if !y_min.is_finite() || !y_max.is_finite() {
                     return;
                 }
+                // Keep the limit lines on screen
+                let mask = self.mask.filter(|_| main.is_some());
+                if let Some(mask) = mask {
+                    y_min = y_min.min(-mask.attenuation);
+                    y_max = y_max.max(0.0);
+                }
 
                 if (y_max - y_min).abs() < 1e-12 {
                     let mid = 0.5 * (y_max + y_min);
@@ -146,12 +221,30 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
                 let log_f_min = f_min.log10();
                 let log_f_max = f_max.log10();
                 let log_span = (log_f_max - log_f_min).max(1e-12);
+                let lin_span = (f_max - f_min).max(1e-12);
 
                 let map_x = |f: f64| -> f32 {
-                    let t = ((f.log10() - log_f_min) / log_span) as f32;
+                    let t = if log_x {
+                        ((f.log10() - log_f_min) / log_span) as f32
+                    } else {
+                        ((f - f_min) / lin_span) as f32
+                    };
                     left + t.clamp(0.0, 1.0) * plot_w
                 };
 
+                // Decades on a log axis, five evenly spaced frequencies on a linear one
+                let x_ticks: Vec<(f64, String)> = if log_x {
+                    (log_f_min.floor() as i32..=log_f_max.ceil() as i32)
+                        .map(|d| (10f64.powi(d), format!("1e{}", d)))
+                        .filter(|(f, _)| *f >= f_min && *f <= f_max)
+                        .collect()
+                } else {
+                    (0..=4)
+                        .map(|k| f_min + lin_span * k as f64 / 4.0)
+                        .map(|f| (f, fmt_tick(f)))
+                        .collect()
+                };
+
                 let map_y = |y: f64| -> f32 {
                     let t = ((y - y_min) / (y_max - y_min)) as f32;
                     bottom - t * plot_h
@@ -175,17 +268,12 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
                 }
 
                 // Vertical grid lines
-                let decade_start = log_f_min.floor() as i32;
-                let decade_end = log_f_max.ceil() as i32;
-                for d in decade_start..=decade_end {
-                    let f = 10f64.powi(d);
-                    if f >= f_min && f <= f_max {
-                        let xx = map_x(f);
-                        frame.stroke(
-                            &Path::line(Point::new(xx, top), Point::new(xx, bottom)),
-                            grid,
-                        );
-                    }
+                for &(f, _) in &x_ticks {
+                    let xx = map_x(f);
+                    frame.stroke(
+                        &Path::line(Point::new(xx, top), Point::new(xx, bottom)),
+                        grid,
+                    );
                 }
 
                 frame.stroke(
@@ -197,12 +285,69 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
                     },
                 );
 
-                // Y tick labels (dB)
+                // Spec mask: tinted bands with their limit lines
+                if let Some(mask) = mask {
+                    let pass_color = Color::from_rgb8(0x2E, 0xE5, 0x9D);
+                    let stop_color = Color::from_rgb8(0xFF, 0x8C, 0x00);
+                    let limit = |color: Color| Stroke {
+                        width: 1.0,
+                        style: iced::widget::canvas::Style::Solid(Color { a: 0.8, ..color }),
+                        line_dash: LineDash {
+                            segments: &[4.0, 3.0],
+                            offset: 0,
+                        },
+                        ..Stroke::default()
+                    };
+                    let edge_x = |f: f64| {
+                        if usable(f) { map_x(f) } else { left }
+                    };
+                    let (pass_l, pass_r, stop_l, stop_r) = if mask.highpass {
+                        (edge_x(mask.pass_edge), right, left, edge_x(mask.stop_edge))
+                    } else {
+                        (left, edge_x(mask.pass_edge), edge_x(mask.stop_edge), right)
+                    };
+                    for (l, r, color) in
+                        [(pass_l, pass_r, pass_color), (stop_l, stop_r, stop_color)]
+                    {
+                        if r > l {
+                            frame.fill_rectangle(
+                                Point::new(l, top),
+                                Size::new(r - l, plot_h),
+                                Color { a: 0.08, ..color },
+                            );
+                        }
+                    }
+                    if pass_r > pass_l {
+                        for db in [mask.ripple, -mask.ripple] {
+                            let yy = map_y(db).clamp(top, bottom);
+                            frame.stroke(
+                                &Path::line(Point::new(pass_l, yy), Point::new(pass_r, yy)),
+                                limit(pass_color),
+                            );
+                        }
+                    }
+                    if stop_r > stop_l {
+                        let yy = map_y(-mask.attenuation);
+                        frame.stroke(
+                            &Path::line(Point::new(stop_l, yy), Point::new(stop_r, yy)),
+                            limit(stop_color),
+                        );
+                        frame.fill_text(Text {
+                            content: format!("-{} dB", fmt_tick(mask.attenuation)),
+                            position: Point::new(stop_l + 4.0, yy - 14.0),
+                            color: stop_color,
+                            size: 11.0.into(),
+                            ..Text::default()
+                        });
+                    }
+                }
+
+                // Y tick labels
                 let lbl = label_color();
                 let y_mid = 0.5 * (y_min + y_max);
                 for (val, yy) in [(y_max, top), (y_mid, (top + bottom) * 0.5), (y_min, bottom)] {
                     frame.fill_text(Text {
-                        content: format!("{:.1} dB", val),
+                        content: format!("{:.1} {}", val, self.y_unit),
                         position: Point::new(panel_x + 6.0, yy - 7.0),
                         color: lbl,
                         size: 12.0.into(),
@@ -210,7 +355,7 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
                     });
                 }
 
-                // X tick labels at decades
+                // X tick labels
                 let tick_stroke = Stroke {
                     width: 1.0,
                     style: iced::widget::canvas::Style::Solid(Color::from_rgb8(0x22, 0x22, 0x22)),
@@ -219,11 +364,7 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
                 let tick_len = 6.0_f32;
                 let x_label_y = bottom + 18.0;
 
-                for d in decade_start..=decade_end {
-                    let f = 10f64.powi(d);
-                    if f < f_min || f > f_max {
-                        continue;
-                    }
+                for (f, label) in x_ticks {
                     let xx = map_x(f);
                     frame.stroke(
                         &Path::line(Point::new(xx, bottom), Point::new(xx, bottom + tick_len)),
@@ -232,7 +373,7 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
 
                     // Value labels
                     frame.fill_text(Text {
-                        content: format!("1e{}", d),
+                        content: label,
                         position: Point::new(xx - 14.0, x_label_y - 10.0),
                         color: lbl,
                         size: 12.0.into(),
@@ -241,44 +382,289 @@ impl<'a> canvas::Program<Message> for BodeView<'a> {
                 }
 
                 frame.fill_text(Text {
-                    content: self.x_label.into(),
+                    content: format!("Frequency ({})", per_unit("cycles", self.time_unit)),
                     position: Point::new(left + plot_w * 0.5 - 80.0, bottom + 22.0),
                     color: lbl,
                     size: 12.0.into(),
                     ..Text::default()
                 });
 
-                // Bode magnitude line
-                let line_color = Color::from_rgb8(0x00, 0xB3, 0xFF);
-
-                let mut started = false;
-                let bode_path = Path::new(|p| {
-                    for i in 0..freqs.len() {
-                        let f = freqs[i];
-                        let y = mag_db[i];
-                        if !f.is_finite() || !y.is_finite() || f <= 0.0 {
-                            continue;
+                let curve_path = |freqs: &[f64], mag_db: &[f64]| {
+                    let mut started = false;
+                    Path::new(|p| {
+                        for (&f, &y) in freqs.iter().zip(mag_db) {
+                            if !usable(f) || !y.is_finite() {
+                                continue;
+                            }
+                            let pt = Point::new(map_x(f), map_y(y));
+                            if !started {
+                                p.move_to(pt);
+                                started = true;
+                            } else {
+                                p.line_to(pt);
+                            }
                         }
-                        let pt = Point::new(map_x(f), map_y(y));
-                        if !started {
-                            p.move_to(pt);
-                            started = true;
+                    })
+                };
+
+                // Sweep family, fading from green (lowest center) to magenta (highest)
+                let steps = sweep.len().max(2) - 1;
+                for (i, (center, freqs, mag_db)) in sweep.iter().enumerate() {
+                    let t = i as f32 / steps as f32;
+                    let color = Color::from_rgba(0.2 + 0.7 * t, 0.85 - 0.6 * t, 0.4 + 0.5 * t, 0.8);
+                    frame.stroke(
+                        &curve_path(freqs, mag_db),
+                        Stroke {
+                            width: 1.2,
+                            style: iced::widget::canvas::Style::Solid(color),
+                            ..Stroke::default()
+                        },
+                    );
+                    if i == 0 || i + 1 == sweep.len() {
+                        frame.fill_text(Text {
+                            content: format!("fc {}", fmt_tick(*center)),
+                            position: Point::new(map_x(*center) - 14.0, top + 2.0),
+                            color,
+                            size: 11.0.into(),
+                            ..Text::default()
+                        });
+                    }
+                }
+
+                // Pinned reference under the current curve
+                if let Some((freqs, values)) = reference {
+                    let reference_color = Color::from_rgb8(0xB0, 0xB0, 0xC0);
+                    frame.stroke(
+                        &curve_path(freqs, values),
+                        Stroke {
+                            width: 1.5,
+                            style: iced::widget::canvas::Style::Solid(reference_color),
+                            line_dash: LineDash {
+                                segments: &[6.0, 4.0],
+                                offset: 0,
+                            },
+                            ..Stroke::default()
+                        },
+                    );
+                    frame.fill_text(Text {
+                        content: format!(
+                            "current (blue) / reference: {} (grey, dashed)",
+                            self.reference_label
+                        ),
+                        position: Point::new(right - 8.0, top + 4.0),
+                        color: reference_color,
+                        size: 12.0.into(),
+                        align_x: iced::widget::text::Alignment::Right,
+                        ..Text::default()
+                    });
+                }
+
+                // Bode magnitude line
+                if let Some((freqs, mag_db)) = main {
+                    frame.stroke(
+                        &curve_path(freqs, mag_db),
+                        Stroke {
+                            width: 2.0,
+                            style: iced::widget::canvas::Style::Solid(Color::from_rgb8(
+                                0x00, 0xB3, 0xFF,
+                            )),
+                            ..Stroke::default()
+                        },
+                    );
+
+                    // Out-of-spec stretches in red, with an overall verdict
+                    if let Some(mask) = mask {
+                        let fail = Color::from_rgb8(0xFF, 0x3B, 0x3B);
+                        let mut failing = 0;
+                        let mut prev: Option<Point> = None;
+                        let bad_path = Path::new(|p| {
+                            for (&f, &y) in freqs.iter().zip(mag_db) {
+                                if !usable(f) || !y.is_finite() || !mask.violates(f, y) {
+                                    prev = None;
+                                    continue;
+                                }
+                                failing += 1;
+                                let pt = Point::new(map_x(f), map_y(y));
+                                match prev {
+                                    Some(_) => p.line_to(pt),
+                                    None => p.move_to(pt),
+                                }
+                                prev = Some(pt);
+                            }
+                        });
+                        frame.stroke(
+                            &bad_path,
+                            Stroke {
+                                width: 3.0,
+                                style: iced::widget::canvas::Style::Solid(fail),
+                                ..Stroke::default()
+                            },
+                        );
+                        let (verdict, color) = if failing == 0 {
+                            (
+                                String::from("meets spec"),
+                                Color::from_rgb8(0x2E, 0xE5, 0x9D),
+                            )
                         } else {
-                            p.line_to(pt);
+                            (format!("violates spec at {failing} point(s)"), fail)
+                        };
+                        frame.fill_text(Text {
+                            content: verdict,
+                            position: Point::new(left + 6.0, top + 4.0),
+                            color,
+                            size: 12.0.into(),
+                            ..Text::default()
+                        });
+                    }
+
+                    if let Some(requested) = self.cutoff {
+                        let marker = Color::from_rgb8(0x9C, 0xFF, 0xB0);
+                        if requested.is_finite() && requested > 0.0 {
+                            let xx = map_x(requested);
+                            frame.stroke(
+                                &Path::line(Point::new(xx, top), Point::new(xx, bottom)),
+                                Stroke {
+                                    width: 1.0,
+                                    style: iced::widget::canvas::Style::Solid(Color {
+                                        a: 0.4,
+                                        ..marker
+                                    }),
+                                    ..Stroke::default()
+                                },
+                            );
+                        }
+                        // Skipped when the curve never falls 3 dB below its peak
+                        if let Some(fc) = math::peak_minus_3db_freq(freqs, mag_db) {
+                            let xx = map_x(fc);
+                            frame.stroke(
+                                &Path::line(Point::new(xx, top), Point::new(xx, bottom)),
+                                Stroke {
+                                    width: 1.5,
+                                    style: iced::widget::canvas::Style::Solid(marker),
+                                    line_dash: LineDash {
+                                        segments: &[5.0, 4.0],
+                                        offset: 0,
+                                    },
+                                    ..Stroke::default()
+                                },
+                            );
+                            frame.fill_text(Text {
+                                content: format!(
+                                    "fc \u{2248} {} {} (requested {}, \u{0394} {:+.1}%)",
+                                    fmt_tick(fc),
+                                    per_unit("cycles", self.time_unit),
+                                    fmt_tick(requested),
+                                    100.0 * (fc - requested) / requested
+                                ),
+                                position: Point::new(xx + 4.0, bottom - 18.0),
+                                color: marker,
+                                size: 12.0.into(),
+                                ..Text::default()
+                            });
                         }
                     }
-                });
 
-                frame.stroke(
-                    &bode_path,
-                    Stroke {
-                        width: 2.0,
-                        style: iced::widget::canvas::Style::Solid(line_color),
-                        ..Stroke::default()
-                    },
-                );
+                    if self.explain {
+                        let note = Color::from_rgb8(0xFF, 0xC8, 0x57);
+                        // Cascades and custom b/a have no single requested
+                        // cutoff to compare against, so mark the -3 dB point alone
+                        if self.cutoff.is_none()
+                            && let Some(f3) = math::minus_3db_freq(freqs, mag_db)
+                        {
+                            let xx = map_x(f3);
+                            frame.stroke(
+                                &Path::line(Point::new(xx, top), Point::new(xx, bottom)),
+                                Stroke {
+                                    width: 1.0,
+                                    style: iced::widget::canvas::Style::Solid(note),
+                                    ..Stroke::default()
+                                },
+                            );
+                            frame.fill_text(Text {
+                                content: format!(
+                                    "-3 dB at {} {}",
+                                    fmt_tick(f3),
+                                    per_unit("cycles", self.time_unit)
+                                ),
+                                position: Point::new(xx + 4.0, top + 4.0),
+                                color: note,
+                                size: 12.0.into(),
+                                ..Text::default()
+                            });
+                        }
+                        if let Some((slope, intercept, f_start, f_end)) =
+                            math::stopband_fit(freqs, mag_db)
+                        {
+                            // Fitted line is straight in dB, so sample it onto the plot's axis
+                            let fit_path = Path::new(|p| {
+                                for k in 0..=20 {
+                                    let lf = f_start.log10()
+                                        + (f_end.log10() - f_start.log10()) * k as f64 / 20.0;
+                                    let y = intercept + slope * lf;
+                                    let pt = Point::new(map_x(10f64.powf(lf)), map_y(y));
+                                    if k == 0 {
+                                        p.move_to(pt);
+                                    } else {
+                                        p.line_to(pt);
+                                    }
+                                }
+                            });
+                            frame.stroke(
+                                &fit_path,
+                                Stroke {
+                                    width: 1.5,
+                                    style: iced::widget::canvas::Style::Solid(note),
+                                    ..Stroke::default()
+                                },
+                            );
+                            frame.fill_text(Text {
+                                content: match self.theory_slope {
+                                    Some(theory) => format!(
+                                        "stopband measured {slope:.1} dB/decade, theory {theory:.0}"
+                                    ),
+                                    None => format!("stopband measured {slope:.1} dB/decade"),
+                                },
+                                position: Point::new(map_x(f_start), top + 20.0),
+                                color: note,
+                                size: 12.0.into(),
+                                ..Text::default()
+                            });
+                        }
+                    }
+                }
             });
 
         vec![geom]
     }
 }
+
+/// Lower and upper frequencies where the response falls 3 dB below the nominal
+/// passband gain (its peak when None), walking out from the peak and
+/// interpolating in log f. A side that never falls (a lowpass toward DC, a
+/// highpass toward Nyquist) comes back as 0 or infinity; None when neither
+/// side falls.
+pub fn bandwidth_3db(freqs: &[f64], mag_db: &[f64], nominal_db: Option<f64>) -> Option<(f64, f64)> {
+    let pts: Vec<(f64, f64)> = freqs
+        .iter()
+        .zip(mag_db)
+        .map(|(&f, &m)| (f, m))
+        .filter(|(f, m)| f.is_finite() && *f > 0.0 && m.is_finite())
+        .collect();
+    let peak = (0..pts.len()).max_by(|&i, &j| pts[i].1.total_cmp(&pts[j].1))?;
+    let level = nominal_db.unwrap_or(pts[peak].1) - 3.0;
+    if pts[peak].1 < level {
+        return None;
+    }
+    let cross = |(f0, m0): (f64, f64), (f1, m1): (f64, f64)| {
+        let t = (m0 - level) / (m0 - m1);
+        10f64.powf(f0.log10() + t * (f1.log10() - f0.log10()))
+    };
+    let lower = (1..=peak)
+        .rev()
+        .find(|&i| pts[i - 1].1 < level)
+        .map_or(0.0, |i| cross(pts[i], pts[i - 1]));
+    let upper = (peak + 1..pts.len())
+        .find(|&i| pts[i].1 < level)
+        .map_or(f64::INFINITY, |i| cross(pts[i - 1], pts[i]));
+    (lower > 0.0 || upper.is_finite()).then_some((lower, upper))
+}
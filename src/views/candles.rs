@@ -1,5 +1,7 @@
-use crate::Message;
-use crate::structures::candle::Candle;
+use crate::math::group_roots;
+use crate::structures::candle::{Candle, CandlePalette};
+use crate::structures::filters::{EdgeHandling, FilterPhase};
+use crate::{Message, fmt_root, fmt_x_tick, glow_purple};
 use iced::widget::canvas;
 use iced::widget::canvas::{Cache, Fill, Frame, Geometry, Path, Stroke, Text};
 use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
@@ -8,17 +10,169 @@ use std::default::Default;
 pub struct CandlePanelView<'a> {
     pub zeros: Option<&'a [num_complex::Complex64]>,
     pub poles: Option<&'a [num_complex::Complex64]>,
+    pub root_tol: f64,
     pub candles: Option<&'a [Candle]>,
+    // Filtered series drawn over the candles on the same value axis
+    pub filtered: Option<&'a [f64]>,
+    // Dates of the raw samples and how many samples each candle spans,
+    // so the x ticks can name the date a candle opens on
+    pub dates: Option<&'a [chrono::NaiveDate]>,
+    pub per_candle: usize,
+    // Simple moving averages of the closes as (period in candles, color)
+    pub overlays: &'a [(usize, Color)],
+    // Bollinger bands as (period in candles, standard deviations)
+    pub bollinger: Option<(usize, f64)>,
     pub cache: &'a Cache,
     pub title: &'a str,
+    pub theme: CandleTheme,
+}
+
+// Colors for rising (close >= open) and falling candles
+#[derive(Debug, Clone, Copy)]
+pub struct CandleTheme {
+    pub up: Color,
+    pub down: Color,
+}
+
+impl From<CandlePalette> for CandleTheme {
+    fn from(palette: CandlePalette) -> Self {
+        match palette {
+            CandlePalette::GreenRed => CandleTheme {
+                up: Color::from_rgba8(0x2E, 0xE5, 0x9D, 0.90),
+                down: Color::from_rgba8(0xFF, 0x4D, 0x5A, 0.90),
+            },
+            // Okabe-Ito sky blue and orange
+            CandlePalette::BlueOrange => CandleTheme {
+                up: Color::from_rgba8(0x56, 0xB4, 0xE9, 0.90),
+                down: Color::from_rgba8(0xE6, 0x9F, 0x00, 0.90),
+            },
+        }
+    }
+}
+
+// Candle under the pointer and where the pointer is, relative to the canvas
+#[derive(Default)]
+pub struct CandleHover {
+    index: Option<usize>,
+    cursor: Option<Point>,
+}
+
+// Mean of each run of `period` consecutive closes, one value per candle from
+// index period - 1 on. Empty when there are fewer candles than the period.
+// A gap's NaN would stay in the filter state, so each finite stretch is
+// averaged on its own and windows that touch a gap are NaN.
+fn trailing_sma(candles: &[Candle], period: usize) -> Vec<f64> {
+    if period == 0 || candles.len() < period {
+        return Vec::new();
+    }
+    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+    let mut sma = vec![f64::NAN; closes.len()];
+    let mut start = 0;
+    for run in closes.split(|v| !v.is_finite()) {
+        if let Ok(out) =
+            crate::math::moving_average(run, period, EdgeHandling::Padded, FilterPhase::Causal)
+        {
+            // Causal from rest, so the first period - 1 outputs are partial sums
+            sma[start + period - 1..start + run.len()]
+                .copy_from_slice(&out.filtered_data[period - 1..]);
+        }
+        start += run.len() + 1;
+    }
+    sma.split_off(period - 1)
+}
+
+// Line through values[j] placed by `at`, lifting the pen over non-finite values
+fn polyline(values: &[f64], at: impl Fn(usize, f64) -> Point) -> Path {
+    let mut builder = canvas::path::Builder::new();
+    let mut pen_down = false;
+    for (j, &v) in values.iter().enumerate() {
+        if !v.is_finite() {
+            pen_down = false;
+        } else if pen_down {
+            builder.line_to(at(j, v));
+        } else {
+            builder.move_to(at(j, v));
+            pen_down = true;
+        }
+    }
+    builder.build()
+}
+
+// Candle plot rectangle for a canvas of the given size; matches the layout in draw
+fn plot_area(size: Size) -> Rectangle {
+    let pad = 12.0_f32;
+    let panel_w = (size.width - 2.0 * pad).max(1.0);
+    let panel_h = (size.height - 2.0 * pad).max(1.0);
+    let inner_l = pad + 12.0;
+    let inner_r = pad + panel_w - 12.0;
+    let inner_t = pad + 10.0;
+    // Bottom gutter for the x tick labels
+    let inner_b = pad + panel_h - 28.0;
+    // Header region for the title and roots
+    let header_b = (inner_t + 88.0).min(inner_b - 20.0);
+    // Right-side gutter for the axis labels
+    let plot_r = inner_r - 64.0;
+    let plot_t = header_b + 10.0;
+    Rectangle {
+        x: inner_l,
+        y: plot_t,
+        width: (plot_r - inner_l).max(1.0),
+        height: (inner_b - plot_t).max(1.0),
+    }
 }
 
 impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
-    type State = ();
+    type State = CandleHover;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: &canvas::Event,
+        bounds: Rectangle,
+        cursor: iced::mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        if !matches!(
+            event,
+            canvas::Event::Mouse(
+                iced::mouse::Event::CursorMoved { .. } | iced::mouse::Event::CursorLeft
+            )
+        ) {
+            return None;
+        }
+        let area = plot_area(bounds.size());
+        let n = self.candles.map_or(0, <[Candle]>::len);
+        let pos = cursor.position_in(bounds).filter(|p| area.contains(*p));
+        let index = match pos {
+            Some(p) if n > 0 => {
+                let slot_w = (area.width / n as f32).max(1.0);
+                Some((((p.x - area.x) / slot_w) as usize).min(n - 1))
+            }
+            _ => None,
+        };
+        if index == state.index && pos == state.cursor {
+            return None;
+        }
+        state.index = index;
+        state.cursor = pos;
+        Some(canvas::Action::request_redraw())
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        _bounds: Rectangle,
+        _cursor: iced::mouse::Cursor,
+    ) -> iced::mouse::Interaction {
+        if state.cursor.is_some() {
+            iced::mouse::Interaction::Crosshair
+        } else {
+            iced::mouse::Interaction::default()
+        }
+    }
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         renderer: &Renderer,
         _theme: &Theme,
         bounds: Rectangle,
@@ -70,11 +224,6 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
                 let inner_l = panel_x + 12.0;
                 let inner_r = panel_x + panel_w - 12.0;
                 let inner_t = panel_y + 10.0;
-                let inner_b = panel_y + panel_h - 12.0;
-
-                // Header region
-                let header_h = 88.0_f32;
-                let header_b = (inner_t + header_h).min(inner_b - 20.0);
 
                 // Title
                 frame.fill_text(Text {
@@ -92,14 +241,6 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
                 let zeros_x = inner_l;
                 let poles_x = inner_l + col_w + col_gap;
 
-                let fmt_c = |z: num_complex::Complex64| -> String {
-                    if z.im >= 0.0 {
-                        format!("{:+.6} +{:.6}j", z.re, z.im)
-                    } else {
-                        format!("{:+.6} {:.6}j", z.re, z.im)
-                    }
-                };
-
                 frame.fill_text(Text {
                     content: "Zeros (z-plane)".into(),
                     position: Point::new(zeros_x, text_y0),
@@ -119,23 +260,30 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
                 let mut y = text_y0 + 16.0;
                 let line_h = 14.0_f32;
 
-                let zeros = self.zeros.unwrap_or(&[]);
-                let poles = self.poles.unwrap_or(&[]);
+                // Finite roots are listed; those at infinity get a count line instead
+                let split = |rs: Option<&[num_complex::Complex64]>| {
+                    let rs = rs.unwrap_or(&[]);
+                    let finite: Vec<_> = rs.iter().copied().filter(|r| r.is_finite()).collect();
+                    let inf = rs.len() - finite.len();
+                    (group_roots(&finite, self.root_tol), inf)
+                };
+                let (zeros, zeros_inf) = split(self.zeros);
+                let (poles, poles_inf) = split(self.poles);
                 let rows = zeros.len().max(poles.len()).min(4); // show first 4
 
                 for i in 0..rows {
-                    if let Some(z) = zeros.get(i) {
+                    if let Some(&(z, k)) = zeros.get(i) {
                         frame.fill_text(Text {
-                            content: fmt_c(*z),
+                            content: fmt_root(z, k),
                             position: Point::new(zeros_x, y),
                             color: Color::from_rgb8(0xD0, 0xD0, 0xD0),
                             size: 12.0.into(),
                             ..Text::default()
                         });
                     }
-                    if let Some(p) = poles.get(i) {
+                    if let Some(&(p, k)) = poles.get(i) {
                         frame.fill_text(Text {
-                            content: fmt_c(*p),
+                            content: fmt_root(p, k),
                             position: Point::new(poles_x, y),
                             color: Color::from_rgb8(0xD0, 0xD0, 0xD0),
                             size: 12.0.into(),
@@ -144,19 +292,24 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
                     }
                     y += line_h;
                 }
+                for (count, x) in [(zeros_inf, zeros_x), (poles_inf, poles_x)] {
+                    if count > 0 {
+                        frame.fill_text(Text {
+                            content: format!("+{count} at \u{221E}"),
+                            position: Point::new(x, y),
+                            color: Color::from_rgb8(0xB8, 0xB8, 0xB8),
+                            size: 12.0.into(),
+                            ..Text::default()
+                        });
+                    }
+                }
 
-                // Candle plot region
-                let plot_l = inner_l;
-
-                // Reserve space inside the panel for right-side axis labels
-                let y_axis_gutter = 64.0_f32; // should be in (56..80)
-                let plot_r = inner_r - y_axis_gutter;
-
-                let plot_t = header_b + 10.0;
-                let plot_b = inner_b;
-
-                let plot_w = (plot_r - plot_l).max(1.0);
-                let plot_h = (plot_b - plot_t).max(1.0);
+                // Candle plot region, leaving a gutter for right-side axis labels
+                let area = plot_area(bounds.size());
+                let (plot_l, plot_t) = (area.x, area.y);
+                let (plot_w, plot_h) = (area.width, area.height);
+                let plot_r = plot_l + plot_w;
+                let plot_b = plot_t + plot_h;
 
                 // Axis label anchor inside panel
                 let axis_x = plot_r + 8.0; // where tick labels start
@@ -205,6 +358,26 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
                         vmax = vmax.max(c.high);
                     }
                 }
+                // Bollinger bands, computed up front so the value range covers them
+                let bands = self.bollinger.map(|(period, k)| {
+                    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+                    crate::math::bollinger_bands(&closes, period, k)
+                });
+                let (upper, lower) = bands
+                    .as_ref()
+                    .map_or((&[][..], &[][..]), |(_, u, l)| (u.as_slice(), l.as_slice()));
+                for &v in self
+                    .filtered
+                    .unwrap_or(&[])
+                    .iter()
+                    .chain(upper)
+                    .chain(lower)
+                {
+                    if v.is_finite() {
+                        vmin = vmin.min(v);
+                        vmax = vmax.max(v);
+                    }
+                }
                 if !(tmin.is_finite() && tmax.is_finite() && vmin.is_finite() && vmax.is_finite()) {
                     return;
                 }
@@ -317,11 +490,7 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
                     // Determine candle direction
                     let up = c.close >= c.open;
 
-                    let color = if up {
-                        Color::from_rgba8(0x2E, 0xE5, 0x9D, 0.90) // green
-                    } else {
-                        Color::from_rgba8(0xFF, 0x4D, 0x5A, 0.90) // red
-                    };
+                    let color = if up { self.theme.up } else { self.theme.down };
 
                     // Wick
                     frame.stroke(
@@ -358,6 +527,122 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
                         },
                     );
                 }
+                // x ticks under a few candles, labelled with the sample each one opens on
+                let last = candles.len() - 1;
+                for k in 0..=4 {
+                    let i = (k as f32 / 4.0 * last as f32).round() as usize;
+                    frame.fill_text(Text {
+                        content: fmt_x_tick(self.dates, i * self.per_candle),
+                        position: Point::new(wick_x_for(i as f32), plot_b + 6.0),
+                        color: Color::from_rgba8(0xFF, 0xFF, 0xFF, 0.65),
+                        size: 11.0.into(),
+                        align_x: match k {
+                            0 => iced::widget::text::Alignment::Left,
+                            4 => iced::widget::text::Alignment::Right,
+                            _ => iced::widget::text::Alignment::Center,
+                        },
+                        ..Text::default()
+                    });
+                }
+
+                // Filtered line, sample j placed at its fraction through the candle slots
+                if let Some(f) = self.filtered
+                    && f.len() >= 2
+                {
+                    let per_candle = f.len() as f32 / candles.len() as f32;
+                    // About one point per pixel is plenty
+                    let step = ((f.len() as f32 / plot_w).floor() as usize).max(1);
+                    let stroke = Stroke {
+                        width: 1.5,
+                        style: iced::widget::canvas::Style::Solid(glow_purple()),
+                        ..Stroke::default()
+                    };
+                    let mut prev: Option<Point> = None;
+                    for (j, &v) in f.iter().enumerate().step_by(step) {
+                        if !v.is_finite() {
+                            prev = None;
+                            continue;
+                        }
+                        let x = plot_l + (j as f32 + 0.5) / per_candle * slot_w;
+                        let p = Point::new(x.min(plot_r), map_y(v));
+                        if let Some(q) = prev {
+                            frame.stroke(&Path::line(q, p), stroke);
+                        }
+                        prev = Some(p);
+                    }
+                }
+
+                // Bollinger bands: shaded between upper and lower, lines on top
+                if let (Some((period, k)), Some((middle, upper, lower))) = (self.bollinger, &bands)
+                {
+                    let color = Color::from_rgb8(0xB3, 0x9D, 0xDB);
+                    let at = |j: usize, v: f64| {
+                        Point::new(wick_x_for((j + period - 1) as f32), map_y(v))
+                    };
+                    if upper.len() >= 2 && upper.iter().chain(lower).all(|v| v.is_finite()) {
+                        let mut band = canvas::path::Builder::new();
+                        band.move_to(at(0, upper[0]));
+                        for (j, &v) in upper.iter().enumerate().skip(1) {
+                            band.line_to(at(j, v));
+                        }
+                        for (j, &v) in lower.iter().enumerate().rev() {
+                            band.line_to(at(j, v));
+                        }
+                        band.close();
+                        frame.fill(&band.build(), Color { a: 0.12, ..color });
+                    }
+                    for (line, dash) in [(upper, false), (middle, true), (lower, false)] {
+                        frame.stroke(
+                            &polyline(line, at),
+                            Stroke {
+                                width: 1.0,
+                                style: iced::widget::canvas::Style::Solid(color),
+                                line_dash: iced::widget::canvas::LineDash {
+                                    segments: if dash { &[4.0, 3.0] } else { &[] },
+                                    offset: 0,
+                                },
+                                ..Stroke::default()
+                            },
+                        );
+                    }
+                    if !middle.is_empty() {
+                        frame.fill_text(Text {
+                            content: format!("BB {period}, {}\u{03C3}", crate::fmt_tick(k)),
+                            position: Point::new(
+                                plot_l + 6.0,
+                                plot_t + 4.0 + 14.0 * self.overlays.len() as f32,
+                            ),
+                            color,
+                            size: 11.0.into(),
+                            ..Text::default()
+                        });
+                    }
+                }
+
+                // SMA overlays, the first point once a full window of closes exists
+                for (k, &(period, color)) in self.overlays.iter().enumerate() {
+                    let sma = trailing_sma(candles, period);
+                    if sma.is_empty() {
+                        continue;
+                    }
+                    let stroke = Stroke {
+                        width: 1.5,
+                        style: iced::widget::canvas::Style::Solid(color),
+                        ..Stroke::default()
+                    };
+                    let at = |j: usize, v: f64| {
+                        Point::new(wick_x_for((j + period - 1) as f32), map_y(v))
+                    };
+                    frame.stroke(&polyline(&sma, at), stroke);
+                    frame.fill_text(Text {
+                        content: format!("SMA {period}"),
+                        position: Point::new(plot_l + 6.0, plot_t + 4.0 + 14.0 * k as f32),
+                        color,
+                        size: 11.0.into(),
+                        ..Text::default()
+                    });
+                }
+
                 // Last-close dashed reference line
                 if let Some(last) = candles
                     .iter()
@@ -368,9 +653,9 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
 
                     if y_last.is_finite() {
                         let color = if last.close >= last.open {
-                            Color::from_rgba8(0x2E, 0xE5, 0x9D, 0.90)
+                            self.theme.up
                         } else {
-                            Color::from_rgba8(0xFF, 0x4D, 0x5A, 0.90)
+                            self.theme.down
                         };
 
                         // dashed line across plot
@@ -428,6 +713,68 @@ impl<'a> canvas::Program<Message> for CandlePanelView<'a> {
                 }
             });
 
-        vec![geom]
+        // Crosshair and OHLC readout, redrawn every frame outside the cache
+        let mut hover = Frame::new(renderer, bounds.size());
+        if let (Some(p), Some(i), Some(candles)) = (state.cursor, state.index, self.candles)
+            && let Some(c) = candles.get(i)
+        {
+            let area = plot_area(bounds.size());
+            let guide = Stroke {
+                width: 1.0,
+                style: iced::widget::canvas::Style::Solid(Color::from_rgba8(
+                    0xFF, 0xFF, 0xFF, 0.45,
+                )),
+                line_dash: iced::widget::canvas::LineDash {
+                    segments: &[3.0, 3.0],
+                    offset: 0,
+                },
+                ..Stroke::default()
+            };
+            hover.stroke(
+                &Path::line(
+                    Point::new(p.x, area.y),
+                    Point::new(p.x, area.y + area.height),
+                ),
+                guide,
+            );
+            hover.stroke(
+                &Path::line(
+                    Point::new(area.x, p.y),
+                    Point::new(area.x + area.width, p.y),
+                ),
+                guide,
+            );
+
+            let label = format!(
+                "#{i}  O {:.2}  H {:.2}  L {:.2}  C {:.2}",
+                c.open, c.high, c.low, c.close
+            );
+            let font_px = 11.0_f32;
+            let box_w = label.chars().count() as f32 * font_px * 0.6 + 12.0;
+            let box_h = font_px + 8.0;
+            // Keep the box inside the plot, flipping to the left of the cursor near the edge
+            let mut x = p.x + 10.0;
+            if x + box_w > area.x + area.width {
+                x = p.x - 10.0 - box_w;
+            }
+            let y = (p.y - box_h - 6.0).max(area.y);
+            hover.fill(
+                &Path::rounded_rectangle(
+                    Point::new(x, y),
+                    Size::new(box_w, box_h),
+                    iced::border::Radius::from(4.0),
+                ),
+                Color::from_rgba8(0x16, 0x16, 0x1C, 0.92),
+            );
+            hover.fill_text(Text {
+                content: label,
+                position: Point::new(x + 6.0, y + 3.0),
+                color: Color::from_rgb8(0xE6, 0xE6, 0xE6),
+                size: font_px.into(),
+                ..Text::default()
+            });
+        }
+
+        vec![geom, hover.into_geometry()]
     }
 }
@@ -0,0 +1,84 @@
+use crate::*;
+use iced::advanced::graphics::Viewport;
+use iced::advanced::graphics::geometry::Renderer as _;
+use iced::widget::canvas;
+use iced::{Font, Pixels, Rectangle, Size, Theme, mouse};
+
+// Plots that can be written out as images
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlotKind {
+    #[default]
+    BodeMagnitude,
+    BodePhase,
+    PoleZero,
+    Nyquist,
+    Candles,
+}
+
+impl PlotKind {
+    pub const ALL: [PlotKind; 5] = [
+        PlotKind::BodeMagnitude,
+        PlotKind::BodePhase,
+        PlotKind::PoleZero,
+        PlotKind::Nyquist,
+        PlotKind::Candles,
+    ];
+}
+
+impl std::fmt::Display for PlotKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            PlotKind::BodeMagnitude => "Bode magnitude",
+            PlotKind::BodePhase => "Bode phase",
+            PlotKind::PoleZero => "Pole/zero",
+            PlotKind::Nyquist => "Nyquist",
+            PlotKind::Candles => "Candles",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// Draw a canvas program over the app background with the software renderer,
+// independent of the window, and return its RGBA pixels. The program should
+// hold a fresh Cache, since geometry cached by the GPU renderer can't be
+// replayed here.
+pub fn rasterize<P>(program: &P, size: Size<u32>) -> Result<Vec<u8>, String>
+where
+    P: canvas::Program<Message>,
+    P::State: Default,
+{
+    if size.width == 0 || size.height == 0 {
+        return Err(String::from("Image size must be non-zero"));
+    }
+    let bounds = Rectangle::with_size(Size::new(size.width as f32, size.height as f32));
+    let mut renderer =
+        iced::Renderer::Secondary(iced_tiny_skia::Renderer::new(Font::DEFAULT, Pixels(16.0)));
+    let layers = [
+        canvas::Program::<Message>::draw(
+            &views::background::Background,
+            &(),
+            &renderer,
+            &Theme::Dark,
+            bounds,
+            mouse::Cursor::Unavailable,
+        ),
+        program.draw(
+            &P::State::default(),
+            &renderer,
+            &Theme::Dark,
+            bounds,
+            mouse::Cursor::Unavailable,
+        ),
+    ];
+    for geometry in layers.into_iter().flatten() {
+        renderer.draw_geometry(geometry);
+    }
+    match &mut renderer {
+        iced::Renderer::Secondary(r) => Ok(iced_tiny_skia::window::compositor::screenshot(
+            r,
+            &Viewport::with_physical_size(size, 1.0),
+            iced::Color::BLACK,
+        )),
+        iced::Renderer::Primary(_) => Err(String::from("Software renderer unavailable")),
+    }
+}
@@ -7,15 +7,122 @@ use iced::{Color, Point, Rectangle, Renderer, Size};
 
 pub struct SpectralView<'a> {
     pub fft_out: Option<&'a [f64]>,
+    /// Spectrum of the unfiltered data, drawn behind the filtered bars
+    pub raw_fft: Option<&'a [f64]>,
+    pub overlays: &'a [structures::dataset::NamedSeries],
+    /// Sample rate (samples/day) and length of the transformed series, so bin i sits at i*fs/n_samples
+    pub fs: f64,
+    pub n_samples: usize,
+    /// Zero padding the spectra were computed with, for binning the overlays
+    pub padding: structures::filters::FftPadding,
+    /// (bin, magnitude) of the dominant peaks in fft_out to annotate
+    pub peaks: &'a [(usize, f64)],
+    /// Some(floor) plots 20*log10(mag) clamped at the floor, None plots linear magnitude
+    pub db_floor: Option<f64>,
+    /// Values are power (a Welch PSD) rather than magnitude, so dB is 10*log10
+    pub power: bool,
+    /// Time unit of the series, for the period and cycles-per-unit labels
+    pub time_unit: &'a str,
     pub cache: &'a Cache,
 }
 
+// Bin under the pointer and where the pointer is, relative to the canvas
+#[derive(Default)]
+pub struct SpectrumHover {
+    bin: Option<usize>,
+    cursor: Option<Point>,
+}
+
+// Bar plot rectangle for a canvas of the given size; matches the layout in draw
+fn plot_area(size: Size) -> Rectangle {
+    let pad = 12.0_f32;
+    let panel_w = (size.width - 3.0 * pad).max(1.0);
+    let panel_h = (size.height - 2.0 * pad).max(1.0);
+    let left = pad + 40.0;
+    let right = pad + panel_w - 12.0;
+    let top = pad + 12.0;
+    let bottom = pad + panel_h - 28.0;
+    Rectangle {
+        x: left,
+        y: top,
+        width: (right - left).max(1.0),
+        height: (bottom - top).max(1.0),
+    }
+}
+
+impl SpectralView<'_> {
+    // Width of one bin in cycles/day for a spectrum of n bins
+    fn bin_hz(&self, n: usize) -> f64 {
+        let n_samples = if self.n_samples > 0 {
+            self.n_samples
+        } else {
+            2 * n.saturating_sub(1)
+        };
+        self.fs / n_samples.max(1) as f64
+    }
+
+    // Magnitude as plotted, linear or dB clamped at the floor
+    fn level(&self, m: f64) -> f64 {
+        match self.db_floor {
+            // 10 log10(p) is 20 log10(sqrt p)
+            Some(floor) if self.power => math::to_db(m.sqrt(), floor),
+            Some(floor) => math::to_db(m, floor),
+            None => m,
+        }
+    }
+}
+
 impl<'a> canvas::Program<Message> for SpectralView<'a> {
-    type State = ();
+    type State = SpectrumHover;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: &canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        if !matches!(
+            event,
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. } | mouse::Event::CursorLeft)
+        ) {
+            return None;
+        }
+        let area = plot_area(bounds.size());
+        let n = self.fft_out.map_or(0, <[f64]>::len);
+        let pos = cursor.position_in(bounds).filter(|p| area.contains(*p));
+        let bin = match pos {
+            // DC is never drawn, so snap to bin 1 and up
+            Some(p) if n >= 2 => {
+                let f = (p.x - area.x) as f64 / area.width as f64 * 0.5 * self.fs;
+                Some(((f / self.bin_hz(n)).round() as usize).clamp(1, n - 1))
+            }
+            _ => None,
+        };
+        if bin == state.bin && pos == state.cursor {
+            return None;
+        }
+        state.bin = bin;
+        state.cursor = pos;
+        Some(canvas::Action::request_redraw())
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        _bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.cursor.is_some() {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::default()
+        }
+    }
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         renderer: &Renderer,
         _theme: &Theme,
         bounds: Rectangle,
@@ -107,14 +214,30 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
             }
 
             // Y range from both series (raw + filtered if present)
-            let ymin = 0f64;
+            let ymin = self.db_floor.unwrap_or(0.0);
             let mut ymax = f64::NEG_INFINITY;
 
-            for &y in fft_out {
+            for y in fft_out
+                .iter()
+                .chain(self.raw_fft.unwrap_or(&[]))
+                .map(|&m| self.level(m))
+            {
                 if y.is_finite() {
                     ymax = ymax.max(y);
                 }
             }
+            let overlay_spectra = self
+                .overlays
+                .iter()
+                .enumerate()
+                .filter_map(|(k, o)| o.spectrum.as_deref().map(|s| (k, o, s)));
+            for (_, _, spectrum) in overlay_spectra.clone() {
+                for y in spectrum.iter().skip(1).map(|&m| self.level(m)) {
+                    if y.is_finite() {
+                        ymax = ymax.max(y);
+                    }
+                }
+            }
 
             if !ymin.is_finite() || !ymax.is_finite() {
                 return;
@@ -168,8 +291,9 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
 
             let y_mid = 0.5 * (ymin + ymax);
             for (val, yy) in [(ymax, top), (y_mid, (top + bottom) * 0.5), (ymin, bottom)] {
+                let unit = if self.db_floor.is_some() { " dB" } else { "" };
                 frame.fill_text(Text {
-                    content: fmt_tick(val),
+                    content: format!("{}{unit}", fmt_tick(val)),
                     position: Point::new(panel_x + 6.0, yy - 6.0),
                     color: label_color,
                     size: size.into(),
@@ -177,8 +301,14 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
                 });
             }
 
+            // Frequency axis runs 0..Nyquist
+            let nyq = 0.5 * self.fs;
+            let map_f = |f: f64| -> f32 { left + (f / nyq) as f32 * plot_w };
+            let bin_hz = self.bin_hz(n);
+
             // --- bars ---
-            let baseline_val = if ymin <= 0.0 && 0.0 <= ymax {
+            // dB bars grow up from the floor
+            let baseline_val = if self.db_floor.is_none() && ymin <= 0.0 && 0.0 <= ymax {
                 0.0
             } else {
                 ymin
@@ -186,7 +316,7 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
             let baseline_y = map_y(baseline_val);
 
             // Bar sizing
-            let dx = plot_w / (n as f32);
+            let dx = map_f(bin_hz) - left;
             let gap = (dx * 0.15).min(3.0); // spacing between bars
             let bar_w = (dx - gap).max(1.0);
 
@@ -194,16 +324,52 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
             let mut max_bar_height = 0f64;
 
             for &num in fft_out {
-                max_bar_height = f64::max(max_bar_height, num);
+                max_bar_height = f64::max(max_bar_height, self.level(num) - baseline_val);
+            }
+
+            // raw spectrum as faint bars so the filtered bars show what was kept
+            if let Some(raw) = self.raw_fft {
+                let raw_color = Color {
+                    a: 0.35,
+                    ..Color::from_rgb8(0x99, 0x99, 0x99)
+                };
+                for (i, y) in raw.iter().map(|&m| self.level(m)).enumerate().skip(1) {
+                    if !y.is_finite() {
+                        continue;
+                    }
+                    let x = map_f(i as f64 * bin_hz) - 0.5 * bar_w;
+                    let y_px = map_y(y);
+                    let (top_y, height) = if y_px < baseline_y {
+                        (y_px, baseline_y - y_px)
+                    } else {
+                        (baseline_y, y_px - baseline_y)
+                    };
+                    let rect =
+                        Path::rectangle(Point::new(x, top_y), Size::new(bar_w, height.max(1.0)));
+                    frame.fill(
+                        &rect,
+                        Fill {
+                            style: Style::Solid(raw_color),
+                            ..Fill::default()
+                        },
+                    );
+                }
+                frame.fill_text(Text {
+                    content: "raw (grey) / filtered (blue)".into(),
+                    position: Point::new(left + 6.0, top + 4.0),
+                    color: label_color,
+                    size: 12.0.into(),
+                    ..Text::default()
+                });
             }
 
-            for (i, &y) in fft_out.iter().enumerate().skip(1) {
+            for (i, y) in fft_out.iter().map(|&m| self.level(m)).enumerate().skip(1) {
                 if !y.is_finite() {
                     continue;
                 }
 
-                // x position centered in bin i
-                let x = left + (i as f32) * dx + gap * 0.5;
+                // x position centered on bin i's frequency
+                let x = map_f(i as f64 * bin_hz) - 0.5 * bar_w;
 
                 let y_px = map_y(y);
 
@@ -229,6 +395,56 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
                 );
             }
 
+            // dominant peaks, labeled with frequency and period
+            let peak_color = Color::from_rgb8(0xFF, 0xC8, 0x57);
+            for &(i, y) in self.peaks {
+                // Placed between bins, since the spacing is coarse on short records
+                let f = math::refine_peak(fft_out, i) * bin_hz;
+                let p = Point::new(map_f(f), map_y(self.level(y)));
+                frame.fill(&Path::circle(p, 3.5), peak_color);
+                frame.fill_text(Text {
+                    content: format!("{} ({:.1} {})", fmt_tick(f), 1.0 / f, self.time_unit),
+                    position: Point::new(p.x + 5.0, p.y - 14.0),
+                    color: peak_color,
+                    size: 11.0.into(),
+                    ..Text::default()
+                });
+            }
+
+            // overlay spectra as lines, each binned by its own length
+            for (k, series, spectrum) in overlay_spectra {
+                if spectrum.len() < 2 {
+                    continue;
+                }
+                let overlay_bin = self.fs
+                    / math::fft_len(series.display_data().len(), self.padding).max(1) as f64;
+                let color = series_color(k);
+                let stroke = Stroke {
+                    width: 1.5,
+                    style: Style::Solid(Color { a: 0.85, ..color }),
+                    ..Stroke::default()
+                };
+                let mut prev = None;
+                for (i, y) in spectrum.iter().map(|&m| self.level(m)).enumerate().skip(1) {
+                    if !y.is_finite() {
+                        prev = None;
+                        continue;
+                    }
+                    let p = Point::new(map_f(i as f64 * overlay_bin), map_y(y));
+                    if let Some(q) = prev {
+                        frame.stroke(&Path::line(q, p), stroke);
+                    }
+                    prev = Some(p);
+                }
+                frame.fill_text(Text {
+                    content: series.name.clone(),
+                    position: Point::new(right - 120.0, top + 4.0 + 14.0 * k as f32),
+                    color,
+                    size: 12.0.into(),
+                    ..Text::default()
+                });
+            }
+
             let tick_stroke = Stroke {
                 width: 1.0,
                 style: Style::Solid(Color::from_rgb8(0x22, 0x22, 0x22)),
@@ -239,7 +455,6 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
             let tick_len = 6.0_f32;
 
             // label 0 .. Nyquist (fs/2) in units cycles/day
-            let nyq = 1. / 2.0;
             for k in 0..=4 {
                 let t = k as f32 / 4.0;
                 let x = left + t * plot_w;
@@ -250,7 +465,10 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
                     tick_stroke,
                 );
 
-                // value
+                // DC sits under the y labels, leave it unlabeled
+                if k == 0 {
+                    continue;
+                }
                 let f = (t as f64) * nyq;
                 frame.fill_text(Text {
                     content: fmt_tick(f),
@@ -263,7 +481,7 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
 
             // x-axis unit label
             frame.fill_text(Text {
-                content: "Frequency (cycles/day)".into(),
+                content: format!("Frequency ({})", per_unit("cycles", self.time_unit)),
                 position: Point::new(left + plot_w * 0.5 - 70.0, bottom + 20.0),
                 color: label_color,
                 size: 12.0.into(),
@@ -271,6 +489,65 @@ impl<'a> canvas::Program<Message> for SpectralView<'a> {
             });
         });
 
-        vec![geom]
+        // Snapped bin guide and frequency/magnitude readout, redrawn every frame outside the cache
+        let mut hover = canvas::Frame::new(renderer, bounds.size());
+        if let (Some(p), Some(i), Some(fft_out)) = (state.cursor, state.bin, self.fft_out)
+            && let Some(&m) = fft_out.get(i)
+        {
+            let area = plot_area(bounds.size());
+            let f = i as f64 * self.bin_hz(fft_out.len());
+            let x = area.x + (f / (0.5 * self.fs)) as f32 * area.width;
+            hover.stroke(
+                &Path::line(Point::new(x, area.y), Point::new(x, area.y + area.height)),
+                Stroke {
+                    width: 1.0,
+                    style: Style::Solid(Color::from_rgba8(0xFF, 0xFF, 0xFF, 0.45)),
+                    line_dash: canvas::LineDash {
+                        segments: &[3.0, 3.0],
+                        offset: 0,
+                    },
+                    ..Stroke::default()
+                },
+            );
+
+            let magnitude = match self.db_floor {
+                // Clamped at the floor the same way the bars are
+                Some(_) => format!("{} dB", fmt_tick(self.level(m))),
+                None => fmt_tick(m),
+            };
+            let label = format!(
+                "bin {i}  {} {} ({:.1} {})  {magnitude}",
+                fmt_tick(f),
+                per_unit("cycles", self.time_unit),
+                1.0 / f,
+                self.time_unit
+            );
+            let font_px = 11.0_f32;
+            let box_w = label.chars().count() as f32 * font_px * 0.6 + 12.0;
+            let box_h = font_px + 8.0;
+            // Keep the box inside the plot, flipping to the left of the cursor near the edge
+            let mut bx = p.x + 10.0;
+            if bx + box_w > area.x + area.width {
+                bx = p.x - 10.0 - box_w;
+            }
+            let by = (p.y - box_h - 6.0).max(area.y);
+            hover.fill(
+                &Path::rounded_rectangle(
+                    Point::new(bx, by),
+                    Size::new(box_w, box_h),
+                    Radius::from(4.0),
+                ),
+                Color::from_rgba8(0x16, 0x16, 0x1C, 0.92),
+            );
+            hover.fill_text(Text {
+                content: label,
+                position: Point::new(bx + 6.0, by + 3.0),
+                color: Color::from_rgb8(0xE6, 0xE6, 0xE6),
+                size: font_px.into(),
+                ..Text::default()
+            });
+        }
+
+        vec![geom, hover.into_geometry()]
     }
 }
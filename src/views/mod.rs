@@ -1,6 +1,10 @@
 pub mod background;
 pub mod bode;
 pub mod candles;
+pub mod export;
 pub mod frequency;
+pub mod nyquist;
 pub mod pz;
+pub mod sections;
+pub mod spectrogram;
 pub mod time;
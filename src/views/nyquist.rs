@@ -0,0 +1,235 @@
+use crate::*;
+use iced::Theme;
+use iced::border::Radius;
+use iced::mouse;
+use iced::widget::canvas::{self, Cache, Fill, Geometry, Path, Stroke, Style, Text};
+use iced::{Color, Point, Rectangle, Renderer, Size};
+use num_complex::Complex;
+
+// H(e^{jw}) traced in the complex plane, w from 0 to pi
+pub struct NyquistView<'a> {
+    pub curve: Option<&'a [Complex<f64>]>,
+    // Shown in place of the plot when there is no curve
+    pub empty_text: &'a str,
+    pub cache: &'a Cache,
+}
+
+impl<'a> canvas::Program<Message> for NyquistView<'a> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let geom = self.cache.draw(renderer, bounds.size(), |frame| {
+            let w = bounds.width;
+            let h = bounds.height;
+
+            let pad = 12.0_f32;
+            let panel_x = pad;
+            let panel_y = pad;
+            let panel_w = (w - 2.0 * pad).max(1.0);
+            let panel_h = (h - 2.0 * pad).max(1.0);
+
+            let panel = Path::rounded_rectangle(
+                Point::new(panel_x, panel_y),
+                Size::new(panel_w, panel_h),
+                Radius::from(22.0),
+            );
+            frame.fill(
+                &panel,
+                Fill {
+                    style: Style::Solid(panel_bg()),
+                    ..Fill::default()
+                },
+            );
+            frame.stroke(
+                &panel,
+                Stroke {
+                    width: 1.0,
+                    style: Style::Solid(panel_border()),
+                    ..Stroke::default()
+                },
+            );
+            frame.stroke(
+                &panel,
+                Stroke {
+                    width: 1.0,
+                    style: Style::Solid(Color {
+                        a: 0.22,
+                        ..glow_purple()
+                    }),
+                    ..Stroke::default()
+                },
+            );
+
+            let center = Point::new(panel_x + panel_w * 0.5, panel_y + panel_h * 0.5);
+            let curve = match self.curve {
+                Some(c) if c.iter().any(|z| z.is_finite()) => c,
+                _ => {
+                    frame.fill_text(Text {
+                        content: self.empty_text.into(),
+                        position: center,
+                        color: label_color(),
+                        size: 14.0.into(),
+                        align_x: iced::widget::text::Alignment::Center,
+                        align_y: iced::alignment::Vertical::Center,
+                        ..Text::default()
+                    });
+                    return;
+                }
+            };
+
+            // Pull runaway points (poles on or near the unit circle) back
+            // to the clamp circle so they can't blow up the scale
+            let mut clamped = false;
+            let points: Vec<Option<Complex<f64>>> = curve
+                .iter()
+                .map(|&z| {
+                    if !z.is_finite() {
+                        clamped = true;
+                        None
+                    } else if z.norm() > NYQUIST_MAX_GAIN {
+                        clamped = true;
+                        Some(z * (NYQUIST_MAX_GAIN / z.norm()))
+                    } else {
+                        Some(z)
+                    }
+                })
+                .collect();
+
+            // Square extent around the origin that always shows -1
+            let extent = points
+                .iter()
+                .flatten()
+                .map(|z| z.re.abs().max(z.im.abs()))
+                .fold(1.0_f64, f64::max)
+                * 1.15;
+            let scale = 0.5 * panel_w.min(panel_h) / extent as f32;
+            let to_px = |z: Complex<f64>| {
+                Point::new(
+                    center.x + z.re as f32 * scale,
+                    center.y - z.im as f32 * scale,
+                )
+            };
+
+            let axis_stroke = Stroke {
+                width: 1.0,
+                style: Style::Solid(grid_color()),
+                ..Stroke::default()
+            };
+            frame.stroke(
+                &Path::line(
+                    Point::new(panel_x, center.y),
+                    Point::new(panel_x + panel_w, center.y),
+                ),
+                axis_stroke,
+            );
+            frame.stroke(
+                &Path::line(
+                    Point::new(center.x, panel_y),
+                    Point::new(center.x, panel_y + panel_h),
+                ),
+                axis_stroke,
+            );
+
+            let label_color = label_color();
+            frame.fill_text(Text {
+                content: "Re".into(),
+                position: Point::new(panel_x + panel_w - 24.0, center.y + 4.0),
+                color: label_color,
+                size: 12.0.into(),
+                ..Text::default()
+            });
+            frame.fill_text(Text {
+                content: "Im".into(),
+                position: Point::new(center.x + 4.0, panel_y + 6.0),
+                color: label_color,
+                size: 12.0.into(),
+                ..Text::default()
+            });
+            frame.fill_text(Text {
+                content: format!("\u{00B1}{}", fmt_tick(extent)),
+                position: Point::new(panel_x + 12.0, panel_y + 10.0),
+                color: label_color,
+                size: 12.0.into(),
+                ..Text::default()
+            });
+
+            let mut builder = canvas::path::Builder::new();
+            let mut pen_down = false;
+            for z in &points {
+                match z {
+                    Some(z) if pen_down => builder.line_to(to_px(*z)),
+                    Some(z) => {
+                        builder.move_to(to_px(*z));
+                        pen_down = true;
+                    }
+                    None => pen_down = false,
+                }
+            }
+            frame.stroke(
+                &builder.build(),
+                Stroke {
+                    width: 2.0,
+                    style: Style::Solid(Color::from_rgb8(0x00, 0x66, 0xCC)),
+                    ..Stroke::default()
+                },
+            );
+
+            // Dot at w = 0 shows which way the sweep runs
+            if let Some(Some(z)) = points.first() {
+                frame.fill(
+                    &Path::circle(to_px(*z), 3.0),
+                    Color::from_rgb8(0x00, 0x66, 0xCC),
+                );
+            }
+
+            // Critical point
+            let critical = to_px(Complex::new(-1.0, 0.0));
+            let d = 5.0;
+            let critical_stroke = Stroke {
+                width: 2.0,
+                style: Style::Solid(Color::from_rgb8(0xCC, 0x00, 0x00)),
+                ..Stroke::default()
+            };
+            frame.stroke(
+                &Path::line(
+                    Point::new(critical.x - d, critical.y - d),
+                    Point::new(critical.x + d, critical.y + d),
+                ),
+                critical_stroke,
+            );
+            frame.stroke(
+                &Path::line(
+                    Point::new(critical.x - d, critical.y + d),
+                    Point::new(critical.x + d, critical.y - d),
+                ),
+                critical_stroke,
+            );
+            frame.fill_text(Text {
+                content: "-1".into(),
+                position: Point::new(critical.x - 6.0, critical.y + 8.0),
+                color: label_color,
+                size: 12.0.into(),
+                ..Text::default()
+            });
+
+            if clamped {
+                frame.fill_text(Text {
+                    content: format!("clamped at |H| = {}", fmt_tick(NYQUIST_MAX_GAIN)),
+                    position: Point::new(panel_x + 12.0, panel_y + panel_h - 22.0),
+                    color: Color::from_rgb8(0xFF, 0x8C, 0x00),
+                    size: 12.0.into(),
+                    ..Text::default()
+                });
+            }
+        });
+
+        vec![geom]
+    }
+}
@@ -9,6 +9,10 @@ use num_complex::Complex;
 pub struct PzPlotView<'a> {
     pub zeros: Option<&'a [Complex<f64>]>,
     pub poles: Option<&'a [Complex<f64>]>,
+    pub root_tol: f64,
+    pub explain: bool,
+    // Shown in place of the plot when there are no roots
+    pub empty_text: &'a str,
     pub cache: &'a Cache,
 }
 
@@ -83,7 +87,7 @@ impl<'a> canvas::Program<Message> for PzPlotView<'a> {
                 let top = panel_y + 12.0;
                 let bottom = panel_y + panel_h - 30.0;
                 frame.fill_text(Text {
-                    content: "No data loaded".into(),
+                    content: self.empty_text.into(),
                     position: Point::new(((left + right) * 0.5) - x_bias, (top + bottom) * 0.5),
                     color: label_color(),
                     size: size.into(),
@@ -207,11 +211,41 @@ impl<'a> canvas::Program<Message> for PzPlotView<'a> {
                 ..Text::default()
             });
 
+            // Multiplicity count beside coincident markers
+            let multiplicity_label = |frame: &mut canvas::Frame, p: Point, k: usize| {
+                if k > 1 {
+                    frame.fill_text(Text {
+                        content: format!("\u{00D7}{k}"),
+                        position: Point::new(p.x + 6.0, p.y - 16.0),
+                        color: label_color,
+                        size: 11.0.into(),
+                        ..Text::default()
+                    });
+                }
+            };
+
+            // Roots at infinity can't be placed, so count them in a corner instead
+            let at_infinity = |rs: Option<&[Complex<f64>]>| {
+                rs.map_or(0, |rs| rs.iter().filter(|r| !r.is_finite()).count())
+            };
+            let (inf_zeros, inf_poles) = (at_infinity(self.zeros), at_infinity(self.poles));
+            if inf_zeros + inf_poles > 0 {
+                frame.fill_text(Text {
+                    content: format!("\u{221E}: {inf_zeros} zero(s), {inf_poles} pole(s)"),
+                    position: Point::new(origin.x + inner_w - 16.0, origin.y + 12.0),
+                    color: label_color,
+                    size: 12.0.into(),
+                    align_x: iced::widget::text::Alignment::Right,
+                    ..Text::default()
+                });
+            }
+
             // Zeros:
             if let Some(zs) = self.zeros {
-                for &z in zs {
+                for (z, k) in math::group_roots(zs, self.root_tol) {
                     if z.re.is_finite() && z.im.is_finite() {
                         let p = to_px(z);
+                        multiplicity_label(frame, p, k);
                         frame.stroke(
                             &Path::circle(p, 5.0),
                             Stroke {
@@ -224,15 +258,24 @@ impl<'a> canvas::Program<Message> for PzPlotView<'a> {
                 }
             }
 
-            // Poles
+            // Poles, outside the unit circle in orange
             if let Some(ps) = self.poles {
-                for &p0 in ps {
+                let unstable_color = Color::from_rgb8(0xFF, 0x8C, 0x00);
+                let mut any_unstable = false;
+                for (p0, k) in math::group_roots(ps, self.root_tol) {
                     if p0.re.is_finite() && p0.im.is_finite() {
                         let p = to_px(p0);
+                        multiplicity_label(frame, p, k);
                         let d = 5.0;
+                        let unstable = p0.norm() > 1.0 + STABILITY_EPS;
+                        any_unstable |= unstable;
                         let pole_stroke = Stroke {
                             width: 2.0,
-                            style: Style::Solid(Color::from_rgb8(0xCC, 0x00, 0x00)),
+                            style: Style::Solid(if unstable {
+                                unstable_color
+                            } else {
+                                Color::from_rgb8(0xCC, 0x00, 0x00)
+                            }),
                             ..Stroke::default()
                         };
 
@@ -246,6 +289,48 @@ impl<'a> canvas::Program<Message> for PzPlotView<'a> {
                         );
                     }
                 }
+
+                if any_unstable {
+                    frame.fill_text(Text {
+                        content: "\u{00D7} unstable pole (|z| > 1)".into(),
+                        position: Point::new(origin.x + 12.0, origin.y + inner_h - 22.0),
+                        color: unstable_color,
+                        size: 12.0.into(),
+                        ..Text::default()
+                    });
+                }
+
+                // Call out the pole closest to the unit circle
+                let nearest = ps
+                    .iter()
+                    .filter(|p| p.is_finite() && p.im >= 0.0)
+                    .max_by(|a, b| a.norm().total_cmp(&b.norm()));
+                if self.explain
+                    && let Some(&p0) = nearest
+                    && p0.norm() > 0.8
+                {
+                    let p = to_px(p0);
+                    let note = Color::from_rgb8(0xFF, 0xC8, 0x57);
+                    let anchor = Point::new(origin.x + 10.0, origin.y + 10.0);
+                    frame.stroke(
+                        &Path::line(Point::new(anchor.x + 20.0, anchor.y + 16.0), p),
+                        Stroke {
+                            width: 1.0,
+                            style: Style::Solid(note),
+                            ..Stroke::default()
+                        },
+                    );
+                    frame.fill_text(Text {
+                        content: format!(
+                            "|p| = {:.3}: poles near the unit circle = sharp resonance",
+                            p0.norm()
+                        ),
+                        position: anchor,
+                        color: note,
+                        size: 12.0.into(),
+                        ..Text::default()
+                    });
+                }
             }
         });
 
@@ -0,0 +1,119 @@
+use crate::*;
+use iced::Theme;
+use iced::border::Radius;
+use iced::mouse;
+use iced::widget::canvas::{self, Fill, Geometry, Path, Stroke, Style, Text};
+use iced::{Color, Point, Rectangle, Renderer, Size};
+
+// Small |H| plot for a single second-order section
+pub struct SectionResponseView<'a> {
+    pub freqs: &'a [f64],
+    pub mag: &'a [f64],
+}
+
+impl<'a> canvas::Program<Message> for SectionResponseView<'a> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let geom = canvas::Cache::new().draw(renderer, bounds.size(), |frame| {
+            let w = bounds.width;
+            let h = bounds.height;
+
+            let pad = 4.0_f32;
+            let panel = Path::rounded_rectangle(
+                Point::new(pad, pad),
+                Size::new((w - 2.0 * pad).max(1.0), (h - 2.0 * pad).max(1.0)),
+                Radius::from(10.0),
+            );
+            frame.fill(
+                &panel,
+                Fill {
+                    style: Style::Solid(panel_bg()),
+                    ..Fill::default()
+                },
+            );
+            frame.stroke(
+                &panel,
+                Stroke {
+                    width: 1.0,
+                    style: Style::Solid(Color {
+                        a: 0.22,
+                        ..glow_purple()
+                    }),
+                    ..Stroke::default()
+                },
+            );
+
+            let left = pad + 8.0;
+            let right = w - pad - 8.0;
+            let top = pad + 18.0;
+            let bottom = h - pad - 6.0;
+            let plot_w = (right - left).max(1.0);
+            let plot_h = (bottom - top).max(1.0);
+
+            let mut f_min = f64::INFINITY;
+            let mut f_max = f64::NEG_INFINITY;
+            let mut m_max = 0.0_f64;
+            for (&f, &m) in self.freqs.iter().zip(self.mag) {
+                if f.is_finite() && f > 0.0 && m.is_finite() {
+                    f_min = f_min.min(f);
+                    f_max = f_max.max(f);
+                    m_max = m_max.max(m);
+                }
+            }
+            if !(f_min.is_finite() && f_max > f_min) {
+                return;
+            }
+            if m_max <= 0.0 {
+                m_max = 1.0;
+            }
+
+            frame.fill_text(Text {
+                content: format!("peak |H| = {}", fmt_tick(m_max)),
+                position: Point::new(left, pad + 4.0),
+                color: label_color(),
+                size: 11.0.into(),
+                ..Text::default()
+            });
+
+            let log_f_min = f_min.log10();
+            let log_span = (f_max.log10() - log_f_min).max(1e-12);
+            let map_x =
+                |f: f64| -> f32 { left + ((f.log10() - log_f_min) / log_span) as f32 * plot_w };
+            let map_y = |m: f64| -> f32 { bottom - (m / m_max) as f32 * plot_h };
+
+            let mut started = false;
+            let path = Path::new(|p| {
+                for (&f, &m) in self.freqs.iter().zip(self.mag) {
+                    if !(f.is_finite() && f > 0.0 && m.is_finite()) {
+                        continue;
+                    }
+                    let pt = Point::new(map_x(f), map_y(m));
+                    if !started {
+                        p.move_to(pt);
+                        started = true;
+                    } else {
+                        p.line_to(pt);
+                    }
+                }
+            });
+            frame.stroke(
+                &path,
+                Stroke {
+                    width: 1.5,
+                    style: Style::Solid(Color::from_rgb8(0x00, 0xB3, 0xFF)),
+                    ..Stroke::default()
+                },
+            );
+        });
+
+        vec![geom]
+    }
+}
@@ -0,0 +1,185 @@
+use crate::*;
+use iced::Theme;
+use iced::border::Radius;
+use iced::mouse;
+use iced::widget::canvas::{self, Cache, Fill, Geometry, Path, Stroke, Style, Text};
+use iced::{Color, Point, Rectangle, Renderer, Size};
+
+// STFT frames as a heatmap, time across and frequency up
+pub struct SpectrogramView<'a> {
+    /// One row of bin magnitudes per frame, as math::stft returns them
+    pub frames: Option<&'a [Vec<f64>]>,
+    /// Sample rate (samples/day) and samples between frame starts, for the axes
+    pub fs: f64,
+    pub hop: usize,
+    /// Some(floor) colors by dB below the loudest cell, down to the floor; None is linear
+    pub db_floor: Option<f64>,
+    /// Time unit of the series, for the axis label
+    pub time_unit: &'a str,
+    pub cache: &'a Cache,
+}
+
+impl<'a> canvas::Program<Message> for SpectrogramView<'a> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let geom = self.cache.draw(renderer, bounds.size(), |frame| {
+            let w = bounds.width;
+            let h = bounds.height;
+
+            let pad = 12.0_f32;
+            let panel_x = pad;
+            let panel_y = pad;
+            let panel_w = (w - 3.0 * pad).max(1.0);
+            let panel_h = (h - 2.0 * pad).max(1.0);
+
+            let panel = Path::rounded_rectangle(
+                Point::new(panel_x, panel_y),
+                Size::new(panel_w, panel_h),
+                Radius::from(22.0),
+            );
+            frame.fill(
+                &panel,
+                Fill {
+                    style: Style::Solid(panel_bg()),
+                    ..Fill::default()
+                },
+            );
+            frame.stroke(
+                &panel,
+                Stroke {
+                    width: 1.0,
+                    style: Style::Solid(panel_border()),
+                    ..Stroke::default()
+                },
+            );
+            frame.stroke(
+                &panel,
+                Stroke {
+                    width: 1.0,
+                    style: Style::Solid(Color {
+                        a: 0.22,
+                        ..glow_purple()
+                    }),
+                    ..Stroke::default()
+                },
+            );
+
+            let left = panel_x + 40.0;
+            let right = panel_x + panel_w - 12.0;
+            let top = panel_y + 12.0;
+            let bottom = panel_y + panel_h - 28.0;
+            let plot_w = (right - left).max(1.0);
+            let plot_h = (bottom - top).max(1.0);
+            let label_color = label_color();
+
+            let frames = match self.frames {
+                Some(f) if !f.is_empty() && f[0].len() >= 2 => f,
+                _ => {
+                    frame.fill_text(Text {
+                        content: "No data loaded".into(),
+                        position: Point::new((left + right) * 0.5, (top + bottom) * 0.5),
+                        color: label_color,
+                        size: 14.0.into(),
+                        align_x: iced::widget::text::Alignment::Center,
+                        align_y: iced::alignment::Vertical::Center,
+                        ..Text::default()
+                    });
+                    return;
+                }
+            };
+            let bins = frames[0].len();
+
+            // Color scale runs from the floor (or zero) up to the loudest cell
+            let peak = frames
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|m| m.is_finite())
+                .fold(0.0, f64::max);
+            let shade = |m: f64| -> f32 {
+                if !m.is_finite() || peak <= 0.0 {
+                    return 0.0;
+                }
+                match self.db_floor {
+                    Some(floor) if floor < 0.0 => {
+                        let db = math::to_db(m / peak, floor);
+                        (1.0 - db / floor) as f32
+                    }
+                    _ => (m / peak) as f32,
+                }
+            };
+
+            let cell_w = plot_w / frames.len() as f32;
+            let cell_h = plot_h / bins as f32;
+            for (i, row) in frames.iter().enumerate() {
+                let x = left + i as f32 * cell_w;
+                for (k, &m) in row.iter().enumerate() {
+                    // Bin k is centered on its frequency, DC at the bottom edge
+                    let y = bottom - (k as f32 + 0.5) * cell_h;
+                    frame.fill_rectangle(
+                        Point::new(x, (y - 0.5 * cell_h).max(top)),
+                        Size::new(cell_w + 0.5, cell_h + 0.5),
+                        colormap(shade(m)),
+                    );
+                }
+            }
+
+            frame.stroke(
+                &Path::rectangle(Point::new(left, top), Size::new(plot_w, plot_h)),
+                Stroke {
+                    width: 1.0,
+                    style: Style::Solid(grid_color()),
+                    ..Stroke::default()
+                },
+            );
+
+            // Frequency up the side, 0..Nyquist
+            let nyq = 0.5 * self.fs;
+            for k in 0..=2 {
+                let t = k as f32 / 2.0;
+                frame.fill_text(Text {
+                    content: fmt_tick(t as f64 * nyq),
+                    position: Point::new(panel_x + 6.0, bottom - t * plot_h - 6.0),
+                    color: label_color,
+                    size: 12.0.into(),
+                    ..Text::default()
+                });
+            }
+
+            // Time along the bottom, each column labelled by its frame's center
+            let half_window = (bins - 1) as f64;
+            for k in 0..=4 {
+                let t = k as f32 / 4.0;
+                let start = t as f64 * frames.len() as f64 * self.hop as f64;
+                frame.fill_text(Text {
+                    content: fmt_tick((start + half_window) / self.fs),
+                    position: Point::new(left + t * plot_w - 12.0, bottom + 6.0),
+                    color: label_color,
+                    size: 12.0.into(),
+                    ..Text::default()
+                });
+            }
+            frame.fill_text(Text {
+                content: format!(
+                    "Time ({}) / frequency ({})",
+                    self.time_unit,
+                    per_unit("cycles", self.time_unit)
+                ),
+                position: Point::new(left + plot_w * 0.5 - 110.0, bottom + 18.0),
+                color: label_color,
+                size: 12.0.into(),
+                ..Text::default()
+            });
+        });
+
+        vec![geom]
+    }
+}
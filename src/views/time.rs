@@ -8,15 +8,167 @@ use iced::{Color, Point, Rectangle, Renderer, Size};
 pub struct TimeSeriesPlotView<'a> {
     pub raw: Option<&'a [f64]>,
     pub filtered: Option<&'a [f64]>,
+    // Fourier series reconstruction over the raw samples
+    pub fit: Option<&'a [f64]>,
+    // Sum of the detected spectral peaks
+    pub peak_model: Option<&'a [f64]>,
+    // Upper and lower amplitude envelope around the filtered line
+    pub envelope: Option<(&'a [f64], &'a [f64])>,
+    // Raw data rebuilt from its K largest frequency bins, labelled with K
+    pub denoised: Option<(&'a [f64], usize)>,
+    // Regression trend and its equation
+    pub trend: Option<(&'a [f64], &'a str)>,
+    pub overlays: &'a [structures::dataset::NamedSeries],
+    // Dates of the raw samples, used for the x tick labels when present
+    pub dates: Option<&'a [chrono::NaiveDate]>,
+    // (samples per unit, unit) from the loaded file; undated ticks then read
+    // as time in that unit instead of a sample index
+    pub time_axis: Option<(f64, &'a str)>,
+    // Plot log10(y); non-positive samples are left out
+    pub y_log: bool,
+    // Replaces the raw/filtered legend when the view plots something else
+    pub legend: Option<&'a str>,
     pub cache: &'a Cache,
 }
 
+// Visible sample range and the mouse gesture in progress
+#[derive(Default)]
+pub struct TimeZoom {
+    // None shows every sample
+    window: Option<(usize, usize)>,
+    // Cursor x and window when a drag started
+    drag: Option<(f32, (usize, usize))>,
+    last_click: Option<iced::advanced::mouse::Click>,
+}
+
+impl TimeZoom {
+    // Visible [start, end) for a series of n samples; a stale window from
+    // longer data falls back to the whole series
+    fn range(&self, n: usize) -> (usize, usize) {
+        match self.window {
+            Some((start, end)) if start < end && end <= n => (start, end),
+            _ => (0, n),
+        }
+    }
+}
+
+// Line plot rectangle for a canvas of the given size; matches the layout in draw
+fn plot_area(size: Size) -> Rectangle {
+    let pad = 12.0_f32;
+    let panel_w = (size.width - 3.0 * pad).max(1.0);
+    let panel_h = (size.height - 2.0 * pad).max(1.0);
+    let left = pad + 40.0;
+    let right = pad + panel_w - 12.0;
+    let top = pad + 12.0;
+    let bottom = pad + panel_h - 42.0;
+    Rectangle {
+        x: left,
+        y: top,
+        width: (right - left).max(1.0),
+        height: (bottom - top).max(1.0),
+    }
+}
+
+impl TimeSeriesPlotView<'_> {
+    // Samples on the shared x axis: raw, cut to the filtered length when shorter
+    fn sample_count(&self) -> usize {
+        let n_raw = self.raw.map_or(0, <[f64]>::len);
+        match self.filtered {
+            Some(f) => n_raw.min(f.len()),
+            None => n_raw,
+        }
+    }
+}
+
 impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
-    type State = ();
+    type State = TimeZoom;
+
+    // Scroll zooms around the cursor, dragging pans a zoomed view and a
+    // double-click shows the whole series again
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: &canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        let n = self.sample_count();
+        if n < 2 {
+            return None;
+        }
+        let area = plot_area(bounds.size());
+        let pos = cursor.position_in(bounds);
+        let over_plot = pos.filter(|p| area.contains(*p));
+        let (start, end) = state.range(n);
+        let len = end - start;
+        let window = match event {
+            canvas::Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                let p = over_plot?;
+                let steps = match *delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => y / 40.0,
+                };
+                let new_len = ((len as f32 * 0.8_f32.powf(steps)).round() as usize)
+                    .clamp(MIN_ZOOM_SAMPLES.min(n), n);
+                // Keep the sample under the cursor where it is
+                let frac = ((p.x - area.x) / area.width).clamp(0.0, 1.0);
+                let anchor = start as f32 + frac * len as f32;
+                let new_start = (anchor - frac * new_len as f32).round().max(0.0) as usize;
+                let new_start = new_start.min(n - new_len);
+                Some((new_start, new_start + new_len))
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let p = over_plot?;
+                let click =
+                    iced::advanced::mouse::Click::new(p, mouse::Button::Left, state.last_click);
+                state.last_click = Some(click);
+                if click.kind() == iced::advanced::mouse::click::Kind::Double {
+                    state.drag = None;
+                    Some((0, n))
+                } else {
+                    state.drag = Some((p.x, (start, end)));
+                    return Some(canvas::Action::capture());
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                let ((x0, (s0, e0)), p) = state.drag.zip(pos)?;
+                let shift = ((x0 - p.x) / area.width * (e0 - s0) as f32).round() as isize;
+                let new_start = (s0 as isize + shift).clamp(0, (n - (e0 - s0)) as isize) as usize;
+                Some((new_start, new_start + (e0 - s0)))
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                state.drag.take()?;
+                return Some(canvas::Action::request_redraw());
+            }
+            _ => None,
+        }?;
+        let window = (window != (0, n)).then_some(window);
+        if window != state.window {
+            state.window = window;
+            self.cache.clear();
+        }
+        Some(canvas::Action::request_redraw().and_capture())
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        let over_plot = cursor
+            .position_in(bounds)
+            .is_some_and(|p| plot_area(bounds.size()).contains(p));
+        match (state.drag, state.window) {
+            (Some(_), _) => mouse::Interaction::Grabbing,
+            (None, Some(_)) if over_plot => mouse::Interaction::Grab,
+            _ => mouse::Interaction::default(),
+        }
+    }
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         renderer: &Renderer,
         _theme: &Theme,
         bounds: Rectangle,
@@ -73,7 +225,7 @@ impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
             let left = panel_x + 40.0;
             let right = panel_x + panel_w - 12.0;
             let top = panel_y + 12.0;
-            let bottom = panel_y + panel_h - 28.0;
+            let bottom = panel_y + panel_h - 42.0;
 
             let plot_w = (right - left).max(1.0);
             let plot_h = (bottom - top).max(1.0);
@@ -96,25 +248,54 @@ impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
                 }
             };
 
-            let n_raw = raw.len();
+            // Everything below works on the visible window, so n counts its samples
+            let n_total = self.sample_count();
+            let (start, end) = state.range(n_total);
+            let n = end - start;
+            let visible = |s: &'a [f64]| &s[start.min(s.len())..end.min(s.len())];
+            // Overlays span the full width at their own length, so window them proportionally
+            let visible_overlay = |s: &'a [f64]| {
+                let total = n_total.max(1);
+                &s[start * s.len() / total..end * s.len() / total]
+            };
 
-            let n = match self.filtered {
-                Some(f) => n_raw.min(f.len()),
-                None => n_raw,
+            // Value in axis units; NaN for what a log axis can't show
+            let to_axis = |y: f64| -> f64 {
+                if !self.y_log {
+                    y
+                } else if y > 0.0 {
+                    y.log10()
+                } else {
+                    f64::NAN
+                }
             };
 
             // Y range from both series (raw + filtered if present)
             let mut ymin = f64::INFINITY;
             let mut ymax = f64::NEG_INFINITY;
 
-            for &y in &raw[..n] {
+            for y in visible(raw).iter().map(|&y| to_axis(y)) {
                 if y.is_finite() {
                     ymin = ymin.min(y);
                     ymax = ymax.max(y);
                 }
             }
-            if let Some(f) = self.filtered {
-                for &y in &f[..n] {
+            let (upper, lower) = self.envelope.unzip();
+            let trend = self.trend.map(|(t, _)| t);
+            let denoised = self.denoised.map(|(d, _)| d);
+            for f in [
+                self.filtered,
+                self.fit,
+                self.peak_model,
+                upper,
+                lower,
+                trend,
+                denoised,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                for y in visible(f).iter().map(|&y| to_axis(y)) {
                     if y.is_finite() {
                         ymin = ymin.min(y);
                         ymax = ymax.max(y);
@@ -122,6 +303,18 @@ impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
                 }
             }
 
+            for y in self
+                .overlays
+                .iter()
+                .flat_map(|o| visible_overlay(o.display_data()))
+                .map(|&y| to_axis(y))
+            {
+                if y.is_finite() {
+                    ymin = ymin.min(y);
+                    ymax = ymax.max(y);
+                }
+            }
+
             if !ymin.is_finite() || !ymax.is_finite() {
                 return;
             }
@@ -138,9 +331,8 @@ impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
             ymin -= pad_y;
             ymax += pad_y;
 
-            let map_x = |i: usize| -> f32 { left + (i as f32) * (plot_w / ((n - 1) as f32)) };
             let map_y = |y: f64| -> f32 {
-                let t = ((y - ymin) / (ymax - ymin)) as f32;
+                let t = ((to_axis(y) - ymin) / (ymax - ymin)) as f32;
                 bottom - t * plot_h
             };
 
@@ -179,7 +371,11 @@ impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
             let y_mid = 0.5 * (ymin + ymax);
             for (val, yy) in [(ymax, top), (y_mid, (top + bottom) * 0.5), (ymin, bottom)] {
                 frame.fill_text(Text {
-                    content: format!("{val:.1}"),
+                    content: if self.y_log {
+                        fmt_tick(10f64.powf(val))
+                    } else {
+                        format!("{val:.1}")
+                    },
                     position: Point::new(panel_x + 6.0, yy - 6.0),
                     color: label_color,
                     size: size.into(),
@@ -187,25 +383,49 @@ impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
                 });
             }
 
+            // x ticks at the vertical grid lines, anchored inward at the ends
+            for k in 0..=4 {
+                let t = k as f32 / 4.0;
+                let i = start + (t * (n.max(1) - 1) as f32).round() as usize;
+                let content = match (self.dates, self.time_axis) {
+                    (None, Some((fs, _))) => fmt_tick(i as f64 / fs),
+                    _ => fmt_x_tick(self.dates, i),
+                };
+                frame.fill_text(Text {
+                    content,
+                    position: Point::new(left + t * plot_w, bottom + 4.0),
+                    color: label_color,
+                    size: 11.0.into(),
+                    align_x: match k {
+                        0 => iced::widget::text::Alignment::Left,
+                        4 => iced::widget::text::Alignment::Right,
+                        _ => iced::widget::text::Alignment::Center,
+                    },
+                    ..Text::default()
+                });
+            }
+
+            if let (None, Some((_, unit))) = (self.dates, self.time_axis) {
+                frame.fill_text(Text {
+                    content: format!("time ({unit})"),
+                    position: Point::new(right, bottom + 22.0),
+                    color: label_color,
+                    size: 12.0.into(),
+                    align_x: iced::widget::text::Alignment::Right,
+                    ..Text::default()
+                });
+            }
+
+            // Pixel width a series covers when sample i sits at map_x(i)
+            let span = |len: usize| plot_w * (len.max(1) - 1) as f32 / (n.max(2) - 1) as f32;
+
             // draw raw line
             let raw_stroke = Stroke {
                 width: 2.0,
                 style: Style::Solid(Color::from_rgb8(0x00, 0x66, 0xCC)),
                 ..Stroke::default()
             };
-
-            let mut prev = None;
-            for (i, &y) in raw.iter().enumerate().take(n) {
-                if !y.is_finite() {
-                    prev = None;
-                    continue;
-                }
-                let p = Point::new(map_x(i), map_y(y));
-                if let Some(q) = prev {
-                    frame.stroke(&Path::line(q, p), raw_stroke);
-                }
-                prev = Some(p);
-            }
+            frame.stroke(&trace(visible(raw), left, span(n), &map_y), raw_stroke);
 
             // draw filtered line (if available)
             if let Some(f) = self.filtered {
@@ -214,29 +434,154 @@ impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
                     style: Style::Solid(Color::from_rgb8(0xCC, 0x00, 0x00)),
                     ..Stroke::default()
                 };
+                let f = visible(f);
+                frame.stroke(&trace(f, left, span(f.len()), &map_y), filt_stroke);
+            }
 
-                let mut prev = None;
-                for (i, &y) in f.iter().enumerate().take(n) {
-                    if !y.is_finite() {
-                        prev = None;
-                        continue;
-                    }
-                    let p = Point::new(map_x(i), map_y(y));
-                    if let Some(q) = prev {
-                        frame.stroke(&Path::line(q, p), filt_stroke);
-                    }
-                    prev = Some(p);
+            // peak model
+            if let Some(m) = self.peak_model {
+                let model_stroke = Stroke {
+                    width: 1.5,
+                    style: Style::Solid(Color::from_rgb8(0xE0, 0x9A, 0x1A)),
+                    ..Stroke::default()
+                };
+                let m = visible(m);
+                frame.stroke(&trace(m, left, span(m.len()), &map_y), model_stroke);
+            }
+
+            if let Some((upper, lower)) = self.envelope {
+                let envelope_stroke = Stroke {
+                    width: 1.0,
+                    style: Style::Solid(Color::from_rgb8(0xFF, 0x6F, 0xD8)),
+                    ..Stroke::default()
+                };
+                for e in [upper, lower] {
+                    let e = visible(e);
+                    frame.stroke(&trace(e, left, span(e.len()), &map_y), envelope_stroke);
                 }
             }
 
+            // Fourier fit, dashed so it reads apart from the filtered line
+            if let Some(f) = self.fit {
+                let f = visible(f);
+                frame.stroke(
+                    &trace(f, left, span(f.len()), &map_y),
+                    Stroke {
+                        width: 2.0,
+                        style: Style::Solid(Color::from_rgb8(0x22, 0xAA, 0x55)),
+                        line_dash: canvas::LineDash {
+                            segments: &[6.0, 4.0],
+                            offset: 0,
+                        },
+                        ..Stroke::default()
+                    },
+                );
+            }
+
+            if let Some((d, _)) = self.denoised {
+                let d = visible(d);
+                frame.stroke(
+                    &trace(d, left, span(d.len()), &map_y),
+                    Stroke {
+                        width: 1.5,
+                        style: Style::Solid(Color::from_rgb8(0x1A, 0xC8, 0xB4)),
+                        ..Stroke::default()
+                    },
+                );
+            }
+
+            // Regression trend, dashed white with its equation in the corner
+            if let Some((t, equation)) = self.trend {
+                let t = visible(t);
+                frame.stroke(
+                    &trace(t, left, span(t.len()), &map_y),
+                    Stroke {
+                        width: 1.5,
+                        style: Style::Solid(Color::from_rgb8(0xF0, 0xF0, 0xF0)),
+                        line_dash: canvas::LineDash {
+                            segments: &[3.0, 3.0],
+                            offset: 0,
+                        },
+                        ..Stroke::default()
+                    },
+                );
+                frame.fill_text(Text {
+                    content: equation.into(),
+                    position: Point::new(left + 6.0, top + 4.0),
+                    color: label_color,
+                    size: 12.0.into(),
+                    ..Text::default()
+                });
+            }
+
+            // overlay series, each stretched over its own x-extent
+            for (k, series) in self.overlays.iter().enumerate() {
+                let ys = visible_overlay(series.display_data());
+                if ys.len() < 2 {
+                    continue;
+                }
+                let color = series_color(k);
+                let stroke = Stroke {
+                    width: 1.5,
+                    style: Style::Solid(Color { a: 0.85, ..color }),
+                    ..Stroke::default()
+                };
+                frame.stroke(&trace(ys, left, plot_w, &map_y), stroke);
+                frame.fill_text(Text {
+                    content: series.name.clone(),
+                    position: Point::new(right - 120.0, top + 4.0 + 14.0 * k as f32),
+                    color,
+                    size: 12.0.into(),
+                    ..Text::default()
+                });
+            }
+
             // legend
             frame.fill_text(Text {
-                content: if self.filtered.is_some() {
-                    "raw (blue) / filtered (red)".into()
-                } else {
-                    "raw (blue)".into()
+                content: {
+                    let base = match (self.legend, self.filtered) {
+                        (Some(l), _) => l,
+                        (None, Some(_)) => "raw (blue) / filtered (red)",
+                        (None, None) => "raw (blue)",
+                    };
+                    let mut content = String::from(base);
+                    if self.peak_model.is_some() {
+                        content.push_str(" / peak model (orange)");
+                    }
+                    if self.fit.is_some() {
+                        content.push_str(" / Fourier fit (green, dashed)");
+                    }
+                    if self.envelope.is_some() {
+                        content.push_str(" / envelope (pink)");
+                    }
+                    if let Some((_, k)) = self.denoised {
+                        content.push_str(&format!(" / top {k} bins (teal)"));
+                    }
+                    if self.trend.is_some() {
+                        content.push_str(" / trend (white, dashed)");
+                    }
+                    if state.window.is_some() {
+                        content.push_str(&format!(
+                            " / samples {start}\u{2013}{} (double-click to reset)",
+                            end - 1
+                        ));
+                    }
+                    if self.y_log {
+                        let skipped = [Some(raw), self.filtered]
+                            .into_iter()
+                            .flatten()
+                            .flat_map(visible)
+                            .filter(|&&y| y <= 0.0)
+                            .count();
+                        if skipped > 0 {
+                            content.push_str(&format!(
+                                " / {skipped} non-positive sample(s) skipped (log y)"
+                            ));
+                        }
+                    }
+                    content
                 },
-                position: Point::new(left, bottom + 8.0),
+                position: Point::new(left, bottom + 22.0),
                 color: label_color,
                 size: 12.0.into(),
                 ..Text::default()
@@ -246,3 +591,62 @@ impl<'a> canvas::Program<Message> for TimeSeriesPlotView<'a> {
         vec![geom]
     }
 }
+
+// Per-column (min, max) of `data` over `target_px` columns, so a long series
+// draws as an envelope instead of one segment per sample. Columns with no
+// finite sample come back as NaN.
+pub fn decimate_for_width(data: &[f64], target_px: usize) -> Vec<(f64, f64)> {
+    let cols = target_px.min(data.len());
+    (0..cols)
+        .map(|c| {
+            let bucket = &data[c * data.len() / cols..(c + 1) * data.len() / cols];
+            let (lo, hi) = bucket
+                .iter()
+                .filter(|y| y.is_finite())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &y| {
+                    (lo.min(y), hi.max(y))
+                });
+            if lo <= hi {
+                (lo, hi)
+            } else {
+                (f64::NAN, f64::NAN)
+            }
+        })
+        .collect()
+}
+
+// Polyline of `ys` spread over `width` pixels from `left`, broken wherever
+// map_y gives a non-finite pixel (non-finite samples, or ones a log axis skips). Beyond one sample per pixel it follows the min/max
+// envelope of each column instead.
+fn trace(ys: &[f64], left: f32, width: f32, map_y: &impl Fn(f64) -> f32) -> Path {
+    let mut builder = canvas::path::Builder::new();
+    if ys.len() < 2 {
+        return builder.build();
+    }
+    let mut pen_down = false;
+    let mut visit = |p: Point| {
+        if !p.y.is_finite() {
+            pen_down = false;
+        } else if pen_down {
+            builder.line_to(p);
+        } else {
+            builder.move_to(p);
+            pen_down = true;
+        }
+    };
+    if ys.len() as f32 > width {
+        let cols = decimate_for_width(ys, (width as usize).max(2));
+        let step = width / (cols.len() - 1) as f32;
+        for (c, &(lo, hi)) in cols.iter().enumerate() {
+            let x = left + c as f32 * step;
+            visit(Point::new(x, map_y(lo)));
+            visit(Point::new(x, map_y(hi)));
+        }
+    } else {
+        let step = width / (ys.len() - 1) as f32;
+        for (i, &y) in ys.iter().enumerate() {
+            visit(Point::new(left + i as f32 * step, map_y(y)));
+        }
+    }
+    builder.build()
+}